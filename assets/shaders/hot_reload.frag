@@ -0,0 +1,10 @@
+#version 450
+
+layout(location = 0) in vec4 v_colour;
+
+layout(location = 0) out vec4 f_color;
+
+// edit this file while the hot_reload example is running
+void main() {
+    f_color = v_colour;
+}
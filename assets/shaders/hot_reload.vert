@@ -0,0 +1,17 @@
+#version 450
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec4 colour;
+
+layout(location = 0) out vec4 v_colour;
+
+layout(set = 0, binding = 0) uniform Data {
+    mat4 world;
+    mat4 view;
+    mat4 proj;
+} uniforms;
+
+void main() {
+    gl_Position = uniforms.proj * uniforms.view * uniforms.world * vec4(position, 1.0);
+    v_colour = colour;
+}
@@ -1,28 +1,29 @@
 use rust_vulkan_graphics::*;
 
-fn main() {
+fn main() -> Result<(), Error> {
     let (context, command_allocator, descript_allocator) = get_general_compute_data();
-    let data_buffer = create_shader_data_buffer(0..65536u32, &context, BufferType::Storage);
+    let data_buffer = create_shader_data_buffer(0..65536u32, &context, BufferType::Storage)?;
     const MULTIPLIER: u32 = 4;
 
-    let shader = cs::load(context.device().clone()).unwrap();
+    let shader = cs::load(context.device().clone())?;
 
-    let pipeline = create_compute_pipeline(&context, &shader, "main");
+    let pipeline = create_compute_pipeline(&context, &shader, "main")?;
 
-    let descriptor_set = get_descriptor_set(&pipeline, &descript_allocator, 0, [WriteDescriptorSet::buffer(0, data_buffer.clone())]);
+    let descriptor_set = get_descriptor_set(&pipeline, &descript_allocator, 0, [WriteDescriptorSet::buffer(0, data_buffer.clone())])?;
 
     let push_constants = cs::PushConstantData {
         multiple: MULTIPLIER
     };
 
-    run_compute_operation_push_constants(&context, &command_allocator, &pipeline, [65536, 1, 1], [64, 1, 1], descriptor_set, push_constants, None);
+    run_compute_operation_push_constants(&context, &command_allocator, &pipeline, [65536, 1, 1], [64, 1, 1], descriptor_set, push_constants, None)?;
 
-    let content = data_buffer.read().unwrap();
+    let content = data_buffer.read()?;
     for (n, val) in content.iter().enumerate() {
         assert_eq!(*val, n as u32 * MULTIPLIER);
     }
 
     println!("Everything succeeded!");
+    Ok(())
 }
 
 mod cs {
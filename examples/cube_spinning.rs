@@ -46,25 +46,20 @@ mod fs {
 }
 
 
-fn main() {
+fn main() -> Result<(), Error> {
 
-    let (mut event_loop, vulkano_context, mut vulkano_windows, window_ids, commands_allocator, descriptor_set_allocator) = get_general_graphics_data(vec![("Cube".to_string(), 750.0, 500.0, false), ("".to_string(), 300.0, 500.0, false)], gen_swapchain_func!(Format::B8G8R8A8_SRGB));
+    let (mut event_loop, vulkano_context, mut vulkano_windows, window_ids, commands_allocator, descriptor_set_allocator) = get_general_graphics_data(vec![("Cube".to_string(), 750.0, 500.0, false)], gen_swapchain_func!(Format::B8G8R8A8_SRGB));
     let uniform_allocator = create_uniform_buffer_allocator(vulkano_context.memory_allocator());
-    let mut gui = vec![create_gui_window(
-        "Cube Spinning Settings".to_string(),
-        vec![("Enable Spinning".to_string(), true)],
-        vec![("Spin Speed".to_string(), 0.5, -5.0..=5.0)],
-        Vec::new(),
-        Vec::new(),
-        Vec::new(),
-        Vec::new(),
-        Vec::new(),
-
-        &mut vulkano_windows, window_ids[1], &event_loop
-    )];
-
-    let vertex_buffer = create_shader_data_buffer(test_cube::COLOURED_VERTICES, &vulkano_context, BufferType::Vertex);
-    let index_buffer = create_shader_data_buffer(test_cube::INDICES, &vulkano_context, BufferType::Index);
+    let mut gui = vec![
+        GuiWindowBuilder::new("Cube Spinning Settings")
+            .overlay()
+            .checkbox("Enable Spinning", true)
+            .f32_slider("Spin Speed", 0.5, -5.0..=5.0)
+            .build(&mut vulkano_windows, window_ids[0], &event_loop)?
+    ];
+
+    let vertex_buffer = create_shader_data_buffer(test_cube::COLOURED_VERTICES, &vulkano_context, BufferType::Vertex)?;
+    let index_buffer = create_shader_data_buffer(test_cube::INDICES, &vulkano_context, BufferType::Index)?;
 
     let mut camera = Camera::new(Some([-2.0, 0.0, 0.0]), None, Some(10.0), None);
 
@@ -72,10 +67,9 @@ fn main() {
     let mut cube_rotation = 0.0;
 
     let cube_window_id = window_ids[0];
-    let gui_window_id = window_ids[1];
 
-    let vs = vs::load(vulkano_context.device().clone()).unwrap();
-    let fs = fs::load(vulkano_context.device().clone()).unwrap();
+    let vs = vs::load(vulkano_context.device().clone())?;
+    let fs = fs::load(vulkano_context.device().clone())?;
 
     let mut cube_render_pipeline = MultiSamplePipeline3D::new(
         &vulkano_context,
@@ -85,7 +79,7 @@ fn main() {
         &fs,
         &vertex_defs::coloured(),
         Some(SampleCount::Sample4),
-    );
+    )?;
 
     loop {
         if !generic_winit_event_handling_with_camera(&mut event_loop, &mut vulkano_windows, &mut gui, (&mut camera, &cube_window_id)) {break;}
@@ -94,13 +88,15 @@ fn main() {
         if frame_time > 1.0 / 60.0 {
             last_frame_time = Instant::now();
 
-            attempt_gui_redraw(&mut gui[0], &mut vulkano_windows, gui_window_id);
-
-            if gui[0].checkboxes[0].1 {
-                cube_rotation += frame_time * gui[0].f32_sliders[0].1;
+            if gui[0].get_bool("Enable Spinning")? {
+                cube_rotation += frame_time * gui[0].get_f32("Spin Speed")?;
             }
 
-            draw_cube(vulkano_windows.get_renderer_mut(cube_window_id).unwrap(), cube_rotation, &vertex_buffer, &index_buffer, &mut cube_render_pipeline, &camera, &uniform_allocator);
+            let renderer = vulkano_windows.get_renderer_mut(cube_window_id).ok_or(Error::MissingWindow(cube_window_id))?;
+            if let Err(err) = draw_cube(renderer, &mut gui[0], cube_rotation, &vertex_buffer, &index_buffer, &mut cube_render_pipeline, &camera, &uniform_allocator) {
+                // the renderer recreates its swapchain on the next acquire, so the frame can just be skipped
+                if !err.is_out_of_date() {return Err(err);}
+            }
 
 
             camera.do_move(frame_time);
@@ -108,21 +104,26 @@ fn main() {
 
     }
 
+    Ok(())
 }
 
 fn draw_cube(
     renderer: &mut VulkanoWindowRenderer,
+    gui: &mut GuiWindowData,
     cube_rotation: f32,
     vertex_buffer: &Subbuffer<[ColouredVertex]>,
     index_buffer: &Subbuffer<[u32]>,
     pipeline: &mut MultiSamplePipeline3D, 
     camera: &Camera,
     uniform_allocator: &SubbufferAllocator,
-) {
-    let uniforms = get_uniform_subbuffer(cube_rotation, renderer.swapchain_image_size(), uniform_allocator, camera);
-    let before_future = renderer.acquire().unwrap();
-    let after_future = pipeline.draw_from_vertices(before_future, renderer.swapchain_image_view(), vertex_buffer, index_buffer, &uniforms);
+) -> Result<(), Error> {
+    let uniforms = get_uniform_subbuffer(cube_rotation, renderer.swapchain_image_size(), uniform_allocator, camera)?;
+    let before_future = renderer.acquire()?;
+    let cube_future = pipeline.draw_from_vertices(before_future, renderer.swapchain_image_view(), vertex_buffer, index_buffer, &uniforms)?;
+    // the settings are drawn over the cube, in the same frame
+    let after_future = draw_gui_overlay(gui, cube_future, renderer);
     renderer.present(after_future, true);
+    Ok(())
 }
 
 
@@ -131,7 +132,7 @@ fn get_uniform_subbuffer (
     swapchain_size: [u32; 2],
     allocator: &SubbufferAllocator,
     camera: &Camera
-) -> Subbuffer<vs::Data> {
+) -> Result<Subbuffer<vs::Data>, Error> {
 
     let rotation_mat = Matrix3::from_angle_y(rotation);
 
@@ -144,8 +145,8 @@ fn get_uniform_subbuffer (
         proj: proj.into(),
     };
 
-    let subbuffer = allocator.allocate_sized().unwrap();
-    *subbuffer.write().unwrap() = uniform_data;
-    subbuffer
+    let subbuffer = allocator.allocate_sized()?;
+    *subbuffer.write()? = uniform_data;
+    Ok(subbuffer)
 
 }
\ No newline at end of file
@@ -3,22 +3,30 @@ use std::time::Instant;
 use rust_vulkan_graphics::*;
 // use std::time::Instant;
 
-fn main() {
+fn main() -> Result<(), Error> {
 
     let (mut event_loop, _vulkano_contex, mut vulkano_windows, window_ids, _, _) = get_general_graphics_data(vec![("".to_string(), 300.0, 500.0, false)], gen_swapchain_func!(Format::B8G8R8A8_SRGB));
     let mut gui = vec![
-        create_gui_window(
-            "Example Gui".to_string(),
-            vec![("Do nothing".to_string(), false), ("Do something (lie)".to_string(), false)],
-            vec![("Random Slider".to_string(), 0.5, 0.0..=1.0)],
-            vec![("Random int Slider".to_string(), 5, 0..=15)],
-            Vec::new(),
-            Vec::new(),
-            vec![("Unsigned int setting".to_string(), 5)],
-            vec![("Name Field".to_string(), "Mr Testing".to_string())],
-
-            &mut vulkano_windows, window_ids[0], &event_loop
-        )
+        GuiWindowBuilder::new("Example Gui")
+            .section("Checkboxes")
+            .checkbox("Do nothing", false)
+            .checkbox("Do something (lie)", false)
+            .section("Sliders")
+            .f32_slider("Random Slider", 0.5, 0.0..=1.0)
+            .i32_slider("Random int Slider", 5, 0..=15)
+            .section("Data Boxes")
+            .u32_box("Unsigned int setting", 5)
+            .text_box("Name Field", "Mr Testing")
+            .section("Everything Else")
+            .button("Say Hello")
+            .rgb("Favourite Colour", [0.2, 0.6, 1.0])
+            .combo("Shape", ["Cube", "Sphere", "Torus"], 0)
+            .group("Advanced", false, |group| group
+                .vec3("Offset", [0.0, 1.0, 0.0])
+                .rgba("Tint", [1.0, 1.0, 1.0, 0.5])
+            )
+            .plot("Frame Time (ms)", 120)
+            .build(&mut vulkano_windows, window_ids[0], &event_loop)?
     ];
 
     let mut last_frame_time = Instant::now();
@@ -29,7 +37,13 @@ fn main() {
         let frame_time = last_frame_time.elapsed().as_secs_f32();
         if frame_time > 1.0 / 60.0 {
             last_frame_time = Instant::now();
-            attempt_gui_redraw(&mut gui[0], &mut vulkano_windows, window_ids[0]);
+            gui[0].push_plot("Frame Time (ms)", frame_time * 1000.0)?;
+            if gui[0].take_click("Say Hello")? {
+                println!("Hello {}, you picked a {}", gui[0].get_string("Name Field")?, gui[0].get_selected_name("Shape")?);
+            }
+            attempt_gui_redraw(&mut gui[0], &mut vulkano_windows, window_ids[0])?;
         }
     }
+
+    Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,87 @@
+use rust_vulkan_graphics::*;
+use maths::{Matrix3, Matrix4};
+use vulkano::sync::{self, GpuFuture};
+
+mod vs {
+    rust_vulkan_graphics::shader!{
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+            layout(location = 1) in vec4 colour;
+
+            layout(location = 0) out vec4 v_colour;
+
+            layout(set = 0, binding = 0) uniform Data {
+                mat4 world;
+                mat4 view;
+                mat4 proj;
+            } uniforms;
+
+            void main() {
+                gl_Position = uniforms.proj * uniforms.view * uniforms.world * vec4(position, 1.0);
+                v_colour = colour;
+            }
+        ",
+    }
+}
+
+mod fs {
+    rust_vulkan_graphics::shader!{
+        ty: "fragment",
+        src: r"
+            #version 460
+
+            layout(location = 0) in vec4 v_colour;
+
+            layout(location = 0) out vec4 f_color;
+
+            void main() {
+                f_color = v_colour;
+            }
+        ",
+
+    }
+}
+
+
+fn main() -> Result<(), Error> {
+
+    let (vulkano_context, commands_allocator, descriptor_set_allocator) = get_headless_graphics_data();
+    let uniform_allocator = create_uniform_buffer_allocator(vulkano_context.memory_allocator());
+
+    let vertex_buffer = create_shader_data_buffer(test_cube::COLOURED_VERTICES, &vulkano_context, BufferType::Vertex)?;
+    let index_buffer = create_shader_data_buffer(test_cube::INDICES, &vulkano_context, BufferType::Index)?;
+
+    let camera = Camera::new(Some([-2.0, 0.0, 0.0]), None, None, None);
+    let target = OffscreenTarget::new(&vulkano_context, &commands_allocator, [512, 512], Format::B8G8R8A8_SRGB)?;
+
+    let vs = vs::load(vulkano_context.device().clone())?;
+    let fs = fs::load(vulkano_context.device().clone())?;
+
+    let mut pipeline = MultiSamplePipeline3D::new(
+        &vulkano_context,
+        &commands_allocator,
+        &descriptor_set_allocator,
+        &vs,
+        &fs,
+        &vertex_defs::coloured(),
+        Some(SampleCount::Sample4),
+    )?;
+
+    let (view, proj) = get_generic_uniforms(target.dimensions(), &camera);
+    let uniforms = uniform_allocator.allocate_sized()?;
+    *uniforms.write()? = vs::Data {
+        world: Matrix4::from(Matrix3::from_angle_y(0.5)).into(),
+        view: view.into(),
+        proj: proj.into(),
+    };
+
+    let before_future = sync::now(vulkano_context.device().clone()).boxed();
+    let after_future = pipeline.draw_from_vertices(before_future, target.view(), &vertex_buffer, &index_buffer, &uniforms)?;
+    target.save_png(after_future, "cube.png")?;
+
+    println!("Saved cube.png");
+    Ok(())
+}
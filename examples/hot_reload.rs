@@ -0,0 +1,96 @@
+use std::time::Instant;
+use rust_vulkan_graphics::*;
+use maths::{Matrix3, Matrix4};
+use bytemuck::{Pod, Zeroable};
+
+/// matches the uniform block in assets/shaders/hot_reload.vert
+#[repr(C)]
+#[derive(Clone, Copy, Zeroable, Pod)]
+struct Uniforms {
+    world: [[f32; 4]; 4],
+    view: [[f32; 4]; 4],
+    proj: [[f32; 4]; 4],
+}
+
+
+fn main() -> Result<(), Error> {
+
+    let (mut event_loop, vulkano_context, mut vulkano_windows, window_ids, commands_allocator, descriptor_set_allocator) = get_general_graphics_data(vec![("Hot Reload Example".to_string(), 750.0, 500.0, false)], gen_swapchain_func!(Format::B8G8R8A8_SRGB));
+    let uniform_allocator = create_uniform_buffer_allocator(vulkano_context.memory_allocator());
+    let mut gui = Vec::new();
+
+    let vertex_buffer = create_shader_data_buffer(test_cube::COLOURED_VERTICES, &vulkano_context, BufferType::Vertex)?;
+    let index_buffer = create_shader_data_buffer(test_cube::INDICES, &vulkano_context, BufferType::Index)?;
+
+    let mut camera = Camera::new(Some([-2.0, 0.0, 0.0]), None, Some(10.0), None);
+
+    let mut last_frame_time = Instant::now();
+    let mut rotation = 0.0;
+
+    let window_id = window_ids[0];
+
+    let mut vs = ShaderFile::load(vulkano_context.device(), "assets/shaders/hot_reload.vert")?;
+    let mut fs = ShaderFile::load(vulkano_context.device(), "assets/shaders/hot_reload.frag")?;
+
+    let mut render_pipeline = MultiSamplePipeline3D::new(
+        &vulkano_context,
+        &commands_allocator,
+        &descriptor_set_allocator,
+        vs.module(),
+        fs.module(),
+        &vertex_defs::coloured(),
+        Some(SampleCount::Sample4),
+    )?;
+
+    loop {
+        if !generic_winit_event_handling_with_camera(&mut event_loop, &mut vulkano_windows, &mut gui, (&mut camera, &window_id)) {break;}
+
+        let frame_time = last_frame_time.elapsed().as_secs_f32();
+        if frame_time > 1.0 / 60.0 {
+            last_frame_time = Instant::now();
+
+            // a broken shader is reported and the last working one kept until the file is fixed
+            match render_pipeline.hot_reload(&mut vs, &mut fs) {
+                Ok(true) => println!("Reloaded shaders"),
+                Ok(false) => {},
+                Err(err) => eprintln!("{}", err),
+            }
+
+            rotation += frame_time * 0.5;
+
+            let renderer = vulkano_windows.get_renderer_mut(window_id).ok_or(Error::MissingWindow(window_id))?;
+            if let Err(err) = draw(renderer, rotation, &vertex_buffer, &index_buffer, &mut render_pipeline, &camera, &uniform_allocator) {
+                // the renderer recreates its swapchain on the next acquire, so the frame can just be skipped
+                if !err.is_out_of_date() {return Err(err);}
+            }
+
+            camera.do_move(frame_time);
+        }
+
+    }
+
+    Ok(())
+}
+
+fn draw(
+    renderer: &mut VulkanoWindowRenderer,
+    rotation: f32,
+    vertex_buffer: &Subbuffer<[ColouredVertex]>,
+    index_buffer: &Subbuffer<[u32]>,
+    pipeline: &mut MultiSamplePipeline3D,
+    camera: &Camera,
+    uniform_allocator: &SubbufferAllocator,
+) -> Result<(), Error> {
+    let (view, proj) = get_generic_uniforms(renderer.swapchain_image_size(), camera);
+    let uniforms = uniform_allocator.allocate_sized()?;
+    *uniforms.write()? = Uniforms {
+        world: Matrix4::from(Matrix3::from_angle_y(rotation)).into(),
+        view: view.into(),
+        proj: proj.into(),
+    };
+
+    let before_future = renderer.acquire()?;
+    let after_future = pipeline.draw_from_vertices(before_future, renderer.swapchain_image_view(), vertex_buffer, index_buffer, &uniforms)?;
+    renderer.present(after_future, true);
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use std::time::Instant;
+use rust_vulkan_graphics::*;
+use maths::{Matrix3, Matrix4};
+
+mod vs {
+    rust_vulkan_graphics::shader!{
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+            layout(location = 1) in vec4 colour;
+            layout(location = 2) in mat4 model;
+
+            layout(location = 0) out vec4 v_colour;
+
+            layout(set = 0, binding = 0) uniform Data {
+                mat4 world;
+                mat4 view;
+                mat4 proj;
+            } uniforms;
+
+            void main() {
+                gl_Position = uniforms.proj * uniforms.view * model * uniforms.world * vec4(position, 1.0);
+                v_colour = colour;
+            }
+        ",
+    }
+}
+
+mod fs {
+    rust_vulkan_graphics::shader!{
+        ty: "fragment",
+        src: r"
+            #version 460
+
+            layout(location = 0) in vec4 v_colour;
+
+            layout(location = 0) out vec4 f_color;
+
+            void main() {
+                f_color = v_colour;
+            }
+        ",
+
+    }
+}
+
+const GRID_SIZE: u32 = 20;
+const SPACING: f32 = 3.0;
+
+
+fn main() -> Result<(), Error> {
+
+    let (mut event_loop, vulkano_context, mut vulkano_windows, window_ids, commands_allocator, descriptor_set_allocator) = get_general_graphics_data(vec![("Instancing Example".to_string(), 750.0, 500.0, false)], gen_swapchain_func!(Format::B8G8R8A8_SRGB));
+    let uniform_allocator = create_uniform_buffer_allocator(vulkano_context.memory_allocator());
+    let mut gui = Vec::new();
+
+    let vertex_buffer = create_shader_data_buffer(test_cube::COLOURED_VERTICES, &vulkano_context, BufferType::Vertex)?;
+    let index_buffer = create_shader_data_buffer(test_cube::INDICES, &vulkano_context, BufferType::Index)?;
+
+    // a grid of cubes, each instance only holds its own translation
+    let mut instances = Vec::new();
+    for x in 0..GRID_SIZE {
+        for y in 0..GRID_SIZE {
+            for z in 0..GRID_SIZE {
+                instances.push(InstanceModel {
+                    model: [
+                        [1.0, 0.0, 0.0, 0.0],
+                        [0.0, 1.0, 0.0, 0.0],
+                        [0.0, 0.0, 1.0, 0.0],
+                        [x as f32 * SPACING, y as f32 * SPACING, z as f32 * SPACING, 1.0],
+                    ],
+                });
+            }
+        }
+    }
+    let instance_count = instances.len() as u32;
+    let instance_buffer = create_shader_data_buffer(instances, &vulkano_context, BufferType::Vertex)?;
+
+    let mut camera = Camera::new(Some([-10.0, 10.0, -10.0]), Some([1.0, 0.0, 1.0]), Some(10.0), None);
+
+    let mut last_frame_time = Instant::now();
+    let mut rotation = 0.0;
+
+    let window_id = window_ids[0];
+
+    let vs = vs::load(vulkano_context.device().clone())?;
+    let fs = fs::load(vulkano_context.device().clone())?;
+
+    let mut render_pipeline = MultiSamplePipeline3D::new(
+        &vulkano_context,
+        &commands_allocator,
+        &descriptor_set_allocator,
+        &vs,
+        &fs,
+        &vertex_defs::coloured_instanced(),
+        Some(SampleCount::Sample4),
+    )?;
+
+    loop {
+        if !generic_winit_event_handling_with_camera(&mut event_loop, &mut vulkano_windows, &mut gui, (&mut camera, &window_id)) {break;}
+
+        let frame_time = last_frame_time.elapsed().as_secs_f32();
+        if frame_time > 1.0 / 60.0 {
+            last_frame_time = Instant::now();
+
+            rotation += frame_time;
+
+            let renderer = vulkano_windows.get_renderer_mut(window_id).ok_or(Error::MissingWindow(window_id))?;
+            if let Err(err) = draw(renderer, rotation, &vertex_buffer, &instance_buffer, instance_count, &index_buffer, &mut render_pipeline, &camera, &uniform_allocator) {
+                // the renderer recreates its swapchain on the next acquire, so the frame can just be skipped
+                if !err.is_out_of_date() {return Err(err);}
+            }
+
+            camera.do_move(frame_time);
+        }
+
+    }
+
+    Ok(())
+}
+
+fn draw(
+    renderer: &mut VulkanoWindowRenderer,
+    rotation: f32,
+    vertex_buffer: &Subbuffer<[ColouredVertex]>,
+    instance_buffer: &Subbuffer<[InstanceModel]>,
+    instance_count: u32,
+    index_buffer: &Subbuffer<[u32]>,
+    pipeline: &mut MultiSamplePipeline3D,
+    camera: &Camera,
+    uniform_allocator: &SubbufferAllocator,
+) -> Result<(), Error> {
+    let uniforms = get_uniform_subbuffer(rotation, renderer.swapchain_image_size(), uniform_allocator, camera)?;
+    let before_future = renderer.acquire()?;
+    let after_future = pipeline.draw_instanced_from_vertices(before_future, renderer.swapchain_image_view(), vertex_buffer, instance_buffer, instance_count, index_buffer, &uniforms)?;
+    renderer.present(after_future, true);
+    Ok(())
+}
+
+
+fn get_uniform_subbuffer (
+    rotation: f32,
+    swapchain_size: [u32; 2],
+    allocator: &SubbufferAllocator,
+    camera: &Camera
+) -> Result<Subbuffer<vs::Data>, Error> {
+
+    let rotation_mat = Matrix3::from_angle_y(rotation);
+
+    let (view, proj) = get_generic_uniforms(swapchain_size, camera);
+
+    let uniform_data = vs::Data {
+        world: Matrix4::from(rotation_mat).into(),
+        view: view.into(),
+        proj: proj.into(),
+    };
+
+    let subbuffer = allocator.allocate_sized()?;
+    *subbuffer.write()? = uniform_data;
+    Ok(subbuffer)
+
+}
@@ -1,81 +1,63 @@
 use std::time::Instant;
 use rust_vulkan_graphics::*;
-use maths::{Matrix3, Matrix4};
-
-mod vs {
-    rust_vulkan_graphics::shader!{
-        ty: "vertex",
-        src: r"
-            #version 450
-
-            layout(location = 0) in vec3 position;
-            layout(location = 1) in vec3 normal;
-
-            layout(location = 0) out vec4 v_colour;
-            layout(location = 1) out vec3 v_normal;
-            
-            layout(set = 0, binding = 0) uniform Data {
-                mat4 world;
-                mat4 view;
-                mat4 proj;
-                vec3 colour;
-            } uniforms;
-            
-            void main() {
-                gl_Position = uniforms.proj * uniforms.view * uniforms.world * vec4(position, 1.0);
-                v_colour = vec4(uniforms.colour, 1.0);
-                v_normal = mat3(transpose(inverse(uniforms.world))) * normal;;
-            }
-        ",
-    }
-}
-
-mod fs {
-    rust_vulkan_graphics::shader!{
-        ty: "fragment",
-        src: r"
-            #version 460
 
-            layout(location = 0) in vec4 v_colour;
-            layout(location = 1) in vec3 v_normal;
+type MeshBuffers = (Subbuffer<[PositionVertex]>, Subbuffer<[Normal]>, Subbuffer<[u32]>);
 
-            layout(location = 0) out vec4 f_color;
+fn main() -> Result<(), Error> {
 
-            const vec3 LIGHTDIR = vec3(-0.302, -0.302, 0.905);
-
-            void main() {
-                float light = max(dot(v_normal, LIGHTDIR), 0.0) + 0.2;
-
-                f_color = v_colour * light;
-            }
-        ",
-
-    }
-}
-
-
-fn main() {
-
-    let meshes = load_obj("assets/island.obj");
+    let obj = load_obj("assets/island.obj")?;
 
     let (mut event_loop, vulkano_context, mut vulkano_windows, window_ids, commands_allocator, descriptor_set_allocator) = get_general_graphics_data(vec![("Obj Example".to_string(), 750.0, 500.0, false)], gen_swapchain_func!(Format::B8G8R8A8_SRGB));
     let uniform_allocator = create_uniform_buffer_allocator(vulkano_context.memory_allocator());
-    let mut gui = Vec::new();
-
-    let total_mesh = combine_meshes(&meshes);
+    // the colour tints every material of the island
+    let mut gui = vec![
+        GuiWindowBuilder::new("Obj Settings")
+            .overlay()
+            .rgb("Colour", [1.0; 3])
+            .build(&mut vulkano_windows, window_ids[0], &event_loop)?
+    ];
+
+    // each mesh is a node under one root, so turning the root turns the whole island
+    let mut scene: Scene<MeshBuffers, BlinnPhongMaterial> = Scene::new();
+    let root = scene.add_node(None, Transform::IDENTITY);
+    for mesh in obj.meshes.iter() {
+        let material = match obj.material(mesh) {
+            Some(material) => BlinnPhongMaterial::new([material.diffuse[0], material.diffuse[1], material.diffuse[2], 1.0], material.specular, material.shininess.max(1.0)),
+            None => BlinnPhongMaterial::default(),
+        };
+        let mesh_index = scene.add_mesh(mesh.mesh.get_buffers(&vulkano_context)?);
+        let material_index = scene.add_material(material);
+        let node = scene.add_mesh_node(Some(root), Transform::IDENTITY, mesh_index, Some(material_index));
+        scene.set_bounds(node, mesh.mesh.aabb());
+    }
 
-    let (vertex_buffer, normal_buffer, index_buffer) = total_mesh.get_buffers(&vulkano_context);
+    let mut lights = LightSet::new([0.2; 3]);
+    lights.add(Light::directional([0.302, 0.302, -0.905], [1.0; 3]));
+    lights.add(Light::point([0.0, 8.0, 0.0], [1.0, 0.6, 0.2]).with_intensity(2.0));
 
 
+    // drag with the left mouse button to circle the island, the middle button to pan and scroll to zoom
     let mut camera = Camera::new(Some([-20.0, 5.0, 0.0]), Some([1.0, -0.3, 0.0]), Some(10.0), None);
+    camera.controllable();
+    camera.set_controller(CameraController::Orbit(OrbitController::new([0.0; 3])));
 
     let mut last_frame_time = Instant::now();
     let mut rotation = 0.0;
 
     let scene_window_id = window_ids[0];
 
-    let vs = vs::load(vulkano_context.device().clone()).unwrap();
-    let fs = fs::load(vulkano_context.device().clone()).unwrap();
+    let vs = blinn_phong::vs::load(vulkano_context.device().clone())?;
+    let fs = blinn_phong::fs_shadowed::load(vulkano_context.device().clone())?;
+
+    let mut shadow_map = ShadowMap::new(
+        &vulkano_context,
+        &commands_allocator,
+        ShadowMapConfig {
+            cascades: 3,
+            ..Default::default()
+        },
+        &vertex_defs::position(),
+    )?;
 
     let mut render_pipeline = MultiSamplePipeline3D::new(
         &vulkano_context,
@@ -85,7 +67,7 @@ fn main() {
         &fs,
         &vertex_defs::position_normal(),
         Some(SampleCount::Sample4),
-    );
+    )?;
 
     loop {
         if !generic_winit_event_handling_with_camera(&mut event_loop, &mut vulkano_windows, &mut gui, (&mut camera, &scene_window_id)) {break;}
@@ -95,9 +77,13 @@ fn main() {
             last_frame_time = Instant::now();
 
             rotation += frame_time * 0.5;
+            scene.node_mut(root).transform_mut().rotation = Quaternion::from_axis_angle([0.0, 1.0, 0.0], rotation);
 
-
-            draw(vulkano_windows.get_renderer_mut(scene_window_id).unwrap(), rotation, &vertex_buffer, &normal_buffer, &index_buffer, &mut render_pipeline, &camera, &uniform_allocator);
+            let renderer = vulkano_windows.get_renderer_mut(scene_window_id).ok_or(Error::MissingWindow(scene_window_id))?;
+            if let Err(err) = draw(renderer, &mut gui[0], &mut scene, &lights, &mut shadow_map, &mut render_pipeline, &camera, &uniform_allocator) {
+                // the renderer recreates its swapchain on the next acquire, so the frame can just be skipped
+                if !err.is_out_of_date() {return Err(err);}
+            }
 
 
             camera.do_move(frame_time);
@@ -105,47 +91,58 @@ fn main() {
 
     }
 
+    Ok(())
 }
 
 fn draw(
     renderer: &mut VulkanoWindowRenderer,
-    cube_rotation: f32,
-    vertex_buffer: &Subbuffer<[PositionVertex]>,
-    normal_buffer: &Subbuffer<[Normal]>,
-    index_buffer: &Subbuffer<[u32]>,
+    gui: &mut GuiWindowData,
+    scene: &mut Scene<MeshBuffers, BlinnPhongMaterial>,
+    lights: &LightSet,
+    shadow_map: &mut ShadowMap,
     pipeline: &mut MultiSamplePipeline3D, 
     camera: &Camera,
     uniform_allocator: &SubbufferAllocator,
-) {
-    let uniforms = get_uniform_subbuffer(cube_rotation, renderer.swapchain_image_size(), uniform_allocator, camera, [0.2; 3]);
-    let before_future = renderer.acquire().unwrap();
-    let after_future = pipeline.draw_from_vertices_and_normals(before_future, renderer.swapchain_image_view(), vertex_buffer, normal_buffer, index_buffer, &uniforms);
+) -> Result<(), Error> {
+    let [width, height] = renderer.swapchain_image_size();
+    // the first light is the sun, which is the one that casts shadows
+    if let Some(Light::Directional {direction, ..}) = lights.lights.first() {
+        shadow_map.update(camera, width as f32 / height as f32, *direction);
+    }
+    let shadows = shadow_map.create_buffer(uniform_allocator, 0)?;
+    let lights = lights.create_buffer(uniform_allocator, camera.position.into())?;
+    let (view, proj) = get_generic_uniforms(renderer.swapchain_image_size(), camera);
+    let (view, proj): ([[f32; 4]; 4], [[f32; 4]; 4]) = (view.into(), proj.into());
+    let tint = gui.get_rgb("Colour")?;
+
+    scene.update_world_matrices();
+
+    let before_future = renderer.acquire()?;
+    let shadow_future = shadow_map.render(before_future, |pass| {
+        scene.visit_visible(|_, (vertex_buffer, _, index_buffer), _, world| pass.draw(vertex_buffer, index_buffer, world))
+    })?;
+
+    let mut frame = pipeline.begin_frame(shadow_future, renderer.swapchain_image_view())?;
+    // meshes behind the camera still cast shadows, so only the main pass is culled
+    let frustum = Frustum::from_camera(camera, renderer.swapchain_image_size());
+    scene.draw_culled(&mut frame, &frustum, |frame, (vertex_buffer, normal_buffer, index_buffer), material, world| {
+        let uniforms = uniform_allocator.allocate_sized()?;
+        *uniforms.write()? = blinn_phong::vs::Data {world, view, proj};
+        let material_buffer = uniform_allocator.allocate_sized()?;
+        let mut material = material.copied().unwrap_or_default();
+        for i in 0..3 {
+            material.colour[i] *= tint[i];
+        }
+        *material_buffer.write()? = material;
+        frame.draw_with_descriptors(
+            (vertex_buffer.clone(), normal_buffer.clone()),
+            index_buffer,
+            blinn_phong::shadowed_writes(&uniforms, &lights, &material_buffer, &shadows, shadow_map),
+        )
+    })?;
+    let scene_future = frame.end()?;
+    // the settings are drawn over the island, in the same frame
+    let after_future = draw_gui_overlay(gui, scene_future, renderer);
     renderer.present(after_future, true);
+    Ok(())
 }
-
-
-fn get_uniform_subbuffer (
-    rotation: f32,
-    swapchain_size: [u32; 2],
-    allocator: &SubbufferAllocator,
-    camera: &Camera,
-    colour: [f32; 3],
-) -> Subbuffer<vs::Data> {
-
-    let rotation_mat = Matrix3::from_angle_y(rotation);
-
-    let (view, proj) = get_generic_uniforms(swapchain_size, camera);
-    
-
-    let uniform_data = vs::Data {
-        world: Matrix4::from(rotation_mat).into(),
-        view: view.into(),
-        proj: proj.into(),
-        colour: colour.into(),
-    };
-
-    let subbuffer = allocator.allocate_sized().unwrap();
-    *subbuffer.write().unwrap() = uniform_data;
-    subbuffer
-
-}
\ No newline at end of file
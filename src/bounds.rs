@@ -0,0 +1,242 @@
+//! bounding volumes and the view frustum, so meshes outside the view can be skipped
+use maths::Matrix4;
+use super::{Camera, Position};
+
+
+/// An axis aligned bounding box
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl Aabb {
+    pub fn new(min: [f32; 3], max: [f32; 3]) -> Self {
+        Aabb {min, max}
+    }
+
+    /// the smallest box holding every point, None if there are no points
+    pub fn from_points<I: IntoIterator<Item = [f32; 3]>>(points: I) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut aabb = Aabb::new(first, first);
+        for point in points {
+            aabb.add_point(point);
+        }
+        Some(aabb)
+    }
+
+    /// the box around a set of vertices
+    pub fn from_vertices<T: Position>(vertices: &[T]) -> Option<Self> {
+        Self::from_points(vertices.iter().map(|vertex| vertex.pos()))
+    }
+
+    /// grows the box to hold the point
+    pub fn add_point(&mut self, point: [f32; 3]) {
+        for axis in 0..3 {
+            self.min[axis] = self.min[axis].min(point[axis]);
+            self.max[axis] = self.max[axis].max(point[axis]);
+        }
+    }
+
+    /// the smallest box holding both boxes
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        let mut aabb = *self;
+        aabb.add_point(other.min);
+        aabb.add_point(other.max);
+        aabb
+    }
+
+    pub fn centre(&self) -> [f32; 3] {
+        [
+            (self.min[0] + self.max[0]) * 0.5,
+            (self.min[1] + self.max[1]) * 0.5,
+            (self.min[2] + self.max[2]) * 0.5,
+        ]
+    }
+
+    /// half the size of the box along each axis
+    pub fn extents(&self) -> [f32; 3] {
+        [
+            (self.max[0] - self.min[0]) * 0.5,
+            (self.max[1] - self.min[1]) * 0.5,
+            (self.max[2] - self.min[2]) * 0.5,
+        ]
+    }
+
+    pub fn contains_point(&self, point: [f32; 3]) -> bool {
+        (0..3).all(|axis| point[axis] >= self.min[axis] && point[axis] <= self.max[axis])
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|axis| self.min[axis] <= other.max[axis] && self.max[axis] >= other.min[axis])
+    }
+
+    /// the box around this box after it is transformed by a matrix, which may be larger than the transformed box itself
+    pub fn transformed(&self, matrix: &Matrix4) -> Aabb {
+        let matrix: [[f32; 4]; 4] = (*matrix).into();
+        let centre = self.centre();
+        let extents = self.extents();
+        let mut new_centre = [matrix[3][0], matrix[3][1], matrix[3][2]];
+        let mut new_extents = [0.0; 3];
+        for row in 0..3 {
+            for column in 0..3 {
+                new_centre[row] += matrix[column][row] * centre[column];
+                new_extents[row] += matrix[column][row].abs() * extents[column];
+            }
+        }
+        Aabb {
+            min: [new_centre[0] - new_extents[0], new_centre[1] - new_extents[1], new_centre[2] - new_extents[2]],
+            max: [new_centre[0] + new_extents[0], new_centre[1] + new_extents[1], new_centre[2] + new_extents[2]],
+        }
+    }
+}
+
+
+/// A sphere holding a set of points, cheaper to test than an Aabb but usually looser
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub centre: [f32; 3],
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(centre: [f32; 3], radius: f32) -> Self {
+        BoundingSphere {centre, radius}
+    }
+
+    /// a sphere centred on the points' bounding box, holding every point, None if there are no points
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = [f32; 3]>,
+        I::IntoIter: Clone,
+    {
+        let points = points.into_iter();
+        let centre = Aabb::from_points(points.clone())?.centre();
+        let radius = points.map(|point| distance_squared(point, centre)).fold(0.0, f32::max).sqrt();
+        Some(BoundingSphere {centre, radius})
+    }
+
+    pub fn from_vertices<T: Position>(vertices: &[T]) -> Option<Self> {
+        Self::from_points(vertices.iter().map(|vertex| vertex.pos()))
+    }
+
+    pub fn contains_point(&self, point: [f32; 3]) -> bool {
+        distance_squared(point, self.centre) <= self.radius * self.radius
+    }
+
+    pub fn intersects(&self, other: &BoundingSphere) -> bool {
+        let radii = self.radius + other.radius;
+        distance_squared(self.centre, other.centre) <= radii * radii
+    }
+
+    /// the sphere after it is transformed by a matrix, non uniform scales grow it by the largest scale
+    pub fn transformed(&self, matrix: &Matrix4) -> BoundingSphere {
+        let matrix: [[f32; 4]; 4] = (*matrix).into();
+        let mut centre = [matrix[3][0], matrix[3][1], matrix[3][2]];
+        for row in 0..3 {
+            for column in 0..3 {
+                centre[row] += matrix[column][row] * self.centre[column];
+            }
+        }
+        let scale = (0..3)
+            .map(|column| (matrix[column][0].powi(2) + matrix[column][1].powi(2) + matrix[column][2].powi(2)).sqrt())
+            .fold(0.0, f32::max);
+        BoundingSphere {centre, radius: self.radius * scale}
+    }
+}
+
+
+/// A plane holding every point where dot(normal, point) + distance is zero, the normal points to the inside
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    pub normal: [f32; 3],
+    pub distance: f32,
+}
+
+impl Plane {
+    /// builds a plane from the coefficients of ax + by + cz + d = 0, normalising them
+    pub fn from_coefficients(coefficients: [f32; 4]) -> Self {
+        let length = (coefficients[0].powi(2) + coefficients[1].powi(2) + coefficients[2].powi(2)).sqrt();
+        // an infinite projection's far plane has no normal, and everything is in front of it
+        if length == 0.0 {
+            return Plane {normal: [0.0; 3], distance: coefficients[3]};
+        }
+        Plane {
+            normal: [coefficients[0] / length, coefficients[1] / length, coefficients[2] / length],
+            distance: coefficients[3] / length,
+        }
+    }
+
+    /// the distance from the plane to the point, negative if the point is behind it
+    pub fn signed_distance(&self, point: [f32; 3]) -> f32 {
+        self.normal[0] * point[0] + self.normal[1] * point[1] + self.normal[2] * point[2] + self.distance
+    }
+}
+
+
+/// The volume a camera can see, as six planes facing inwards
+///
+/// The planes are left, right, bottom, top, near and far, taken from vulkan's 0..1 clip space depth like Camera's projections use
+///
+/// With reverse z the near and far planes swap places, and an infinite projection's far plane never culls anything
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// extracts the planes from a column major view projection matrix, they are in whatever space the matrix transforms from
+    pub fn from_matrix(view_proj: &[[f32; 4]; 4]) -> Self {
+        let row = |index: usize| [view_proj[0][index], view_proj[1][index], view_proj[2][index], view_proj[3][index]];
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        let add = |a: [f32; 4], b: [f32; 4]| [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]];
+        let sub = |a: [f32; 4], b: [f32; 4]| [a[0] - b[0], a[1] - b[1], a[2] - b[2], a[3] - b[3]];
+
+        Frustum {
+            planes: [
+                Plane::from_coefficients(add(w, x)),
+                Plane::from_coefficients(sub(w, x)),
+                Plane::from_coefficients(add(w, y)),
+                Plane::from_coefficients(sub(w, y)),
+                Plane::from_coefficients(z),
+                Plane::from_coefficients(sub(w, z)),
+            ],
+        }
+    }
+
+    /// the frustum of the camera, for a viewport of the given size in pixels
+    pub fn from_camera(camera: &Camera, viewport_size: [u32; 2]) -> Self {
+        let aspect_ratio = viewport_size[0] as f32 / viewport_size[1] as f32;
+        Self::from_matrix(&camera.get_view_projection_matrix(aspect_ratio).into())
+    }
+
+    pub fn contains_point(&self, point: [f32; 3]) -> bool {
+        self.planes.iter().all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+
+    /// true if any part of the sphere may be inside the frustum
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes.iter().all(|plane| plane.signed_distance(sphere.centre) >= -sphere.radius)
+    }
+
+    /// true if any part of the box may be inside the frustum
+    ///
+    /// Boxes near a corner of the frustum can pass without touching it, that only costs drawing something that was not needed
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // the corner furthest along the plane's normal
+            let corner = [
+                if plane.normal[0] >= 0.0 {aabb.max[0]} else {aabb.min[0]},
+                if plane.normal[1] >= 0.0 {aabb.max[1]} else {aabb.min[1]},
+                if plane.normal[2] >= 0.0 {aabb.max[2]} else {aabb.min[2]},
+            ];
+            plane.signed_distance(corner) >= 0.0
+        })
+    }
+}
+
+
+fn distance_squared(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
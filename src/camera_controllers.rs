@@ -0,0 +1,246 @@
+//! mouse driven ways of moving a Camera, fed from winit events by generic_winit_event_handling_with_camera
+use winit::event::MouseButton;
+use maths::Vector3;
+
+
+/// How a Camera responds to the mouse, the keyboard controls work alongside all but Orbit
+#[derive(Clone, Debug, PartialEq)]
+pub enum CameraController {
+    /// only the keyboard moves the camera
+    Keyboard,
+    MouseLook(MouseLook),
+    Orbit(OrbitController),
+    Pan(PanController),
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        CameraController::Keyboard
+    }
+}
+
+
+/// Turns the camera with the mouse, grabbing the cursor while looking
+#[derive(Clone, Debug, PartialEq)]
+pub struct MouseLook {
+    /// radians turned per pixel of mouse movement
+    pub sensitivity: f32,
+    /// None to look whenever the camera is controllable
+    pub button: Option<MouseButton>,
+    pub invert_y: bool,
+    /// how close to straight up or down the camera can look, in radians
+    pub max_pitch: f32,
+    held: bool,
+}
+
+impl Default for MouseLook {
+    fn default() -> Self {
+        MouseLook {
+            sensitivity: 0.003,
+            button: Some(MouseButton::Right),
+            invert_y: false,
+            max_pitch: 1.55,
+            held: false,
+        }
+    }
+}
+
+impl MouseLook {
+    pub fn new(sensitivity: f32, button: Option<MouseButton>) -> Self {
+        MouseLook {
+            sensitivity,
+            button,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_looking(&self) -> bool {
+        self.button.is_none() || self.held
+    }
+
+    pub(crate) fn process_button(&mut self, button: MouseButton, pressed: bool) {
+        if self.button == Some(button) {
+            self.held = pressed;
+        }
+    }
+
+    pub(crate) fn process_motion(&mut self, delta: [f32; 2]) -> (f32, f32) {
+        if !self.is_looking() {
+            return (0.0, 0.0);
+        }
+        let y_sign = if self.invert_y {-1.0} else {1.0};
+        (delta[0] * self.sensitivity, y_sign * delta[1] * self.sensitivity)
+    }
+}
+
+
+/// Circles the camera around a target point about the world y axis, the scroll wheel zooms
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrbitController {
+    pub target: [f32; 3],
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// radians turned per pixel dragged
+    pub sensitivity: f32,
+    /// fraction of the distance moved in per line scrolled
+    pub zoom_speed: f32,
+    pub rotate_button: MouseButton,
+    /// None to keep the target fixed
+    pub pan_button: Option<MouseButton>,
+    pub max_pitch: f32,
+    yaw: f32,
+    pitch: f32,
+    rotating: bool,
+    panning: bool,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        OrbitController {
+            target: [0.0; 3],
+            distance: 10.0,
+            min_distance: 0.1,
+            max_distance: 1000.0,
+            sensitivity: 0.005,
+            zoom_speed: 0.1,
+            rotate_button: MouseButton::Left,
+            pan_button: Some(MouseButton::Middle),
+            max_pitch: 1.55,
+            yaw: 0.0,
+            pitch: 0.0,
+            rotating: false,
+            panning: false,
+        }
+    }
+}
+
+impl OrbitController {
+    /// the angle and distance are taken from the camera in Camera::set_controller
+    pub fn new(target: [f32; 3]) -> Self {
+        OrbitController {
+            target,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn sync(&mut self, position: Vector3) {
+        let offset = position - Vector3::from(self.target);
+        let distance = offset.dot(offset).sqrt();
+        if distance > 0.0 {
+            self.distance = distance.clamp(self.min_distance, self.max_distance);
+            let (yaw, pitch) = yaw_pitch(offset);
+            self.yaw = yaw;
+            self.pitch = pitch.clamp(-self.max_pitch, self.max_pitch);
+        }
+    }
+
+    pub(crate) fn process_button(&mut self, button: MouseButton, pressed: bool) {
+        if button == self.rotate_button {
+            self.rotating = pressed;
+        }
+        if Some(button) == self.pan_button {
+            self.panning = pressed;
+        }
+    }
+
+    pub(crate) fn process_motion(&mut self, delta: [f32; 2], position: &mut Vector3, direction: &mut Vector3, up: Vector3) {
+        if self.rotating {
+            self.yaw -= delta[0] * self.sensitivity;
+            self.pitch = (self.pitch + delta[1] * self.sensitivity).clamp(-self.max_pitch, self.max_pitch);
+        }
+        if self.panning {
+            // the target moves with the cursor, further away targets move more per pixel
+            let (right, screen_up) = screen_axes(*direction, up);
+            let speed = self.distance * self.sensitivity * 0.2;
+            let target = Vector3::from(self.target) - right * (delta[0] * speed) + screen_up * (delta[1] * speed);
+            self.target = target.into();
+        }
+        self.apply(position, direction);
+    }
+
+    pub(crate) fn process_scroll(&mut self, lines: f32, position: &mut Vector3, direction: &mut Vector3) {
+        self.distance = (self.distance * (1.0 - lines * self.zoom_speed)).clamp(self.min_distance, self.max_distance);
+        self.apply(position, direction);
+    }
+
+    pub(crate) fn apply(&self, position: &mut Vector3, direction: &mut Vector3) {
+        let offset = from_yaw_pitch(self.yaw, self.pitch);
+        *position = Vector3::from(self.target) + offset * self.distance;
+        *direction = -offset;
+    }
+}
+
+
+/// Slides the camera across the screen, the scroll wheel moves it forwards and back
+#[derive(Clone, Debug, PartialEq)]
+pub struct PanController {
+    /// world units moved per pixel dragged
+    pub speed: f32,
+    /// world units moved per line scrolled
+    pub scroll_speed: f32,
+    pub button: MouseButton,
+    held: bool,
+}
+
+impl Default for PanController {
+    fn default() -> Self {
+        PanController {
+            speed: 0.01,
+            scroll_speed: 1.0,
+            button: MouseButton::Middle,
+            held: false,
+        }
+    }
+}
+
+impl PanController {
+    pub fn new(speed: f32, button: MouseButton) -> Self {
+        PanController {
+            speed,
+            button,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn process_button(&mut self, button: MouseButton, pressed: bool) {
+        if button == self.button {
+            self.held = pressed;
+        }
+    }
+
+    pub(crate) fn process_motion(&mut self, delta: [f32; 2], position: &mut Vector3, direction: Vector3, up: Vector3) {
+        if !self.held {
+            return;
+        }
+        // the view is dragged with the cursor, so the camera moves the opposite way
+        let (right, screen_up) = screen_axes(direction, up);
+        *position = *position - right * (delta[0] * self.speed) + screen_up * (delta[1] * self.speed);
+    }
+
+    pub(crate) fn process_scroll(&mut self, lines: f32, position: &mut Vector3, direction: Vector3) {
+        *position = *position + direction.normalised() * (lines * self.scroll_speed);
+    }
+}
+
+
+fn yaw_pitch(direction: Vector3) -> (f32, f32) {
+    let direction = direction.normalised();
+    (direction.z.atan2(direction.x), direction.y.clamp(-1.0, 1.0).asin())
+}
+
+fn from_yaw_pitch(yaw: f32, pitch: f32) -> Vector3 {
+    [pitch.cos() * yaw.cos(), pitch.sin(), pitch.cos() * yaw.sin()].into()
+}
+
+fn screen_axes(direction: Vector3, camera_up: Vector3) -> (Vector3, Vector3) {
+    let forward = direction.normalised();
+    let mut right = forward.cross(Vector3::Y);
+    // straight up or down there is no horizontal, so the camera's own side is used
+    if right.dot(right) < 1e-8 {
+        right = camera_up.cross(forward);
+    }
+    let right = right.normalised();
+    let up = right.cross(forward);
+    (right, up)
+}
@@ -1,15 +1,150 @@
 #![allow(dead_code)]
-use winit::event::VirtualKeyCode;
-use maths::{Vector3, Matrix3, Matrix4};
+use std::collections::HashMap;
+use winit::event::{VirtualKeyCode, MouseButton};
+use maths::{Vector3, Matrix4};
+use super::{CameraAction, CameraController, CameraKeyframe, CameraPath, InputMap, InputSource, Quaternion, Ray};
+use super::camera_path::{PathPlayback, PathRecording};
 
+/// the world direction the camera treats as up when it has no roll
+const WORLD_UP: [f32; 3] = [0.0, -1.0, 0.0];
 
+
+/// How a Camera projects the world onto the screen
+///
+/// The matrices map depth to vulkan's 0..1 range, near to 0 and far to 1, or the other way round with reverse z
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    /// fov is the vertical field of view in radians
+    Perspective {
+        fov: f32,
+        near: f32,
+        far: f32,
+    },
+    /// a perspective projection with no far plane, nothing is ever clipped for being too far away
+    InfinitePerspective {
+        fov: f32,
+        near: f32,
+    },
+    /// height is how many world units fit vertically on screen, the width follows from the aspect ratio
+    Orthographic {
+        height: f32,
+        near: f32,
+        far: f32,
+    },
+}
+
+impl Default for Projection {
+    /// a 90 degree perspective from 0.01 to 100
+    fn default() -> Self {
+        Projection::Perspective {
+            fov: std::f32::consts::FRAC_PI_2,
+            near: 0.01,
+            far: 100.0,
+        }
+    }
+}
+
+impl Projection {
+    pub fn near(&self) -> f32 {
+        match *self {
+            Projection::Perspective {near, ..} | Projection::InfinitePerspective {near, ..} | Projection::Orthographic {near, ..} => near,
+        }
+    }
+
+    /// the far plane, infinity for InfinitePerspective
+    pub fn far(&self) -> f32 {
+        match *self {
+            Projection::Perspective {far, ..} | Projection::Orthographic {far, ..} => far,
+            Projection::InfinitePerspective {..} => f32::INFINITY,
+        }
+    }
+
+    /// the vertical field of view, None for orthographic projections
+    pub fn fov(&self) -> Option<f32> {
+        match *self {
+            Projection::Perspective {fov, ..} | Projection::InfinitePerspective {fov, ..} => Some(fov),
+            Projection::Orthographic {..} => None,
+        }
+    }
+
+    /// changes the vertical field of view of a perspective projection, orthographic projections are left alone
+    pub fn set_fov(&mut self, new_fov: f32) {
+        match self {
+            Projection::Perspective {fov, ..} | Projection::InfinitePerspective {fov, ..} => *fov = new_fov,
+            Projection::Orthographic {..} => (),
+        }
+    }
+
+    /// half the height of the view at the given distance from the camera
+    pub fn half_height_at(&self, distance: f32) -> f32 {
+        match *self {
+            Projection::Perspective {fov, ..} | Projection::InfinitePerspective {fov, ..} => distance * (fov / 2.0).tan(),
+            Projection::Orthographic {height, ..} => height / 2.0,
+        }
+    }
+
+    /// the projection as a column major matrix, for a viewport with the given width / height
+    pub fn matrix(&self, aspect_ratio: f32, reverse_z: bool) -> [[f32; 4]; 4] {
+        // the depth row maps view space z, which is negative in front of the camera, to clip space z
+        let (x_scale, y_scale, depth_scale, depth_offset, perspective) = match *self {
+            Projection::Perspective {fov, near, far} => {
+                let focal = 1.0 / (fov / 2.0).tan();
+                let (depth_scale, depth_offset) = if reverse_z {
+                    (near / (far - near), near * far / (far - near))
+                } else {
+                    (far / (near - far), near * far / (near - far))
+                };
+                (focal / aspect_ratio, focal, depth_scale, depth_offset, true)
+            },
+            Projection::InfinitePerspective {fov, near} => {
+                let focal = 1.0 / (fov / 2.0).tan();
+                let (depth_scale, depth_offset) = if reverse_z {(0.0, near)} else {(-1.0, -near)};
+                (focal / aspect_ratio, focal, depth_scale, depth_offset, true)
+            },
+            Projection::Orthographic {height, near, far} => {
+                let (depth_scale, depth_offset) = if reverse_z {
+                    (1.0 / (far - near), far / (far - near))
+                } else {
+                    (-1.0 / (far - near), -near / (far - near))
+                };
+                (2.0 / (height * aspect_ratio), 2.0 / height, depth_scale, depth_offset, false)
+            },
+        };
+
+        let (w_from_z, w_offset) = if perspective {(-1.0, 0.0)} else {(0.0, 1.0)};
+        [
+            [x_scale, 0.0, 0.0, 0.0],
+            [0.0, y_scale, 0.0, 0.0],
+            [0.0, 0.0, depth_scale, w_from_z],
+            [0.0, 0.0, depth_offset, w_offset],
+        ]
+    }
+}
+
+
+/// A movable viewpoint, its orientation is kept as a quaternion so it can roll and be smoothly interpolated
+///
+/// With no rotation the camera looks along +x with -y as up
 pub struct Camera {
     pub position: Vector3, 
-    pub direction: Vector3,
-    pub up: Vector3,
+    orientation: Quaternion,
+    /// how far above or below the horizontal the camera can pitch, in radians, None to let it turn over
+    pub pitch_limit: Option<f32>,
     pub move_speed: f32,
     pub rotate_speed: f32,
-    pub movement: [bool; 10], // forward, back, left, right, up, down, spin right, spin left, spin forward, spin backward
+    /// which keys, buttons and axes trigger which movements
+    pub input_map: InputMap,
+    /// how strongly each input is currently held
+    inputs: HashMap<InputSource, f32>,
+    /// relative axis movement added up since the last do_move, it is let go of once the camera has moved with it
+    axis_motion: HashMap<u32, f32>,
+    pub projection: Projection,
+    /// maps near to a depth of 1 and far to 0, which spreads depth precision more evenly, the pipeline must use MultiSamplePipelineConfig::reverse_z
+    pub reverse_z: bool,
+    controller: CameraController,
+    last_cursor: Option<[f64; 2]>,
+    playback: Option<PathPlayback>,
+    recording: Option<PathRecording>,
     is_controlled: bool,
 }
 
@@ -34,11 +169,19 @@ impl Camera {
 
         Camera {
             position,
-            direction,
+            orientation: Camera::orientation_for(direction.into()),
+            pitch_limit: None,
             move_speed: move_speed.unwrap_or(3.0),
             rotate_speed: rotate_speed.unwrap_or(1.0),
-            movement: [false; 10],
-            up: -Vector3::Y,
+            input_map: InputMap::default(),
+            inputs: HashMap::new(),
+            axis_motion: HashMap::new(),
+            projection: Projection::default(),
+            reverse_z: false,
+            controller: CameraController::Keyboard,
+            last_cursor: None,
+            playback: None,
+            recording: None,
             is_controlled: false,
         }
     }
@@ -52,9 +195,9 @@ impl Camera {
     }
 
     pub fn get_view_matrix(&self) -> Matrix4 {
-        let f = self.direction.normalised();
+        let f = self.direction();
     
-        let mut s = self.up.cross(f);
+        let mut s = self.up().cross(f);
 
         s.normalise();
     
@@ -68,88 +211,439 @@ impl Camera {
         )
     }
 
+    /// the unit direction the camera is looking in
+    pub fn direction(&self) -> Vector3 {
+        self.orientation.rotate([1.0, 0.0, 0.0]).into()
+    }
+
+    /// the unit direction of the top of the screen, which leans over as the camera rolls
+    pub fn up(&self) -> Vector3 {
+        self.orientation.rotate(WORLD_UP).into()
+    }
+
+    pub fn orientation(&self) -> Quaternion {
+        self.orientation
+    }
+
+    pub fn set_orientation(&mut self, orientation: Quaternion) {
+        self.orientation = orientation.normalised();
+    }
+
+    /// points the camera along a direction, with no roll, a zero direction is ignored
+    pub fn set_direction(&mut self, direction: Vector3) {
+        if direction.dot(direction) == 0.0 {return;}
+        self.orientation = Camera::orientation_for(direction.into());
+    }
+
+    /// the orientation of a camera looking along a direction with no roll
+    pub fn orientation_for(direction: [f32; 3]) -> Quaternion {
+        let forward = Vector3::from(direction).normalised();
+        let world_up = Vector3::from(WORLD_UP);
+        let mut up = world_up - forward * forward.dot(world_up);
+        // looking straight up or down, any up at right angles will do
+        if up.dot(up) < 1e-8 {
+            let z = Vector3::from([0.0, 0.0, 1.0]);
+            up = z - forward * forward.dot(z);
+        }
+        let up = up.normalised();
+
+        // the columns are where the local x, y and z axes end up
+        let side = up.cross(forward);
+        Quaternion::from_matrix3([forward.into(), (-up).into(), side.into()])
+    }
+
+    /// moves the orientation a fraction t of the way towards another, along the shortest arc
+    pub fn slerp_orientation(&mut self, target: Quaternion, t: f32) {
+        self.orientation = self.orientation.slerp(target, t);
+    }
+
+    /// rotates the camera around a world axis
+    pub fn rotate_about(&mut self, axis: Vector3, angle: f32) {
+        self.orientation = (Quaternion::from_axis_angle(axis.into(), angle) * self.orientation).normalised();
+    }
+
+    /// turns around the world's up axis, keeping the pitch and roll
+    pub fn rotate_yaw(&mut self, angle: f32) {
+        self.rotate_about(WORLD_UP.into(), angle);
+    }
+
+    /// tilts towards or away from up, stopping at the pitch limit if there is one
+    pub fn rotate_pitch(&mut self, angle: f32) {
+        self.pitch_within(angle, self.pitch_limit);
+    }
+
+    /// rolls around the view direction
+    pub fn rotate_roll(&mut self, angle: f32) {
+        let direction = self.direction();
+        self.rotate_about(direction, angle);
+    }
+
+    /// the angle around the world's up axis, 0 when looking along +x
+    pub fn yaw(&self) -> f32 {
+        let direction = self.direction();
+        direction.z.atan2(direction.x)
+    }
+
+    /// the angle above the horizontal, towards up
+    pub fn pitch(&self) -> f32 {
+        self.direction().dot(WORLD_UP.into()).clamp(-1.0, 1.0).asin()
+    }
+
+    /// the angle the camera has rolled around its view direction
+    pub fn roll(&self) -> f32 {
+        let direction = self.direction();
+        let level: Vector3 = Camera::orientation_for(direction.into()).rotate(WORLD_UP).into();
+        let up = self.up();
+        level.cross(up).dot(direction).atan2(level.dot(up))
+    }
+
+    /// sets the orientation from angles as returned by yaw, pitch and roll
+    pub fn set_yaw_pitch_roll(&mut self, yaw: f32, pitch: f32, roll: f32) {
+        let pitch = match self.pitch_limit {
+            Some(limit) => pitch.clamp(-limit, limit),
+            None => pitch,
+        };
+        let horizontal = [pitch.cos() * yaw.cos(), 0.0, pitch.cos() * yaw.sin()];
+        let direction = Vector3::from(horizontal) + Vector3::from(WORLD_UP) * pitch.sin();
+        self.set_direction(direction);
+        self.rotate_roll(roll);
+    }
+
+    /// pitches around the horizontal, so the pitch changes by exactly the angle and the roll is kept
+    fn pitch_within(&mut self, angle: f32, limit: Option<f32>) {
+        let angle = match limit {
+            Some(limit) => {
+                let pitch = self.pitch();
+                (pitch + angle).clamp(-limit, limit) - pitch
+            },
+            None => angle,
+        };
+        if angle == 0.0 {return;}
+
+        let direction = self.direction();
+        let mut left = direction.cross(WORLD_UP.into());
+        // straight up or down there is no horizontal, so the camera's own left is used
+        if left.dot(left) < 1e-8 {
+            left = direction.cross(self.up());
+        }
+        self.rotate_about(left, angle);
+    }
+
+    pub fn controller(&self) -> &CameraController {
+        &self.controller
+    }
+
+    /// changes how the mouse moves the camera, an orbit picks up its angle and distance from where the camera is
+    pub fn set_controller(&mut self, controller: CameraController) {
+        self.controller = controller;
+        let mut direction = self.direction();
+        if let CameraController::Orbit(orbit) = &mut self.controller {
+            orbit.sync(self.position);
+            orbit.apply(&mut self.position, &mut direction);
+            self.set_direction(direction);
+        }
+    }
+
+    /// true while the window should hide and grab the cursor, for mouse look
+    pub fn wants_cursor_grab(&self) -> bool {
+        match &self.controller {
+            CameraController::MouseLook(look) => self.is_controlled && look.is_looking(),
+            _ => false,
+        }
+    }
+
+    pub fn process_mouse_button(&mut self, button: MouseButton, pressed: bool) {
+        if !self.is_controlled {return;}
+        match &mut self.controller {
+            CameraController::Keyboard => (),
+            CameraController::MouseLook(look) => look.process_button(button, pressed),
+            CameraController::Orbit(orbit) => orbit.process_button(button, pressed),
+            CameraController::Pan(pan) => pan.process_button(button, pressed),
+        }
+    }
+
+    /// the cursor's position in the window, in pixels, used for dragging
+    pub fn process_cursor_moved(&mut self, position: [f64; 2]) {
+        let last = self.last_cursor.replace(position);
+        if !self.is_controlled {return;}
+        let delta = match last {
+            Some(last) => [(position[0] - last[0]) as f32, (position[1] - last[1]) as f32],
+            None => return,
+        };
+        let mut direction = self.direction();
+        let up = self.up();
+        match &mut self.controller {
+            CameraController::Orbit(orbit) => {
+                orbit.process_motion(delta, &mut self.position, &mut direction, up);
+                self.set_direction(direction);
+            },
+            CameraController::Pan(pan) => pan.process_motion(delta, &mut self.position, direction, up),
+            // mouse look uses the raw motion, as a grabbed cursor stops moving
+            CameraController::Keyboard | CameraController::MouseLook(_) => (),
+        }
+    }
+
+    /// raw mouse movement, which keeps coming while the cursor is grabbed
+    pub fn process_mouse_motion(&mut self, delta: [f64; 2]) {
+        if !self.is_controlled {return;}
+        if let CameraController::MouseLook(look) = &mut self.controller {
+            let (yaw, pitch) = look.process_motion([delta[0] as f32, delta[1] as f32]);
+            let limit = match self.pitch_limit {
+                Some(limit) => limit.min(look.max_pitch),
+                None => look.max_pitch,
+            };
+            self.rotate_yaw(yaw);
+            self.pitch_within(pitch, Some(limit));
+        }
+    }
+
+    /// the world space ray under the last cursor position seen by process_cursor_moved, for picking
+    pub fn cursor_ray(&self, viewport_size: [u32; 2]) -> Option<Ray> {
+        let cursor = self.last_cursor?;
+        Some(Ray::from_screen([cursor[0] as f32, cursor[1] as f32], viewport_size, self))
+    }
+
+    /// scroll wheel movement in lines, positive away from the user
+    pub fn process_scroll(&mut self, lines: f32) {
+        if !self.is_controlled {return;}
+        let mut direction = self.direction();
+        match &mut self.controller {
+            CameraController::Orbit(orbit) => {
+                orbit.process_scroll(lines, &mut self.position, &mut direction);
+                self.set_direction(direction);
+            },
+            CameraController::Pan(pan) => pan.process_scroll(lines, &mut self.position, direction),
+            CameraController::Keyboard | CameraController::MouseLook(_) => (),
+        }
+    }
+
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// the projection matrix for a viewport with the given width / height
+    pub fn get_projection_matrix(&self, aspect_ratio: f32) -> Matrix4 {
+        matrix4_from_columns(self.projection.matrix(aspect_ratio, self.reverse_z))
+    }
+
+    /// the projection matrix multiplied by the view matrix, taking world space straight to clip space
+    pub fn get_view_projection_matrix(&self, aspect_ratio: f32) -> Matrix4 {
+        self.get_projection_matrix(aspect_ratio) * self.get_view_matrix()
+    }
+
+    /// the view and projection matrices for a viewport of the given size in pixels
+    pub fn get_matrices(&self, viewport_size: [u32; 2]) -> (Matrix4, Matrix4) {
+        let aspect_ratio = viewport_size[0] as f32 / viewport_size[1] as f32;
+        (self.get_view_matrix(), self.get_projection_matrix(aspect_ratio))
+    }
+
     pub fn look_at(&mut self, target: Vector3) {
-        self.direction = (target - self.position).normalised();
+        self.set_direction(target - self.position);
     }
 
     pub fn process_key(&mut self, keycode: VirtualKeyCode, state: bool) {
-        match keycode {
-            VirtualKeyCode::W => {
-                self.movement[0] = state;
-            }
-            VirtualKeyCode::S => {
-                self.movement[1] = state;
-            }
-            VirtualKeyCode::A => {
-                self.movement[2] = state;
-            }
-            VirtualKeyCode::D => {
-                self.movement[3] = state;
-            }
-            VirtualKeyCode::Space => {
-                self.movement[4] = state;
-            }
-            VirtualKeyCode::C => {
-                self.movement[5] = state;
-            }
-            VirtualKeyCode::Q => {
-                self.movement[6] = state;
-            }
-            VirtualKeyCode::E => {
-                self.movement[7] = state;
-            }
-            VirtualKeyCode::R => {
-                self.movement[8] = state;
-            }
-            VirtualKeyCode::F => {
-                self.movement[9] = state;
-            }
-            _ => ()
+        self.process_input(InputSource::Key(keycode), if state {1.0} else {0.0});
+    }
+
+    /// sets how strongly an input is held, 0 for released and 1 for fully pressed
+    pub fn process_input(&mut self, input: InputSource, value: f32) {
+        if value > 0.0 {
+            self.inputs.insert(input, value);
+        } else {
+            self.inputs.remove(&input);
+        }
+    }
+
+    /// the position of an absolute device axis, such as a gamepad stick, split into its positive and negative directions for the input map
+    ///
+    /// The value is held until the axis reports a new one
+    pub fn process_axis(&mut self, axis: u32, value: f32) {
+        let (positive, negative) = self.input_map.axis_strengths(value);
+        self.process_input(InputSource::Axis {axis, positive: true}, positive);
+        self.process_input(InputSource::Axis {axis, positive: false}, negative);
+    }
+
+    /// movement along a relative device axis, such as a mouse, which only lasts for the next do_move
+    ///
+    /// Movement reported several times in a frame is added together
+    pub fn process_axis_motion(&mut self, axis: u32, delta: f32) {
+        let motion = self.axis_motion.entry(axis).or_insert(0.0);
+        *motion += delta;
+        let motion = *motion;
+        self.process_axis(axis, motion);
+    }
+
+    /// how strongly an action is held, from 0 to 1
+    pub fn action_strength(&self, action: CameraAction) -> f32 {
+        self.input_map
+            .inputs_for(action)
+            .filter_map(|input| self.inputs.get(&input))
+            .fold(0.0, |strongest, value| f32::max(strongest, *value))
+    }
+
+    /// lets go of every input, such as when the window loses focus and releases would be missed
+    pub fn release_inputs(&mut self) {
+        self.inputs.clear();
+        self.axis_motion.clear();
+    }
+
+    /// the camera's current position, direction and field of view as a keyframe at the given time
+    pub fn keyframe(&self, time: f32) -> CameraKeyframe {
+        CameraKeyframe {
+            time,
+            position: self.position.into(),
+            direction: self.direction().into(),
+            fov: self.projection.fov(),
+        }
+    }
+
+    /// moves the camera to a keyframe
+    pub fn apply_keyframe(&mut self, keyframe: &CameraKeyframe) {
+        self.position = keyframe.position.into();
+        self.set_direction(keyframe.direction.into());
+        if let Some(fov) = keyframe.fov {
+            self.projection.set_fov(fov);
+        }
+    }
+
+    /// starts moving the camera along a path from its beginning, taking over from the controls until it finishes or is stopped
+    pub fn play_path(&mut self, path: CameraPath) {
+        if let Some(start) = path.sample(0.0) {
+            self.apply_keyframe(&start);
+        }
+        self.playback = Some(PathPlayback {path, time: 0.0});
+    }
+
+    /// stops playback where the camera is, giving back the path
+    pub fn stop_path(&mut self) -> Option<CameraPath> {
+        self.playback.take().map(|playback| playback.path)
+    }
+
+    pub fn is_playing_path(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    /// seconds since the playing path started, counting every loop
+    pub fn path_time(&self) -> Option<f32> {
+        self.playback.as_ref().map(|playback| playback.time)
+    }
+
+    /// starts capturing a keyframe every interval seconds of do_move, however the camera is being moved
+    pub fn start_recording(&mut self, interval: f32) {
+        let mut path = CameraPath::default();
+        path.add_keyframe(self.keyframe(0.0));
+        self.recording = Some(PathRecording {path, interval, time: 0.0, since_keyframe: 0.0});
+    }
+
+    /// stops capturing, returning the recorded path with a final keyframe where the camera is now
+    pub fn stop_recording(&mut self) -> Option<CameraPath> {
+        let mut recording = self.recording.take()?;
+        if recording.since_keyframe > 0.0 {
+            recording.path.add_keyframe(self.keyframe(recording.time));
         }
+        Some(recording.path)
     }
 
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// advances the camera by a frame of time, playing a path if one is playing, otherwise moving with the held inputs
     pub fn do_move(&mut self, time: f32) {
+        if !self.advance_path(time) {
+            self.move_with_inputs(time);
+        }
+        self.record(time);
+        for (axis, _) in std::mem::take(&mut self.axis_motion) {
+            self.process_axis(axis, 0.0);
+        }
+    }
+
+    /// returns false when no path is playing
+    fn advance_path(&mut self, time: f32) -> bool {
+        let playback = match &mut self.playback {
+            Some(playback) => playback,
+            None => return false,
+        };
+        playback.time += time;
+        let frame = playback.path.sample(playback.time);
+        let finished = playback.path.is_finished(playback.time);
+        if let Some(frame) = frame {
+            self.apply_keyframe(&frame);
+        }
+        if finished {
+            self.playback = None;
+        }
+        true
+    }
+
+    fn record(&mut self, time: f32) {
+        let mut recording = match self.recording.take() {
+            Some(recording) => recording,
+            None => return,
+        };
+        recording.time += time;
+        recording.since_keyframe += time;
+        if recording.since_keyframe >= recording.interval {
+            recording.path.add_keyframe(self.keyframe(recording.time));
+            // the time past the interval counts towards the next keyframe, so frames that do not divide it evenly keep the spacing
+            recording.since_keyframe -= recording.interval;
+        }
+        self.recording = Some(recording);
+    }
+
+    fn move_with_inputs(&mut self, time: f32) {
         if !self.is_controlled {return;}
+        // the orbit controller decides where the camera is
+        if let CameraController::Orbit(_) = self.controller {return;}
 
-        // take cross of direction and up to get left
-        let mut left = self.direction.cross(self.up);
+        // movement stays level whatever the camera's pitch and roll
+        let up: Vector3 = WORLD_UP.into();
+        let direction = self.direction();
+        let mut heading = direction - up * direction.dot(up);
+        // looking straight up or down, the top of the screen points ahead when looking down and behind when looking up
+        if heading.dot(heading) < 1e-8 {
+            let screen_up = self.up();
+            heading = (screen_up - up * screen_up.dot(up)) * -direction.dot(up).signum();
+        }
+        let forward = heading.normalised();
+        let left = forward.cross(up).normalised();
+        let strength = |action| self.action_strength(action);
+        let forward_amount = strength(CameraAction::Forward) - strength(CameraAction::Back);
+        let left_amount = strength(CameraAction::Left) - strength(CameraAction::Right);
+        let up_amount = strength(CameraAction::Up) - strength(CameraAction::Down);
+        let spin = (strength(CameraAction::SpinRight) - strength(CameraAction::SpinLeft)) * self.rotate_speed * time;
+        let tilt = (strength(CameraAction::SpinForward) - strength(CameraAction::SpinBackward)) * self.rotate_speed * time;
+        let roll = (strength(CameraAction::RollRight) - strength(CameraAction::RollLeft)) * self.rotate_speed * time;
 
-        let forward = left.cross(self.up);
+        let move_step = self.move_speed * time;
         // forward/back
-        if self.movement[0] {self.position -= forward * self.move_speed * time}
-        if self.movement[1] {self.position += forward * self.move_speed * time}
+        self.position += forward * forward_amount * move_step;
         // left/right
-        if self.movement[2] {self.position += left * self.move_speed * time}
-        if self.movement[3] {self.position -= left * self.move_speed * time}
+        self.position += left * left_amount * move_step;
         // up/down
-        if self.movement[4] {self.position -= self.up * self.move_speed * time}
-        if self.movement[5] {self.position += self.up * self.move_speed * time}
+        self.position -= up * up_amount * move_step;
 
-        // spin around up
-        // normalise up
-        self.up.normalise();
-        // rotate
-        if self.movement[6] {
-            let rotation = Matrix3::from_angle_and_axis(self.rotate_speed * time, self.up);
-            self.direction = rotation * self.direction;
-        }
-        if self.movement[7] {
-            let rotation = Matrix3::from_angle_and_axis(-self.rotate_speed * time, self.up);
-            self.direction = rotation * self.direction;
+        if spin != 0.0 {
+            self.rotate_yaw(spin);
         }
-
-        // spin around left
-        // normalise left
-        left.normalise();
-        // rotate
-        if self.movement[8] {
-            let rotation = Matrix3::from_angle_and_axis(self.rotate_speed * time, left);
-            self.direction = rotation * self.direction;
+        if tilt != 0.0 {
+            self.rotate_pitch(tilt);
         }
-        if self.movement[9] {
-            let rotation = Matrix3::from_angle_and_axis(-self.rotate_speed * time, left);
-            self.direction = rotation * self.direction;
+        if roll != 0.0 {
+            self.rotate_roll(roll);
         }
     }
 }
 
+
+fn matrix4_from_columns(columns: [[f32; 4]; 4]) -> Matrix4 {
+    let [c0, c1, c2, c3] = columns;
+    Matrix4::new(
+        c0[0], c0[1], c0[2], c0[3],
+        c1[0], c1[1], c1[2], c1[3],
+        c2[0], c2[1], c2[2], c2[3],
+        c3[0], c3[1], c3[2], c3[3],
+    )
+}
@@ -0,0 +1,167 @@
+//! keyframed camera flythroughs, played back by Camera::do_move and saved and loaded as ron files
+use serde::{Deserialize, Serialize};
+use super::{error, Camera, Error};
+
+
+/// Where the camera is at one moment of a path
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CameraKeyframe {
+    /// seconds from the start of the path
+    pub time: f32,
+    pub position: [f32; 3],
+    pub direction: [f32; 3],
+    /// vertical field of view in radians, None keeps the camera's projection
+    #[serde(default)]
+    pub fov: Option<f32>,
+}
+
+
+/// How playback speeds up and slows down between each pair of keyframes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Easing::Linear
+    }
+}
+
+impl Easing {
+    /// maps a fraction of the way through from 0..1 to 0..1
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+
+/// A Catmull-Rom spline through a list of keyframes, sorted by time
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraPath {
+    pub keyframes: Vec<CameraKeyframe>,
+    /// a path that ends where it starts loops without a seam
+    pub looping: bool,
+    pub easing: Easing,
+}
+
+impl CameraPath {
+    pub fn new(keyframes: Vec<CameraKeyframe>) -> Self {
+        let mut path = CameraPath::default();
+        for keyframe in keyframes {
+            path.add_keyframe(keyframe);
+        }
+        path
+    }
+
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// inserts a keyframe in time order, after any others at the same time
+    pub fn add_keyframe(&mut self, keyframe: CameraKeyframe) {
+        let index = self.keyframes.partition_point(|other| other.time <= keyframe.time);
+        self.keyframes.insert(index, keyframe);
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    pub fn is_finished(&self, time: f32) -> bool {
+        !self.looping && time >= self.duration()
+    }
+
+    pub fn sample(&self, time: f32) -> Option<CameraKeyframe> {
+        let first = self.keyframes.first()?;
+        let duration = self.duration();
+        if duration <= 0.0 || self.keyframes.len() == 1 {
+            return Some(CameraKeyframe {time, ..*first});
+        }
+
+        let time = if self.looping {time.rem_euclid(duration)} else {time.clamp(0.0, duration)};
+
+        let last = self.keyframes.len() - 1;
+        let next = self.keyframes.partition_point(|keyframe| keyframe.time <= time).clamp(1, last);
+        let (from, to) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let span = to.time - from.time;
+        let t = self.easing.apply(if span > 0.0 {(time - from.time) / span} else {1.0});
+
+        // a looping path wraps round, its last keyframe standing in for the first
+        let (before, after) = if self.looping {
+            (if next >= 2 {next - 2} else {last - 1}, if next < last {next + 1} else {1})
+        } else {
+            (next.saturating_sub(2), (next + 1).min(last))
+        };
+        let (before, after) = (&self.keyframes[before], &self.keyframes[after]);
+        let mut position = [0.0; 3];
+        for i in 0..3 {
+            position[i] = catmull_rom(before.position[i], from.position[i], to.position[i], after.position[i], t);
+        }
+
+        let orientation = Camera::orientation_for(from.direction).slerp(Camera::orientation_for(to.direction), t);
+        Some(CameraKeyframe {
+            time,
+            position,
+            direction: orientation.rotate([1.0, 0.0, 0.0]),
+            fov: match (from.fov, to.fov) {
+                (Some(from), Some(to)) => Some(from + (to - from) * t),
+                (from, to) => from.or(to),
+            },
+        })
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, Error> {
+        error::from_ron(source)
+    }
+
+    pub fn to_ron(&self) -> Result<String, Error> {
+        error::to_ron(self)
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error> {
+        error::load_ron(path)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Error> {
+        error::save_ron(self, path)
+    }
+}
+
+
+pub(crate) struct PathPlayback {
+    pub path: CameraPath,
+    pub time: f32,
+}
+
+pub(crate) struct PathRecording {
+    pub path: CameraPath,
+    /// seconds between keyframes
+    pub interval: f32,
+    pub time: f32,
+    pub since_keyframe: f32,
+}
+
+
+/// the segment of a uniform Catmull-Rom spline between p1 and p2
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1 + (p2 - p0) * t + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2 + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
@@ -0,0 +1,254 @@
+//! the error type shared by every fallible function in the crate
+use std::fmt;
+use serde::{de::DeserializeOwned, Serialize};
+use winit::window::WindowId;
+use vulkano::{
+    buffer::BufferError,
+    image::{ImageError, view::ImageViewCreationError, immutable::ImmutableImageCreationError},
+    sampler::SamplerCreationError,
+    descriptor_set::DescriptorSetCreationError,
+    pipeline::{compute::ComputePipelineCreationError, graphics::GraphicsPipelineCreationError},
+    render_pass::{RenderPassCreationError, FramebufferCreationError},
+    shader::ShaderCreationError,
+    command_buffer::{CommandBufferBeginError, BuildError, PipelineExecutionError, RenderPassError, CopyError, CommandBufferExecError},
+    sync::FlushError,
+    swapchain::AcquireError,
+};
+use super::ObjError;
+
+
+/// Any error that can be returned from this crate
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    /// an obj or mtl file could not be read, parse errors carry the line they happened on
+    Obj(ObjError),
+    Gltf(gltf::Error),
+    /// the gltf file requires an extension that is not in SUPPORTED_GLTF_EXTENSIONS
+    UnsupportedGltfExtension(String),
+    /// the gltf file contains something the loader cannot represent, such as line primitives
+    UnsupportedGltf(String),
+    /// an image file could not be read or written
+    ImageFile(image::ImageError),
+    /// a ron config file, such as an InputMap, could not be parsed
+    RonParse(ron::error::SpannedError),
+    /// a value could not be written as ron
+    Ron(ron::Error),
+
+    // allocation
+    Buffer(BufferError),
+    Image(ImageError),
+    ImageView(ImageViewCreationError),
+    ImmutableImage(ImmutableImageCreationError),
+    Sampler(SamplerCreationError),
+    DescriptorSet(DescriptorSetCreationError),
+
+    // creation and compilation
+    ComputePipeline(ComputePipelineCreationError),
+    GraphicsPipeline(GraphicsPipelineCreationError),
+    RenderPass(RenderPassCreationError),
+    Framebuffer(FramebufferCreationError),
+    Shader(ShaderCreationError),
+    /// a shader file could not be compiled, holds the compiler's message
+    ShaderCompile(String),
+    /// the shader module has no entry point with the given name
+    MissingEntryPoint(String),
+    /// the pipeline layout has no descriptor set at the given index
+    MissingDescriptorSetLayout(usize),
+    /// the device supports none of the depth formats the pipeline can pick from
+    UnsupportedDepthFormat,
+    /// a pipeline that draws on top of its target, with clear set to false, was given a sample count other than Sample1
+    MultisampledWithoutClear,
+
+    // recording and execution
+    CommandBufferBegin(CommandBufferBeginError),
+    CommandBufferBuild(BuildError),
+    PipelineExecution(PipelineExecutionError),
+    RenderPassCommand(RenderPassError),
+    Copy(CopyError),
+    Execution(CommandBufferExecError),
+    Flush(FlushError),
+    Acquire(AcquireError),
+
+    /// there is no window, or no renderer for the window, with the given id
+    MissingWindow(WindowId),
+    /// a scene node was given itself or one of its descendants as a parent
+    SceneCycle,
+    /// no gui widget has the given key
+    MissingGuiWidget(String),
+    /// two gui widgets were given the same key
+    DuplicateGuiWidget(String),
+    /// the gui widget with the key holds a different type of value than was asked for
+    GuiWidgetType {
+        key: String,
+        expected: &'static str,
+    },
+}
+
+impl Error {
+    /// true if the swapchain needs recreating, the frame can be skipped and tried again
+    pub fn is_out_of_date(&self) -> bool {
+        match self {
+            Error::Acquire(AcquireError::OutOfDate) => true,
+            Error::Flush(FlushError::OutOfDate) => true,
+            _ => false,
+        }
+    }
+
+    /// true if the device has been lost, everything created from it needs recreating
+    pub fn is_device_lost(&self) -> bool {
+        match self {
+            Error::Acquire(AcquireError::DeviceLost) => true,
+            Error::Flush(FlushError::DeviceLost) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "io error: {}", err),
+            Error::Obj(err) => write!(f, "obj error: {}", err),
+            Error::Gltf(err) => write!(f, "gltf error: {}", err),
+            Error::UnsupportedGltfExtension(name) => write!(f, "gltf extension \"{}\" is not supported", name),
+            Error::UnsupportedGltf(message) => write!(f, "unsupported gltf content: {}", message),
+            Error::ImageFile(err) => write!(f, "image file error: {}", err),
+            Error::RonParse(err) => write!(f, "ron parse error: {}", err),
+            Error::Ron(err) => write!(f, "ron error: {}", err),
+
+            Error::Buffer(err) => write!(f, "buffer error: {}", err),
+            Error::Image(err) => write!(f, "image error: {}", err),
+            Error::ImageView(err) => write!(f, "image view creation error: {}", err),
+            Error::ImmutableImage(err) => write!(f, "immutable image creation error: {}", err),
+            Error::Sampler(err) => write!(f, "sampler creation error: {}", err),
+            Error::DescriptorSet(err) => write!(f, "descriptor set creation error: {}", err),
+
+            Error::ComputePipeline(err) => write!(f, "compute pipeline creation error: {}", err),
+            Error::GraphicsPipeline(err) => write!(f, "graphics pipeline creation error: {}", err),
+            Error::RenderPass(err) => write!(f, "render pass creation error: {}", err),
+            Error::Framebuffer(err) => write!(f, "framebuffer creation error: {}", err),
+            Error::Shader(err) => write!(f, "shader creation error: {}", err),
+            Error::ShaderCompile(message) => write!(f, "shader compile error: {}", message),
+            Error::MissingEntryPoint(name) => write!(f, "shader has no entry point called \"{}\"", name),
+            Error::MissingDescriptorSetLayout(index) => write!(f, "pipeline has no descriptor set layout at index {}", index),
+            Error::UnsupportedDepthFormat => write!(f, "no supported depth format found"),
+            Error::MultisampledWithoutClear => write!(f, "a pipeline that does not clear its target can only use SampleCount::Sample1"),
+
+            Error::CommandBufferBegin(err) => write!(f, "could not begin command buffer: {}", err),
+            Error::CommandBufferBuild(err) => write!(f, "could not build command buffer: {}", err),
+            Error::PipelineExecution(err) => write!(f, "pipeline execution error: {}", err),
+            Error::RenderPassCommand(err) => write!(f, "render pass error: {}", err),
+            Error::Copy(err) => write!(f, "copy error: {}", err),
+            Error::Execution(err) => write!(f, "command buffer execution error: {}", err),
+            Error::Flush(err) => write!(f, "flush error: {}", err),
+            Error::Acquire(err) => write!(f, "swapchain acquire error: {}", err),
+
+            Error::MissingWindow(id) => write!(f, "no window with id {:?}", id),
+            Error::SceneCycle => write!(f, "a scene node cannot be parented to itself or its descendants"),
+            Error::MissingGuiWidget(key) => write!(f, "no gui widget called \"{}\"", key),
+            Error::DuplicateGuiWidget(key) => write!(f, "more than one gui widget called \"{}\"", key),
+            Error::GuiWidgetType {key, expected} => write!(f, "gui widget \"{}\" does not hold a {}", key, expected),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Obj(err) => Some(err),
+            Error::Gltf(err) => Some(err),
+            Error::ImageFile(err) => Some(err),
+            Error::RonParse(err) => Some(err),
+            Error::Ron(err) => Some(err),
+
+            Error::Buffer(err) => Some(err),
+            Error::Image(err) => Some(err),
+            Error::ImageView(err) => Some(err),
+            Error::ImmutableImage(err) => Some(err),
+            Error::Sampler(err) => Some(err),
+            Error::DescriptorSet(err) => Some(err),
+
+            Error::ComputePipeline(err) => Some(err),
+            Error::GraphicsPipeline(err) => Some(err),
+            Error::RenderPass(err) => Some(err),
+            Error::Framebuffer(err) => Some(err),
+            Error::Shader(err) => Some(err),
+
+            Error::CommandBufferBegin(err) => Some(err),
+            Error::CommandBufferBuild(err) => Some(err),
+            Error::PipelineExecution(err) => Some(err),
+            Error::RenderPassCommand(err) => Some(err),
+            Error::Copy(err) => Some(err),
+            Error::Execution(err) => Some(err),
+            Error::Flush(err) => Some(err),
+            Error::Acquire(err) => Some(err),
+
+            _ => None,
+        }
+    }
+}
+
+
+macro_rules! impl_from_error {
+    ($($source: ty => $variant: ident),* $(,)?) => {
+        $(
+            impl From<$source> for Error {
+                fn from(err: $source) -> Self {
+                    Error::$variant(err)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_error!(
+    std::io::Error => Io,
+    ObjError => Obj,
+    gltf::Error => Gltf,
+    image::ImageError => ImageFile,
+    ron::error::SpannedError => RonParse,
+    ron::Error => Ron,
+
+    BufferError => Buffer,
+    ImageError => Image,
+    ImageViewCreationError => ImageView,
+    ImmutableImageCreationError => ImmutableImage,
+    SamplerCreationError => Sampler,
+    DescriptorSetCreationError => DescriptorSet,
+
+    ComputePipelineCreationError => ComputePipeline,
+    GraphicsPipelineCreationError => GraphicsPipeline,
+    RenderPassCreationError => RenderPass,
+    FramebufferCreationError => Framebuffer,
+    ShaderCreationError => Shader,
+
+    CommandBufferBeginError => CommandBufferBegin,
+    BuildError => CommandBufferBuild,
+    PipelineExecutionError => PipelineExecution,
+    RenderPassError => RenderPassCommand,
+    CopyError => Copy,
+    CommandBufferExecError => Execution,
+    FlushError => Flush,
+    AcquireError => Acquire,
+);
+
+
+/// parses a ron value, such as an InputMap or a CameraPath
+pub(crate) fn from_ron<T: DeserializeOwned>(source: &str) -> Result<T, Error> {
+    Ok(ron::from_str(source)?)
+}
+
+pub(crate) fn to_ron<T: Serialize>(value: &T) -> Result<String, Error> {
+    Ok(ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?)
+}
+
+pub(crate) fn load_ron<T: DeserializeOwned>(path: &str) -> Result<T, Error> {
+    from_ron(&std::fs::read_to_string(path)?)
+}
+
+pub(crate) fn save_ron<T: Serialize>(value: &T, path: &str) -> Result<(), Error> {
+    std::fs::write(path, to_ron(value)?)?;
+    Ok(())
+}
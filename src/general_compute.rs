@@ -7,6 +7,7 @@ use vulkano::{
     sync::{self, GpuFuture}, buffer::BufferContents,
 };
 use vulkano_util::context::{VulkanoContext, VulkanoConfig};
+use super::Error;
 
 
 
@@ -27,14 +28,15 @@ pub fn create_compute_pipeline(
     context: &VulkanoContext,
     shader: &Arc<ShaderModule>,
     entry_point: &str,
-) -> Arc<ComputePipeline> {
-    ComputePipeline::new(
+) -> Result<Arc<ComputePipeline>, Error> {
+    let entry = shader.entry_point(entry_point).ok_or_else(|| Error::MissingEntryPoint(entry_point.to_string()))?;
+    Ok(ComputePipeline::new(
         context.device().clone(),
-        shader.entry_point(entry_point).unwrap(),
+        entry,
         &(),
         None,
         |_| {}
-    ).unwrap()
+    )?)
 }
 
 
@@ -43,15 +45,15 @@ pub fn get_descriptor_set(
     descriptor_set_allocator: &Arc<StandardDescriptorSetAllocator>,
     set_index: usize,
     write_sets: impl IntoIterator<Item = WriteDescriptorSet>
-) -> Arc<PersistentDescriptorSet> {
+) -> Result<Arc<PersistentDescriptorSet>, Error> {
     let layout = pipeline.layout().set_layouts();
 
 
-    PersistentDescriptorSet::new(
+    Ok(PersistentDescriptorSet::new(
         descriptor_set_allocator,
-        layout.get(set_index).unwrap().clone(),
+        layout.get(set_index).ok_or(Error::MissingDescriptorSetLayout(set_index))?.clone(),
         write_sets
-    ).unwrap()
+    )?)
 }
 
 pub fn run_compute_operation<S>(
@@ -62,7 +64,7 @@ pub fn run_compute_operation<S>(
     work_group_size: [u32; 3],
     descriptor_sets: S,
     timeout: Option<Duration>
-)
+) -> Result<(), Error>
 where
     S: DescriptorSetsCollection,
 {
@@ -70,7 +72,7 @@ where
         command_allocator,
         context.compute_queue().queue_family_index(),
         CommandBufferUsage::OneTimeSubmit
-    ).unwrap();
+    )?;
 
     builder
         .bind_pipeline_compute(pipeline.clone())
@@ -80,18 +82,16 @@ where
             0,
             descriptor_sets
         )
-        .dispatch([num_to_process[0] / work_group_size[0], num_to_process[1] / work_group_size[1], num_to_process[2] / work_group_size[2]])
-        .unwrap();
+        .dispatch([num_to_process[0] / work_group_size[0], num_to_process[1] / work_group_size[1], num_to_process[2] / work_group_size[2]])?;
 
-    let command_buffer = builder.build().unwrap();
+    let command_buffer = builder.build()?;
 
     let future = sync::now(context.device().clone())
-        .then_execute(context.compute_queue().clone(), command_buffer)
-        .unwrap()
-        .then_signal_fence_and_flush()
-        .unwrap();
+        .then_execute(context.compute_queue().clone(), command_buffer)?
+        .then_signal_fence_and_flush()?;
 
-    future.wait(timeout).unwrap();
+    future.wait(timeout)?;
+    Ok(())
 }
 
 pub fn run_compute_operation_push_constants<S, T>(
@@ -103,7 +103,7 @@ pub fn run_compute_operation_push_constants<S, T>(
     descriptor_sets: S,
     push_constants: T,
     timeout: Option<Duration>
-)
+) -> Result<(), Error>
 where
     S: DescriptorSetsCollection,
     T: BufferContents,
@@ -112,7 +112,7 @@ where
         command_allocator,
         context.compute_queue().queue_family_index(),
         CommandBufferUsage::OneTimeSubmit
-    ).unwrap();
+    )?;
 
     builder
         .bind_pipeline_compute(pipeline.clone())
@@ -123,16 +123,14 @@ where
             0,
             descriptor_sets
         )
-        .dispatch([num_to_process[0] / work_group_size[0], num_to_process[1] / work_group_size[1], num_to_process[2] / work_group_size[2]])
-        .unwrap();
+        .dispatch([num_to_process[0] / work_group_size[0], num_to_process[1] / work_group_size[1], num_to_process[2] / work_group_size[2]])?;
 
-    let command_buffer = builder.build().unwrap();
+    let command_buffer = builder.build()?;
 
     let future = sync::now(context.device().clone())
-        .then_execute(context.compute_queue().clone(), command_buffer)
-        .unwrap()
-        .then_signal_fence_and_flush()
-        .unwrap();
+        .then_execute(context.compute_queue().clone(), command_buffer)?
+        .then_signal_fence_and_flush()?;
 
-    future.wait(timeout).unwrap();
+    future.wait(timeout)?;
+    Ok(())
 }
\ No newline at end of file
@@ -6,7 +6,7 @@ use winit::{
 use bytemuck::{Pod, Zeroable};
 use vulkano_util::{context::*, window::*};
 use super::Camera;
-use maths::{Vector3, Matrix4, Matrix3};
+use maths::{Vector3, Matrix4};
 use vulkano::{
     pipeline::graphics::vertex_input::Vertex,
     command_buffer::allocator::StandardCommandBufferAllocator,
@@ -64,6 +64,21 @@ impl From<[f32; 3]> for PositionVertex {
     }
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Zeroable, Pod, Vertex)]
+pub struct TexturedVertex {
+    #[format(R32G32B32_SFLOAT)]
+    pub position: [f32; 3],
+    #[format(R32G32_SFLOAT)]
+    pub tex_coord: [f32; 2],
+}
+
+impl Position for TexturedVertex {
+    fn pos(&self) -> [f32; 3] {
+        self.position
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, Zeroable, Pod, Vertex)]
 pub struct Normal {
@@ -77,9 +92,60 @@ impl From<Vector3> for Normal {
     }
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Zeroable, Pod, Vertex)]
+pub struct TexCoord {
+    #[format(R32G32_SFLOAT)]
+    pub tex_coord: [f32; 2],
+}
+
+impl From<[f32; 2]> for TexCoord {
+    fn from(value: [f32; 2]) -> Self {
+        TexCoord {tex_coord: value}
+    }
+}
+
+/// a tangent with the handedness of the bitangent in w
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Zeroable, Pod, Vertex)]
+pub struct Tangent {
+    #[format(R32G32B32A32_SFLOAT)]
+    pub tangent: [f32; 4],
+}
+
+/// a per instance model matrix, the shader input must be called model and takes four locations
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Zeroable, Pod, Vertex)]
+pub struct InstanceModel {
+    #[format(R32G32B32A32_SFLOAT)]
+    pub model: [[f32; 4]; 4],
+}
+
+impl From<Matrix4> for InstanceModel {
+    fn from(value: Matrix4) -> Self {
+        InstanceModel {model: value.into()}
+    }
+}
+
+/// a per instance model matrix and colour, the shader inputs must be called model and instance_colour
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Zeroable, Pod, Vertex)]
+pub struct ColouredInstance {
+    #[format(R32G32B32A32_SFLOAT)]
+    pub model: [[f32; 4]; 4],
+    #[format(R32G32B32A32_SFLOAT)]
+    pub instance_colour: [f32; 4],
+}
+
+impl ColouredInstance {
+    pub fn new(model: Matrix4, colour: [f32; 4]) -> Self {
+        ColouredInstance {model: model.into(), instance_colour: colour}
+    }
+}
+
 pub mod vertex_defs {
     use vulkano::pipeline::graphics::vertex_input::{Vertex, VertexBufferDescription};
-    use super::{PositionVertex, ColouredVertex, Normal};
+    use super::{PositionVertex, ColouredVertex, TexturedVertex, Normal, InstanceModel, ColouredInstance};
 
     pub fn position() -> [VertexBufferDescription; 1]{
         [PositionVertex::per_vertex()]
@@ -87,6 +153,9 @@ pub mod vertex_defs {
     pub fn coloured() -> [VertexBufferDescription; 1]{
         [ColouredVertex::per_vertex()]
     }
+    pub fn textured() -> [VertexBufferDescription; 1]{
+        [TexturedVertex::per_vertex()]
+    }
 
     pub fn position_normal() -> [VertexBufferDescription; 2]{
         [PositionVertex::per_vertex(), Normal::per_vertex()]
@@ -94,6 +163,27 @@ pub mod vertex_defs {
     pub fn coloured_normal() -> [VertexBufferDescription; 2]{
         [ColouredVertex::per_vertex(), Normal::per_vertex()]
     }
+    pub fn textured_normal() -> [VertexBufferDescription; 2]{
+        [TexturedVertex::per_vertex(), Normal::per_vertex()]
+    }
+
+    // instanced defs, the instance buffer always comes after the vertex and normal buffers
+    pub fn position_instanced() -> [VertexBufferDescription; 2]{
+        [PositionVertex::per_vertex(), InstanceModel::per_instance()]
+    }
+    pub fn coloured_instanced() -> [VertexBufferDescription; 2]{
+        [ColouredVertex::per_vertex(), InstanceModel::per_instance()]
+    }
+    pub fn position_coloured_instanced() -> [VertexBufferDescription; 2]{
+        [PositionVertex::per_vertex(), ColouredInstance::per_instance()]
+    }
+
+    pub fn position_normal_instanced() -> [VertexBufferDescription; 3]{
+        [PositionVertex::per_vertex(), Normal::per_vertex(), InstanceModel::per_instance()]
+    }
+    pub fn position_normal_coloured_instanced() -> [VertexBufferDescription; 3]{
+        [PositionVertex::per_vertex(), Normal::per_vertex(), ColouredInstance::per_instance()]
+    }
 }
 
 #[macro_export]
@@ -160,21 +250,10 @@ pub fn create_uniform_buffer_allocator(
 
 
 
+/// the view and projection matrices of the camera, using the camera's projection settings
 pub fn get_generic_uniforms(
     swapchain_size: [u32; 2],
     camera: &Camera,
 ) -> (Matrix4, Matrix4){
-    
-    let aspect_ratio = swapchain_size[0] as f32 / swapchain_size[1] as f32;
-
-    let proj = Matrix4::persective_matrix(
-        std::f32::consts::FRAC_PI_2,
-        aspect_ratio,
-        0.01,
-        100.0,
-    );
-
-    let scale = Matrix4::from(Matrix3::from_scale(1.0));
-
-    (scale * camera.get_view_matrix(), proj)
+    camera.get_matrices(swapchain_size)
 }
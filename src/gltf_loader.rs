@@ -0,0 +1,293 @@
+//! loading of gltf 2.0 files (.gltf and .glb) into meshes, materials and a node hierarchy
+use std::{fs, path::Path};
+use gltf::{Gltf, Document, mesh::Mode, image::Format as GltfFormat};
+use maths::Matrix4;
+use super::{Mesh, PositionVertex, ColouredVertex, TexturedVertex, Normal, TexCoord, Tangent, Error, Transform};
+
+
+/// Extensions that the loader understands, a file that requires any others is rejected
+///
+/// Extensions a file only uses optionally are ignored, as the file still loads correctly without them
+pub const SUPPORTED_GLTF_EXTENSIONS: &[&str] = &[];
+
+
+/// How the alpha of a material's base colour is used
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlphaMode {
+    Opaque,
+    /// fragments with alpha below the cutoff are discarded
+    Mask(f32),
+    Blend,
+}
+
+/// A metallic-roughness material from a gltf file
+///
+/// Textures are indices into the images of the scene
+#[derive(Debug, Clone)]
+pub struct GltfMaterial {
+    pub name: Option<String>,
+    pub base_colour_factor: [f32; 4],
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub emissive_factor: [f32; 3],
+    pub base_colour_texture: Option<usize>,
+    pub metallic_roughness_texture: Option<usize>,
+    pub normal_texture: Option<usize>,
+    pub normal_scale: f32,
+    pub occlusion_texture: Option<usize>,
+    pub occlusion_strength: f32,
+    pub emissive_texture: Option<usize>,
+    pub alpha_mode: AlphaMode,
+    pub double_sided: bool,
+}
+
+/// An image from a gltf file, converted to 8 bit rgba
+#[derive(Debug, Clone)]
+pub struct GltfImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// One drawable part of a gltf mesh, with a single material
+#[derive(Debug, Clone)]
+pub struct GltfPrimitive {
+    pub mesh: Mesh<PositionVertex>,
+    /// the following are one per vertex, or empty if the file did not have them
+    pub tex_coords: Vec<TexCoord>,
+    pub tangents: Vec<Tangent>,
+    pub colours: Vec<[f32; 4]>,
+    /// index into the materials of the scene
+    pub material: Option<usize>,
+}
+
+impl GltfPrimitive {
+    /// returns the mesh with its vertex colours, white if it has none
+    pub fn coloured_mesh(&self) -> Mesh<ColouredVertex> {
+        let vertices = self.mesh.vertices.iter().enumerate().map(|(i, vertex)| ColouredVertex {
+            position: vertex.position,
+            colour: self.colours.get(i).copied().unwrap_or([1.0; 4]),
+        }).collect();
+        let mut mesh = Mesh::new(vertices, self.mesh.indices.clone());
+        mesh.set_normals(self.mesh.normals.clone());
+        mesh
+    }
+
+    /// returns the mesh with its first set of texture coordinates, (0, 0) for every vertex if it has none
+    pub fn textured_mesh(&self) -> Mesh<TexturedVertex> {
+        let vertices = self.mesh.vertices.iter().enumerate().map(|(i, vertex)| TexturedVertex {
+            position: vertex.position,
+            tex_coord: self.tex_coords.get(i).map_or([0.0; 2], |coord| coord.tex_coord),
+        }).collect();
+        let mut mesh = Mesh::new(vertices, self.mesh.indices.clone());
+        mesh.set_normals(self.mesh.normals.clone());
+        mesh
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+/// A node of the hierarchy, the transform is relative to the parent
+#[derive(Debug, Clone)]
+pub struct GltfNode {
+    pub name: Option<String>,
+    pub translation: [f32; 3],
+    /// quaternion, in x, y, z, w order
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    /// index into the meshes of the scene
+    pub mesh: Option<usize>,
+}
+
+impl GltfNode {
+    /// the local transform as a matrix
+    pub fn local_matrix(&self) -> Matrix4 {
+        Transform::from(self).matrix()
+    }
+}
+
+/// The contents of a gltf file
+#[derive(Debug, Clone, Default)]
+pub struct GltfScene {
+    pub nodes: Vec<GltfNode>,
+    /// the nodes at the top of the hierarchy of the default scene
+    pub roots: Vec<usize>,
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<GltfMaterial>,
+    pub images: Vec<GltfImage>,
+}
+
+impl GltfScene {
+    /// returns the material assigned to a primitive of this scene
+    pub fn material(&self, primitive: &GltfPrimitive) -> Option<&GltfMaterial> {
+        primitive.material.and_then(|index| self.materials.get(index))
+    }
+}
+
+
+/// loads a .gltf or .glb file, along with any buffers and images it references
+///
+/// Files that require extensions not in SUPPORTED_GLTF_EXTENSIONS are rejected
+pub fn load_gltf(path: &str) -> Result<GltfScene, Error> {
+    let path = Path::new(path);
+    let bytes = fs::read(path)?;
+
+    let gltf = Gltf::from_slice_without_validation(&bytes)?;
+    if let Some(extension) = gltf.document.extensions_required().find(|ext| !SUPPORTED_GLTF_EXTENSIONS.contains(ext)) {
+        return Err(Error::UnsupportedGltfExtension(extension.to_string()));
+    }
+    let document = Document::from_json(gltf.document.into_json())?;
+
+    let base = path.parent();
+    let buffers = gltf::import_buffers(&document, base, gltf.blob)?;
+    let images = gltf::import_images(&document, base, &buffers)?;
+
+    let mut meshes = Vec::new();
+    for mesh in document.meshes() {
+        let mut primitives = Vec::new();
+        for primitive in mesh.primitives() {
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+            let vertices: Vec<PositionVertex> = match reader.read_positions() {
+                Some(positions) => positions.map(|position| position.into()).collect(),
+                None => return Err(Error::UnsupportedGltf(format!("primitive {} of mesh {} has no positions", primitive.index(), mesh.index()))),
+            };
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..vertices.len() as u32).collect(),
+            };
+            let indices = match primitive.mode() {
+                Mode::Triangles => indices,
+                Mode::TriangleStrip => (0..indices.len().saturating_sub(2)).flat_map(|i| {
+                    // every other triangle is flipped to keep the winding consistent
+                    if i % 2 == 0 {[indices[i], indices[i + 1], indices[i + 2]]} else {[indices[i + 1], indices[i], indices[i + 2]]}
+                }).collect(),
+                Mode::TriangleFan => (1..indices.len().saturating_sub(1)).flat_map(|i| [indices[0], indices[i], indices[i + 1]]).collect(),
+                mode => return Err(Error::UnsupportedGltf(format!("primitive {} of mesh {} uses {:?}, only triangles are supported", primitive.index(), mesh.index(), mode))),
+            };
+
+            let mut triangles = Mesh::new(vertices, indices);
+            match reader.read_normals() {
+                Some(normals) => {triangles.set_normals(normals.map(|normal| Normal {normal}).collect());}
+                None => {triangles.recalculate_normals();}
+            }
+
+            primitives.push(GltfPrimitive {
+                mesh: triangles,
+                tex_coords: reader.read_tex_coords(0).map_or(Vec::new(), |coords| coords.into_f32().map(|tex_coord| TexCoord {tex_coord}).collect()),
+                tangents: reader.read_tangents().map_or(Vec::new(), |tangents| tangents.map(|tangent| Tangent {tangent}).collect()),
+                colours: reader.read_colors(0).map_or(Vec::new(), |colours| colours.into_rgba_f32().collect()),
+                material: primitive.material().index(),
+            });
+        }
+        meshes.push(GltfMesh {
+            name: mesh.name().map(|name| name.to_string()),
+            primitives,
+        });
+    }
+
+    let materials = document.materials().map(|material| {
+        let pbr = material.pbr_metallic_roughness();
+        GltfMaterial {
+            name: material.name().map(|name| name.to_string()),
+            base_colour_factor: pbr.base_color_factor(),
+            metallic_factor: pbr.metallic_factor(),
+            roughness_factor: pbr.roughness_factor(),
+            emissive_factor: material.emissive_factor(),
+            base_colour_texture: pbr.base_color_texture().map(|info| info.texture().source().index()),
+            metallic_roughness_texture: pbr.metallic_roughness_texture().map(|info| info.texture().source().index()),
+            normal_texture: material.normal_texture().map(|normal| normal.texture().source().index()),
+            normal_scale: material.normal_texture().map_or(1.0, |normal| normal.scale()),
+            occlusion_texture: material.occlusion_texture().map(|occlusion| occlusion.texture().source().index()),
+            occlusion_strength: material.occlusion_texture().map_or(1.0, |occlusion| occlusion.strength()),
+            emissive_texture: material.emissive_texture().map(|info| info.texture().source().index()),
+            alpha_mode: match material.alpha_mode() {
+                gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+                gltf::material::AlphaMode::Mask => AlphaMode::Mask(material.alpha_cutoff().unwrap_or(0.5)),
+                gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+            },
+            double_sided: material.double_sided(),
+        }
+    }).collect();
+
+    let mut nodes: Vec<GltfNode> = document.nodes().map(|node| {
+        let (translation, rotation, scale) = node.transform().decomposed();
+        GltfNode {
+            name: node.name().map(|name| name.to_string()),
+            translation,
+            rotation,
+            scale,
+            parent: None,
+            children: node.children().map(|child| child.index()).collect(),
+            mesh: node.mesh().map(|mesh| mesh.index()),
+        }
+    }).collect();
+    for i in 0..nodes.len() {
+        for child in nodes[i].children.clone() {
+            nodes[child].parent = Some(i);
+        }
+    }
+
+    let roots = match document.default_scene().or_else(|| document.scenes().next()) {
+        Some(scene) => scene.nodes().map(|node| node.index()).collect(),
+        None => (0..nodes.len()).filter(|i| nodes[*i].parent.is_none()).collect(),
+    };
+
+    Ok(GltfScene {
+        nodes,
+        roots,
+        meshes,
+        materials,
+        images: images.into_iter().map(convert_image).collect(),
+    })
+}
+
+
+/// converts decoded image data into 8 bit rgba
+fn convert_image(image: gltf::image::Data) -> GltfImage {
+    // channels, bytes per channel
+    let (channels, size) = match image.format {
+        GltfFormat::R8 => (1, 1),
+        GltfFormat::R8G8 => (2, 1),
+        GltfFormat::R8G8B8 => (3, 1),
+        GltfFormat::R8G8B8A8 => (4, 1),
+        GltfFormat::R16 => (1, 2),
+        GltfFormat::R16G16 => (2, 2),
+        GltfFormat::R16G16B16 => (3, 2),
+        GltfFormat::R16G16B16A16 => (4, 2),
+        GltfFormat::R32G32B32FLOAT => (3, 4),
+        GltfFormat::R32G32B32A32FLOAT => (4, 4),
+    };
+
+    let read_channel = |bytes: &[u8]| -> u8 {
+        match size {
+            1 => bytes[0],
+            2 => (u16::from_ne_bytes([bytes[0], bytes[1]]) >> 8) as u8,
+            _ => (f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]).clamp(0.0, 1.0) * 255.0).round() as u8,
+        }
+    };
+
+    let mut pixels = Vec::with_capacity((image.width * image.height * 4) as usize);
+    for pixel in image.pixels.chunks_exact(channels * size) {
+        let values: Vec<u8> = pixel.chunks_exact(size).map(read_channel).collect();
+        match channels {
+            // greyscale
+            1 => pixels.extend_from_slice(&[values[0], values[0], values[0], 255]),
+            2 => pixels.extend_from_slice(&[values[0], values[1], 0, 255]),
+            3 => pixels.extend_from_slice(&[values[0], values[1], values[2], 255]),
+            _ => pixels.extend_from_slice(&values),
+        }
+    }
+
+    GltfImage {
+        width: image.width,
+        height: image.height,
+        pixels,
+    }
+}
@@ -1,35 +1,578 @@
-use std::{ops::RangeInclusive, fmt::Debug};
-use egui::{self, Color32, Context, FontFamily, FontDefinitions, FontData, epaint::Shadow, Stroke, CentralPanel, ScrollArea, Checkbox, Slider, Label, TextEdit, DragValue};
+use std::{ops::RangeInclusive, fmt::Debug, collections::VecDeque};
+use egui::{self, Color32, Context, Window, FontFamily, FontDefinitions, FontData, epaint::Shadow, Stroke, CentralPanel, ScrollArea, Checkbox, Slider, Label, TextEdit, DragValue, Button, ComboBox, CollapsingHeader};
+use egui::plot::{Line, PlotPoints};
 use egui_winit_vulkano::{Gui, GuiConfig};
 use vulkano::sync::GpuFuture;
 use vulkano_util::{window::VulkanoWindows, renderer::VulkanoWindowRenderer};
 use winit::{window::WindowId, event_loop::EventLoop, event::WindowEvent};
+use super::Error;
+
+
+/// One widget in a gui window, holding its current value
+#[derive(Clone, Debug, PartialEq)]
+pub enum GuiWidget {
+    /// a heading that starts a new section, it holds no value
+    Section,
+    Checkbox(bool),
+    F32Slider(f32, RangeInclusive<f32>),
+    I32Slider(i32, RangeInclusive<i32>),
+    F32Box(f32),
+    I32Box(i32),
+    U32Box(u32),
+    TextBox(String),
+    /// true once clicked, until the click is taken with GuiWidgets::take_click
+    Button(bool),
+    Rgb([f32; 3]),
+    /// an unmultiplied colour with alpha
+    Rgba([f32; 4]),
+    /// a choice of one option, such as a variant of an enum
+    Combo {
+        options: Vec<String>,
+        selected: usize,
+    },
+    /// three numbers edited side by side, such as a position or direction
+    Vec3([f32; 3]),
+    /// widgets inside a collapsible header, their keys share the window's namespace, open only sets how it starts
+    Group {
+        open: bool,
+        widgets: GuiWidgets,
+    },
+    /// a line graph of the most recent values pushed with GuiWidgets::push_plot, such as frame times
+    Plot {
+        values: VecDeque<f32>,
+        capacity: usize,
+    },
+}
+
+
+/// The widgets of a gui window in the order they are drawn, looked up by their keys, which are also their labels
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GuiWidgets {
+    entries: Vec<(String, GuiWidget)>,
+}
+
+impl GuiWidgets {
+    pub fn new() -> Self {
+        GuiWidgets::default()
+    }
+
+    /// adds a widget after the others, sections can share a title but any other widget's key must be unique, including those in groups
+    pub fn add(&mut self, key: impl Into<String>, widget: GuiWidget) -> Result<(), Error> {
+        let key = key.into();
+        if widget != GuiWidget::Section && self.get(&key).is_some() {
+            return Err(Error::DuplicateGuiWidget(key));
+        }
+        if let GuiWidget::Group {widgets, ..} = &widget {
+            if let Some(duplicate) = widgets.keys().find(|inner| *inner == key || self.get(inner).is_some()) {
+                return Err(Error::DuplicateGuiWidget(duplicate.to_string()));
+            }
+        }
+        self.entries.push((key, widget));
+        Ok(())
+    }
+
+    /// the widget with the key, searching inside groups too
+    pub fn get(&self, key: &str) -> Option<&GuiWidget> {
+        for (other, widget) in self.entries.iter() {
+            if *widget == GuiWidget::Section {continue;}
+            if other == key {
+                return Some(widget);
+            }
+            if let GuiWidget::Group {widgets, ..} = widget {
+                if let Some(found) = widgets.get(key) {
+                    return Some(found);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut GuiWidget> {
+        for (other, widget) in self.entries.iter_mut() {
+            if *widget == GuiWidget::Section {continue;}
+            if other.as_str() == key {
+                return Some(widget);
+            }
+            if let GuiWidget::Group {widgets, ..} = widget {
+                if let Some(found) = widgets.get_mut(key) {
+                    return Some(found);
+                }
+            }
+        }
+        None
+    }
+
+    /// the keys of every widget that holds a value, including those in groups
+    fn keys(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.entries.iter().filter(|(_, widget)| *widget != GuiWidget::Section).flat_map(|(key, widget)| {
+            let inner = match widget {
+                GuiWidget::Group {widgets, ..} => Some(widgets.keys()),
+                _ => None,
+            };
+            std::iter::once(key.as_str()).chain(inner.into_iter().flatten())
+        }))
+    }
+
+    /// every key and widget, in order
+    pub fn iter(&self) -> impl Iterator<Item = &(String, GuiWidget)> {
+        self.entries.iter()
+    }
+
+    pub fn get_bool(&self, key: &str) -> Result<bool, Error> {
+        match self.find(key)? {
+            GuiWidget::Checkbox(value) => Ok(*value),
+            _ => Err(wrong_type(key, "bool")),
+        }
+    }
+
+    /// the value of an f32 slider or box
+    pub fn get_f32(&self, key: &str) -> Result<f32, Error> {
+        match self.find(key)? {
+            GuiWidget::F32Slider(value, _) | GuiWidget::F32Box(value) => Ok(*value),
+            _ => Err(wrong_type(key, "f32")),
+        }
+    }
+
+    /// the value of an i32 slider or box
+    pub fn get_i32(&self, key: &str) -> Result<i32, Error> {
+        match self.find(key)? {
+            GuiWidget::I32Slider(value, _) | GuiWidget::I32Box(value) => Ok(*value),
+            _ => Err(wrong_type(key, "i32")),
+        }
+    }
+
+    pub fn get_u32(&self, key: &str) -> Result<u32, Error> {
+        match self.find(key)? {
+            GuiWidget::U32Box(value) => Ok(*value),
+            _ => Err(wrong_type(key, "u32")),
+        }
+    }
+
+    pub fn get_string(&self, key: &str) -> Result<&str, Error> {
+        match self.find(key)? {
+            GuiWidget::TextBox(value) => Ok(value),
+            _ => Err(wrong_type(key, "string")),
+        }
+    }
+
+    pub fn set_bool(&mut self, key: &str, new_value: bool) -> Result<(), Error> {
+        match self.find_mut(key)? {
+            GuiWidget::Checkbox(value) => *value = new_value,
+            _ => return Err(wrong_type(key, "bool")),
+        }
+        Ok(())
+    }
+
+    /// sets an f32 slider or box, sliders clamp the value to their range
+    pub fn set_f32(&mut self, key: &str, new_value: f32) -> Result<(), Error> {
+        match self.find_mut(key)? {
+            GuiWidget::F32Slider(value, range) => *value = new_value.clamp(*range.start(), *range.end()),
+            GuiWidget::F32Box(value) => *value = new_value,
+            _ => return Err(wrong_type(key, "f32")),
+        }
+        Ok(())
+    }
+
+    /// sets an i32 slider or box, sliders clamp the value to their range
+    pub fn set_i32(&mut self, key: &str, new_value: i32) -> Result<(), Error> {
+        match self.find_mut(key)? {
+            GuiWidget::I32Slider(value, range) => *value = new_value.clamp(*range.start(), *range.end()),
+            GuiWidget::I32Box(value) => *value = new_value,
+            _ => return Err(wrong_type(key, "i32")),
+        }
+        Ok(())
+    }
+
+    pub fn set_u32(&mut self, key: &str, new_value: u32) -> Result<(), Error> {
+        match self.find_mut(key)? {
+            GuiWidget::U32Box(value) => *value = new_value,
+            _ => return Err(wrong_type(key, "u32")),
+        }
+        Ok(())
+    }
+
+    pub fn set_string(&mut self, key: &str, new_value: impl Into<String>) -> Result<(), Error> {
+        match self.find_mut(key)? {
+            GuiWidget::TextBox(value) => *value = new_value.into(),
+            _ => return Err(wrong_type(key, "string")),
+        }
+        Ok(())
+    }
+
+    /// true if the button has been clicked since the last call
+    pub fn take_click(&mut self, key: &str) -> Result<bool, Error> {
+        match self.find_mut(key)? {
+            GuiWidget::Button(clicked) => Ok(std::mem::take(clicked)),
+            _ => Err(wrong_type(key, "button")),
+        }
+    }
+
+    pub fn get_rgb(&self, key: &str) -> Result<[f32; 3], Error> {
+        match self.find(key)? {
+            GuiWidget::Rgb(colour) => Ok(*colour),
+            _ => Err(wrong_type(key, "rgb colour")),
+        }
+    }
+
+    pub fn get_rgba(&self, key: &str) -> Result<[f32; 4], Error> {
+        match self.find(key)? {
+            GuiWidget::Rgba(colour) => Ok(*colour),
+            _ => Err(wrong_type(key, "rgba colour")),
+        }
+    }
+
+    /// the index of the selected option of a combo box
+    pub fn get_selected(&self, key: &str) -> Result<usize, Error> {
+        match self.find(key)? {
+            GuiWidget::Combo {selected, ..} => Ok(*selected),
+            _ => Err(wrong_type(key, "combo box")),
+        }
+    }
+
+    /// the name of the selected option of a combo box
+    pub fn get_selected_name(&self, key: &str) -> Result<&str, Error> {
+        match self.find(key)? {
+            GuiWidget::Combo {options, selected} => Ok(options.get(*selected).map_or("", |option| option.as_str())),
+            _ => Err(wrong_type(key, "combo box")),
+        }
+    }
+
+    pub fn get_vec3(&self, key: &str) -> Result<[f32; 3], Error> {
+        match self.find(key)? {
+            GuiWidget::Vec3(value) => Ok(*value),
+            _ => Err(wrong_type(key, "vec3")),
+        }
+    }
+
+    /// the plotted values, oldest first
+    pub fn get_plot(&self, key: &str) -> Result<&VecDeque<f32>, Error> {
+        match self.find(key)? {
+            GuiWidget::Plot {values, ..} => Ok(values),
+            _ => Err(wrong_type(key, "plot")),
+        }
+    }
+
+    pub fn set_rgb(&mut self, key: &str, new_colour: [f32; 3]) -> Result<(), Error> {
+        match self.find_mut(key)? {
+            GuiWidget::Rgb(colour) => *colour = new_colour,
+            _ => return Err(wrong_type(key, "rgb colour")),
+        }
+        Ok(())
+    }
+
+    pub fn set_rgba(&mut self, key: &str, new_colour: [f32; 4]) -> Result<(), Error> {
+        match self.find_mut(key)? {
+            GuiWidget::Rgba(colour) => *colour = new_colour,
+            _ => return Err(wrong_type(key, "rgba colour")),
+        }
+        Ok(())
+    }
+
+    /// selects an option of a combo box, indices past the last option select the last
+    pub fn set_selected(&mut self, key: &str, index: usize) -> Result<(), Error> {
+        match self.find_mut(key)? {
+            GuiWidget::Combo {options, selected} => *selected = index.min(options.len().saturating_sub(1)),
+            _ => return Err(wrong_type(key, "combo box")),
+        }
+        Ok(())
+    }
+
+    pub fn set_vec3(&mut self, key: &str, new_value: [f32; 3]) -> Result<(), Error> {
+        match self.find_mut(key)? {
+            GuiWidget::Vec3(value) => *value = new_value,
+            _ => return Err(wrong_type(key, "vec3")),
+        }
+        Ok(())
+    }
+
+    /// adds a value to the end of a plot, dropping the oldest once it is full
+    pub fn push_plot(&mut self, key: &str, value: f32) -> Result<(), Error> {
+        match self.find_mut(key)? {
+            GuiWidget::Plot {values, capacity} => {
+                while values.len() >= (*capacity).max(1) {
+                    values.pop_front();
+                }
+                values.push_back(value);
+            },
+            _ => return Err(wrong_type(key, "plot")),
+        }
+        Ok(())
+    }
+
+    fn find(&self, key: &str) -> Result<&GuiWidget, Error> {
+        self.get(key).ok_or_else(|| Error::MissingGuiWidget(key.to_string()))
+    }
+
+    fn find_mut(&mut self, key: &str) -> Result<&mut GuiWidget, Error> {
+        self.get_mut(key).ok_or_else(|| Error::MissingGuiWidget(key.to_string()))
+    }
+}
+
+fn wrong_type(key: &str, expected: &'static str) -> Error {
+    Error::GuiWidgetType {key: key.to_string(), expected}
+}
+
+
+/// Lays out a gui window one widget at a time, each added by the key its value is read back with
+///
+/// ```ignore
+/// let gui = GuiWindowBuilder::new("Settings")
+///     .section("Spinning")
+///     .checkbox("Enable Spinning", true)
+///     .f32_slider("Spin Speed", 0.5, -5.0..=5.0)
+///     .build(&mut windows, window_id, &event_loop)?;
+/// let speed = gui.get_f32("Spin Speed")?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct GuiWindowBuilder {
+    title: String,
+    widgets: GuiWidgets,
+    /// the first key added twice, reported by build so the adders can be chained
+    duplicate: Option<String>,
+    overlay: bool,
+}
+
+impl GuiWindowBuilder {
+    pub fn new(title: impl Into<String>) -> Self {
+        GuiWindowBuilder {
+            title: title.into(),
+            widgets: GuiWidgets::new(),
+            duplicate: None,
+            overlay: false,
+        }
+    }
+
+    /// adds any widget
+    pub fn widget(mut self, key: impl Into<String>, widget: GuiWidget) -> Self {
+        if let Err(Error::DuplicateGuiWidget(key)) = self.widgets.add(key, widget) {
+            self.duplicate.get_or_insert(key);
+        }
+        self
+    }
+
+    /// starts a new section with a heading
+    pub fn section(self, title: impl Into<String>) -> Self {
+        self.widget(title, GuiWidget::Section)
+    }
+
+    pub fn checkbox(self, key: impl Into<String>, value: bool) -> Self {
+        self.widget(key, GuiWidget::Checkbox(value))
+    }
+
+    pub fn f32_slider(self, key: impl Into<String>, value: f32, range: RangeInclusive<f32>) -> Self {
+        self.widget(key, GuiWidget::F32Slider(value, range))
+    }
+
+    pub fn i32_slider(self, key: impl Into<String>, value: i32, range: RangeInclusive<i32>) -> Self {
+        self.widget(key, GuiWidget::I32Slider(value, range))
+    }
+
+    pub fn f32_box(self, key: impl Into<String>, value: f32) -> Self {
+        self.widget(key, GuiWidget::F32Box(value))
+    }
+
+    pub fn i32_box(self, key: impl Into<String>, value: i32) -> Self {
+        self.widget(key, GuiWidget::I32Box(value))
+    }
+
+    pub fn u32_box(self, key: impl Into<String>, value: u32) -> Self {
+        self.widget(key, GuiWidget::U32Box(value))
+    }
+
+    pub fn text_box(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.widget(key, GuiWidget::TextBox(value.into()))
+    }
+
+    pub fn button(self, key: impl Into<String>) -> Self {
+        self.widget(key, GuiWidget::Button(false))
+    }
+
+    pub fn rgb(self, key: impl Into<String>, colour: [f32; 3]) -> Self {
+        self.widget(key, GuiWidget::Rgb(colour))
+    }
+
+    pub fn rgba(self, key: impl Into<String>, colour: [f32; 4]) -> Self {
+        self.widget(key, GuiWidget::Rgba(colour))
+    }
+
+    /// a combo box choosing between the options, starting on selected
+    pub fn combo<S: Into<String>>(self, key: impl Into<String>, options: impl IntoIterator<Item = S>, selected: usize) -> Self {
+        let options: Vec<String> = options.into_iter().map(|option| option.into()).collect();
+        let selected = selected.min(options.len().saturating_sub(1));
+        self.widget(key, GuiWidget::Combo {options, selected})
+    }
+
+    pub fn vec3(self, key: impl Into<String>, value: [f32; 3]) -> Self {
+        self.widget(key, GuiWidget::Vec3(value))
+    }
+
+    /// a collapsible group, filled in by the closure
+    pub fn group(mut self, title: impl Into<String>, open: bool, fill: impl FnOnce(GuiWindowBuilder) -> GuiWindowBuilder) -> Self {
+        let inner = fill(GuiWindowBuilder::new(""));
+        match inner.into_widgets() {
+            Ok(widgets) => self.widget(title, GuiWidget::Group {open, widgets}),
+            Err(Error::DuplicateGuiWidget(key)) => {
+                self.duplicate.get_or_insert(key);
+                self
+            },
+            Err(_) => self,
+        }
+    }
+
+    /// a line graph keeping the last capacity values, such as frame times
+    pub fn plot(self, key: impl Into<String>, capacity: usize) -> Self {
+        self.widget(key, GuiWidget::Plot {values: VecDeque::with_capacity(capacity), capacity})
+    }
+
+    /// the widgets added so far, or an error if a key was used twice
+    pub fn into_widgets(self) -> Result<GuiWidgets, Error> {
+        match self.duplicate {
+            Some(key) => Err(Error::DuplicateGuiWidget(key)),
+            None => Ok(self.widgets),
+        }
+    }
+
+    /// draws the gui as a floating, movable panel over whatever is already on the window's image, see draw_gui_overlay
+    pub fn overlay(mut self) -> Self {
+        self.overlay = true;
+        self
+    }
+
+    /// creates the gui on an existing window
+    pub fn build(
+        self,
+        windows: &mut VulkanoWindows,
+        window_id: WindowId,
+        event_loop: &EventLoop<()>,
+    ) -> Result<GuiWindowData, Error> {
+        let title = self.title.clone();
+        let overlay = self.overlay;
+        let widgets = self.into_widgets()?;
+        let renderer = windows.get_renderer_mut(window_id).ok_or(Error::MissingWindow(window_id))?;
+        // an overlay keeps the image's contents rather than clearing them
+        let config = GuiConfig {
+            is_overlay: overlay,
+            ..GuiConfig::default()
+        };
+        let gui = Gui::new(event_loop, renderer.surface(), renderer.graphics_queue(), config);
+        set_gui_style(&gui.context());
+
+        Ok(GuiWindowData {
+            title,
+            widgets,
+            overlay,
+            gui,
+            window_id,
+        })
+    }
+}
 
 
 pub struct GuiWindowData {
     pub title: String,
-    pub checkboxes: Vec<(String, bool)>,
-    pub f32_sliders: Vec<(String, f32, RangeInclusive<f32>)>,
-    pub i32_sliders: Vec<(String, i32, RangeInclusive<i32>)>,
-    pub f32_boxes: Vec<(String, f32)>,
-    pub i32_boxes: Vec<(String, i32)>,
-    pub u32_boxes: Vec<(String, u32)>,
-    pub string_boxes: Vec<(String, String)>,
-
-    has_checkboxes: bool,
-    has_sliders: bool,
-    has_boxes: bool,
+    pub widgets: GuiWidgets,
 
+    overlay: bool,
     gui: Gui,
     window_id: WindowId,
 }
 
 impl Debug for GuiWindowData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f,
-            "Gui Window - {}: \n- Checkboxes: {:?} \n- Float sliders: {:?} \n- Int Sliders: {:?} \n- Float Boxes: {:?} \n- Int Boxes: {:?} \n- Uint Boxes: {:?} \n- String Boxes: {:?}",
-            self.title, self.checkboxes, self.f32_sliders, self.i32_sliders, self.f32_boxes, self.i32_boxes, self.u32_boxes, self.string_boxes
-        )
+        writeln!(f, "Gui Window - {}:", self.title)?;
+        for (key, widget) in self.widgets.iter() {
+            writeln!(f, "- {}: {:?}", key, widget)?;
+        }
+        Ok(())
+    }
+}
+
+impl GuiWindowData {
+    /// the window the gui is drawn on
+    pub fn window_id(&self) -> WindowId {
+        self.window_id
+    }
+
+    pub fn is_overlay(&self) -> bool {
+        self.overlay
+    }
+
+    /// true while the cursor is over the gui or dragging one of its widgets, so clicks should not reach the scene
+    pub fn wants_pointer_input(&self) -> bool {
+        self.gui.context().wants_pointer_input()
+    }
+
+    /// true while a text box has focus, so key presses should not reach the scene
+    pub fn wants_keyboard_input(&self) -> bool {
+        self.gui.context().wants_keyboard_input()
+    }
+
+    pub fn get_bool(&self, key: &str) -> Result<bool, Error> {
+        self.widgets.get_bool(key)
+    }
+
+    pub fn get_f32(&self, key: &str) -> Result<f32, Error> {
+        self.widgets.get_f32(key)
+    }
+
+    pub fn get_i32(&self, key: &str) -> Result<i32, Error> {
+        self.widgets.get_i32(key)
+    }
+
+    pub fn get_u32(&self, key: &str) -> Result<u32, Error> {
+        self.widgets.get_u32(key)
+    }
+
+    pub fn get_string(&self, key: &str) -> Result<&str, Error> {
+        self.widgets.get_string(key)
+    }
+
+    pub fn set_bool(&mut self, key: &str, value: bool) -> Result<(), Error> {
+        self.widgets.set_bool(key, value)
+    }
+
+    pub fn set_f32(&mut self, key: &str, value: f32) -> Result<(), Error> {
+        self.widgets.set_f32(key, value)
+    }
+
+    pub fn set_i32(&mut self, key: &str, value: i32) -> Result<(), Error> {
+        self.widgets.set_i32(key, value)
+    }
+
+    pub fn set_u32(&mut self, key: &str, value: u32) -> Result<(), Error> {
+        self.widgets.set_u32(key, value)
+    }
+
+    pub fn set_string(&mut self, key: &str, value: impl Into<String>) -> Result<(), Error> {
+        self.widgets.set_string(key, value)
+    }
+
+    pub fn take_click(&mut self, key: &str) -> Result<bool, Error> {
+        self.widgets.take_click(key)
+    }
+
+    pub fn get_rgb(&self, key: &str) -> Result<[f32; 3], Error> {
+        self.widgets.get_rgb(key)
+    }
+
+    pub fn get_rgba(&self, key: &str) -> Result<[f32; 4], Error> {
+        self.widgets.get_rgba(key)
+    }
+
+    pub fn get_selected(&self, key: &str) -> Result<usize, Error> {
+        self.widgets.get_selected(key)
+    }
+
+    pub fn get_selected_name(&self, key: &str) -> Result<&str, Error> {
+        self.widgets.get_selected_name(key)
+    }
+
+    pub fn get_vec3(&self, key: &str) -> Result<[f32; 3], Error> {
+        self.widgets.get_vec3(key)
+    }
+
+    pub fn push_plot(&mut self, key: &str, value: f32) -> Result<(), Error> {
+        self.widgets.push_plot(key, value)
     }
 }
 
@@ -74,46 +617,6 @@ fn set_gui_style(
     ctx.set_fonts(font); 
 }
 
-pub fn create_gui_window(
-    title: String,
-    checkboxes: Vec<(String, bool)>,
-    f32_sliders: Vec<(String, f32, RangeInclusive<f32>)>,
-    i32_sliders: Vec<(String, i32, RangeInclusive<i32>)>,
-    f32_boxes: Vec<(String, f32)>,
-    i32_boxes: Vec<(String, i32)>,
-    u32_boxes: Vec<(String, u32)>,
-    string_boxes: Vec<(String, String)>,
-
-    windows: &mut VulkanoWindows,
-    window_id: WindowId,
-    event_loop: &EventLoop<()>,
-) -> GuiWindowData {
-    let renderer = windows.get_renderer_mut(window_id).unwrap();
-    let gui = Gui::new(event_loop, renderer.surface(), renderer.graphics_queue(), GuiConfig::default());
-    set_gui_style(&gui.context());
-    let has_checkboxes = !(checkboxes.len() == 0);
-    let has_sliders = !(f32_sliders.len() == 0 && i32_sliders.len() == 0);
-    let has_boxes = !(f32_boxes.len() == 0 && i32_boxes.len() == 0 && u32_boxes.len() == 0 && string_boxes.len() == 0);
-
-    GuiWindowData {
-        title,
-        checkboxes,
-        f32_sliders,
-        i32_sliders,
-        f32_boxes,
-        i32_boxes,
-        u32_boxes,
-        string_boxes,
-
-        has_checkboxes,
-        has_sliders,
-        has_boxes,
-
-        gui,
-        window_id,
-    }
-}
-
 pub fn attempt_update_gui_window(
     window: &mut GuiWindowData,
     event: &WindowEvent,
@@ -135,23 +638,44 @@ fn draw_gui_on_image(
 }
 
 
+/// draws a gui that has a window to itself, acquiring and presenting the window's image, overlays are drawn with draw_gui_overlay instead
 pub fn attempt_gui_redraw(
     gui: &mut GuiWindowData,
     windows: &mut VulkanoWindows,
     redraw_request_id: WindowId,
-) -> bool {
+) -> Result<bool, Error> {
     if redraw_request_id == gui.window_id {
-        let renderer = windows.get_renderer_mut(gui.window_id).unwrap();
+        let renderer = windows.get_renderer_mut(gui.window_id).ok_or(Error::MissingWindow(gui.window_id))?;
         draw_gui_window(gui);
         // Acquire swapchain future
-        let before_future = renderer.acquire().unwrap();
+        let before_future = renderer.acquire()?;
         // Render gui
         let after_future = draw_gui_on_image(gui, before_future, renderer);
         // Present swapchain
         renderer.present(after_future, true);
-        return true;
+        return Ok(true);
     }
-    false
+    Ok(false)
+}
+
+
+/// draws an overlay gui on top of the window's current swapchain image, after the scene has been drawn to it
+///
+/// The future comes from drawing the scene, and the returned one is given to present, so the frame is acquired and presented once
+///
+/// ```ignore
+/// let before_future = renderer.acquire()?;
+/// let scene_future = pipeline.draw_from_vertices(before_future, renderer.swapchain_image_view(), ...)?;
+/// let after_future = draw_gui_overlay(&mut gui, scene_future, renderer);
+/// renderer.present(after_future, true);
+/// ```
+pub fn draw_gui_overlay(
+    gui: &mut GuiWindowData,
+    before_future: Box<dyn GpuFuture>,
+    renderer: &mut VulkanoWindowRenderer,
+) -> Box<dyn GpuFuture> {
+    draw_gui_window(gui);
+    draw_gui_on_image(gui, before_future, renderer)
 }
 
 
@@ -161,6 +685,20 @@ pub fn draw_gui_window(
     let gui = &mut data.gui;
     gui.immediate_ui(|gui| {
         let ctx = gui.context();
+        if data.overlay {
+            Window::new(&data.title)
+                .default_pos([10.0, 10.0])
+                .default_width(250.0)
+                .resizable(true)
+                .collapsible(true)
+                .show(&ctx, |ui| {
+                    ScrollArea::vertical().id_source("settings").show(ui, |ui| {
+                        draw_widgets(ui, &mut data.widgets);
+                    });
+                });
+            return;
+        }
+
         CentralPanel::default().show(&ctx, |ui| {
             ui.vertical_centered(|ui| {
                 sized_text(ui, &data.title, 32.0);
@@ -169,56 +707,111 @@ pub fn draw_gui_window(
             ScrollArea::vertical().id_source("settings").show(
                 ui,
                 |ui| {
-                    if data.has_checkboxes {
-                        ui.vertical_centered(|ui| {
-                            sized_text(ui, "Checkboxes", 16.0);
-                        });
-                        for checkbox_data in data.checkboxes.iter_mut() {
-                            ui.add(Checkbox::new(&mut checkbox_data.1, &checkbox_data.0));
-                        }
-                        ui.separator();
-                    }
-                    if data.has_sliders {
-                        ui.vertical_centered(|ui| {
-                            sized_text(ui, "Sliders", 16.0);
-                        });
-                        for slider_data in data.f32_sliders.iter_mut() {
-                            ui.add(Label::new(slider_data.0.clone()));
-                            ui.add(Slider::new(&mut slider_data.1, slider_data.2.clone()));
-                        }
-                        for slider_data in data.i32_sliders.iter_mut() {
-                            ui.add(Label::new(slider_data.0.clone()));
-                            ui.add(Slider::new(&mut slider_data.1, slider_data.2.clone()));
-                        }
-                        ui.separator();
-                    }
-                    if data.has_boxes {
-                        ui.vertical_centered(|ui| {
-                            sized_text(ui, "Data Boxes", 16.0);
-                        });
-                        for f32_data in data.f32_boxes.iter_mut() {
-                            ui.add(Label::new(f32_data.0.clone()));
-                            ui.add(DragValue::new(&mut f32_data.1));
-                        }
-                        for i32_data in data.i32_boxes.iter_mut() {
-                            ui.add(Label::new(i32_data.0.clone()));
-                            ui.add(DragValue::new(&mut i32_data.1));
-                        }
-                        for u32_data in data.u32_boxes.iter_mut() {
-                            ui.add(Label::new(u32_data.0.clone()));
-                            ui.add(DragValue::new(&mut u32_data.1));
-                        }
-                        for string_data in data.string_boxes.iter_mut() {
-                            ui.add(Label::new(string_data.0.clone()));
-                            ui.add(TextEdit::singleline(&mut string_data.1));
-                        }
-                    }
+                    draw_widgets(ui, &mut data.widgets);
                 }
             )
         });
     });
 }
 
+fn draw_widgets(ui: &mut egui::Ui, widgets: &mut GuiWidgets) {
+    for (index, (key, widget)) in widgets.entries.iter_mut().enumerate() {
+        draw_widget(ui, index, key, widget);
+    }
+}
+
+fn draw_widget(ui: &mut egui::Ui, index: usize, key: &str, widget: &mut GuiWidget) {
+    match widget {
+        GuiWidget::Section => {
+            if index > 0 {
+                ui.separator();
+            }
+            ui.vertical_centered(|ui| {
+                sized_text(ui, key, 16.0);
+            });
+        },
+        GuiWidget::Checkbox(value) => {
+            ui.add(Checkbox::new(value, key));
+        },
+        GuiWidget::F32Slider(value, range) => {
+            ui.add(Label::new(key));
+            ui.add(Slider::new(value, range.clone()));
+        },
+        GuiWidget::I32Slider(value, range) => {
+            ui.add(Label::new(key));
+            ui.add(Slider::new(value, range.clone()));
+        },
+        GuiWidget::F32Box(value) => {
+            ui.add(Label::new(key));
+            ui.add(DragValue::new(value));
+        },
+        GuiWidget::I32Box(value) => {
+            ui.add(Label::new(key));
+            ui.add(DragValue::new(value));
+        },
+        GuiWidget::U32Box(value) => {
+            ui.add(Label::new(key));
+            ui.add(DragValue::new(value));
+        },
+        GuiWidget::TextBox(value) => {
+            ui.add(Label::new(key));
+            ui.add(TextEdit::singleline(value));
+        },
+        GuiWidget::Button(clicked) => {
+            if ui.add(Button::new(key)).clicked() {
+                *clicked = true;
+            }
+        },
+        GuiWidget::Rgb(colour) => {
+            ui.horizontal(|ui| {
+                ui.color_edit_button_rgb(colour);
+                ui.add(Label::new(key));
+            });
+        },
+        GuiWidget::Rgba(colour) => {
+            ui.horizontal(|ui| {
+                ui.color_edit_button_rgba_unmultiplied(colour);
+                ui.add(Label::new(key));
+            });
+        },
+        GuiWidget::Combo {options, selected} => {
+            ui.add(Label::new(key));
+            let selected_text = options.get(*selected).cloned().unwrap_or_default();
+            ComboBox::from_id_source(key).selected_text(selected_text).show_ui(ui, |ui| {
+                for (index, option) in options.iter().enumerate() {
+                    ui.selectable_value(selected, index, option);
+                }
+            });
+        },
+        GuiWidget::Vec3(value) => {
+            ui.add(Label::new(key));
+            ui.horizontal(|ui| {
+                for (axis, component) in ["x", "y", "z"].iter().zip(value.iter_mut()) {
+                    ui.add(DragValue::new(component).speed(0.01).prefix(format!("{}: ", axis)));
+                }
+            });
+        },
+        GuiWidget::Group {open, widgets} => {
+            CollapsingHeader::new(key).default_open(*open).show(ui, |ui| {
+                draw_widgets(ui, widgets);
+            });
+        },
+        GuiWidget::Plot {values, ..} => {
+            let latest = values.back().copied().unwrap_or(0.0);
+            let average = if values.is_empty() {0.0} else {values.iter().sum::<f32>() / values.len() as f32};
+            ui.add(Label::new(format!("{}: {:.2} (average {:.2})", key, latest, average)));
+            let points: PlotPoints = values.iter().enumerate().map(|(index, value)| [index as f64, *value as f64]).collect();
+            egui::plot::Plot::new(key)
+                .height(80.0)
+                .allow_drag(false)
+                .allow_zoom(false)
+                .allow_scroll(false)
+                .include_y(0.0)
+                .show(ui, |plot_ui| plot_ui.line(Line::new(points)));
+        },
+    }
+}
+
 fn sized_text(ui: &mut egui::Ui, text: impl Into<String>, size: f32) {
     ui.label(egui::RichText::new(text).size(size));
 }
@@ -0,0 +1,171 @@
+//! named camera actions and the keys, mouse buttons and axes bound to them, saved and loaded as ron files
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use winit::event::{MouseButton, VirtualKeyCode};
+use super::{error, Error};
+
+
+/// Something the camera can be told to do
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CameraAction {
+    Forward,
+    Back,
+    Left,
+    Right,
+    Up,
+    Down,
+    SpinRight,
+    SpinLeft,
+    SpinForward,
+    SpinBackward,
+    RollLeft,
+    RollRight,
+}
+
+impl CameraAction {
+    pub const ALL: [CameraAction; 12] = [
+        CameraAction::Forward,
+        CameraAction::Back,
+        CameraAction::Left,
+        CameraAction::Right,
+        CameraAction::Up,
+        CameraAction::Down,
+        CameraAction::SpinRight,
+        CameraAction::SpinLeft,
+        CameraAction::SpinForward,
+        CameraAction::SpinBackward,
+        CameraAction::RollLeft,
+        CameraAction::RollRight,
+    ];
+}
+
+
+/// A physical input that can be bound to an action
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputSource {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+    /// one direction of a device axis, winit 0.28 only reports mouse and similar axes, not gamepads
+    Axis {
+        axis: u32,
+        positive: bool,
+    },
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Binding {
+    pub input: InputSource,
+    pub action: CameraAction,
+}
+
+
+/// Which inputs trigger which camera actions
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputMap {
+    pub bindings: Vec<Binding>,
+    /// axis values closer to zero than this are ignored
+    pub axis_dead_zone: f32,
+    /// multiplies raw axis values before they are clamped to 0..1
+    pub axis_scale: f32,
+}
+
+impl Default for InputMap {
+    /// WASD, space and C to move, Q, E, R and F to turn
+    fn default() -> Self {
+        let keys = [
+            (VirtualKeyCode::W, CameraAction::Forward),
+            (VirtualKeyCode::S, CameraAction::Back),
+            (VirtualKeyCode::A, CameraAction::Left),
+            (VirtualKeyCode::D, CameraAction::Right),
+            (VirtualKeyCode::Space, CameraAction::Up),
+            (VirtualKeyCode::C, CameraAction::Down),
+            (VirtualKeyCode::Q, CameraAction::SpinRight),
+            (VirtualKeyCode::E, CameraAction::SpinLeft),
+            (VirtualKeyCode::R, CameraAction::SpinForward),
+            (VirtualKeyCode::F, CameraAction::SpinBackward),
+        ];
+        InputMap {
+            bindings: keys.iter().map(|(key, action)| Binding {input: InputSource::Key(*key), action: *action}).collect(),
+            axis_dead_zone: 0.1,
+            axis_scale: 1.0,
+        }
+    }
+}
+
+impl InputMap {
+    /// a map with nothing bound
+    pub fn empty() -> Self {
+        InputMap {
+            bindings: Vec::new(),
+            ..Default::default()
+        }
+    }
+
+    pub fn bind(&mut self, input: InputSource, action: CameraAction) {
+        let binding = Binding {input, action};
+        if !self.bindings.contains(&binding) {
+            self.bindings.push(binding);
+        }
+    }
+
+    pub fn unbind_input(&mut self, input: InputSource) {
+        self.bindings.retain(|binding| binding.input != input);
+    }
+
+    pub fn unbind_action(&mut self, action: CameraAction) {
+        self.bindings.retain(|binding| binding.action != action);
+    }
+
+    /// replaces whatever is bound to the action with a single input
+    pub fn rebind(&mut self, action: CameraAction, input: InputSource) {
+        self.unbind_action(action);
+        self.bind(input, action);
+    }
+
+    pub fn inputs_for(&self, action: CameraAction) -> impl Iterator<Item = InputSource> + '_ {
+        self.bindings.iter().filter(move |binding| binding.action == action).map(|binding| binding.input)
+    }
+
+    pub fn actions_for(&self, input: InputSource) -> impl Iterator<Item = CameraAction> + '_ {
+        self.bindings.iter().filter(move |binding| binding.input == input).map(|binding| binding.action)
+    }
+
+    /// the strongest held input of each action
+    pub fn action_strengths(&self, inputs: &HashMap<InputSource, f32>) -> HashMap<CameraAction, f32> {
+        let mut strengths = HashMap::new();
+        for binding in self.bindings.iter() {
+            if let Some(value) = inputs.get(&binding.input) {
+                let strength = strengths.entry(binding.action).or_insert(0.0);
+                *strength = f32::max(*strength, *value);
+            }
+        }
+        strengths
+    }
+
+    /// turns a raw axis value into the strengths of its positive and negative directions
+    pub fn axis_strengths(&self, value: f32) -> (f32, f32) {
+        let value = value * self.axis_scale;
+        if value.abs() < self.axis_dead_zone {
+            return (0.0, 0.0);
+        }
+        (value.clamp(0.0, 1.0), (-value).clamp(0.0, 1.0))
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, Error> {
+        error::from_ron(source)
+    }
+
+    pub fn to_ron(&self) -> Result<String, Error> {
+        error::to_ron(self)
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error> {
+        error::load_ron(path)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Error> {
+        error::save_ron(self, path)
+    }
+}
@@ -1,34 +1,67 @@
 mod general_graphics;
 mod gui;
 mod camera_maths;
+mod camera_controllers;
+mod input_map;
+mod camera_path;
 pub mod test_cube;
 mod pipeline;
 mod vulkano_wrapping;
 mod mesh;
+mod obj_loader;
+mod gltf_loader;
+mod error;
 mod general_compute;
+mod texture;
+mod offscreen;
+mod shader_file;
+mod lighting;
+mod shadows;
+mod material;
+mod transform;
+mod scene;
+mod bounds;
+mod picking;
 
-pub use camera_maths::Camera;
+pub use camera_maths::{Camera, Projection};
+pub use camera_controllers::*;
+pub use input_map::*;
+pub use camera_path::*;
 pub use gui::*;
 pub use general_graphics::*;
 pub use pipeline::*;
 pub use vulkano_wrapping::*;
 pub use vulkano::buffer::{allocator::*, Subbuffer};
 pub use mesh::*;
-pub use winit::{event::{Event, WindowEvent, ElementState, KeyboardInput, VirtualKeyCode}, event_loop::{ControlFlow, EventLoop}};
+pub use obj_loader::*;
+pub use gltf_loader::*;
+pub use error::Error;
+pub use winit::{event::{Event, WindowEvent, ElementState, KeyboardInput, VirtualKeyCode, MouseButton}, event_loop::{ControlFlow, EventLoop}};
 pub use vulkano_util::{context::VulkanoContext, renderer::VulkanoWindowRenderer};
 pub use vulkano::pipeline::ComputePipeline;
 pub use vulkano::format::Format;
 pub use general_compute::*;
+pub use texture::Texture;
+pub use offscreen::*;
+pub use shader_file::*;
+pub use lighting::*;
+pub use shadows::*;
+pub use material::*;
+pub use transform::*;
+pub use scene::*;
+pub use bounds::*;
+pub use picking::*;
 pub use vulkano::descriptor_set::{allocator::StandardDescriptorSetAllocator, WriteDescriptorSet};
 pub use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
 pub use vulkano::image::SampleCount;
+pub use vulkano::pipeline::graphics::{input_assembly::PrimitiveTopology, rasterization::{CullMode, FrontFace, PolygonMode, DepthBias}, depth_stencil::CompareOp};
 pub use vulkano_shaders::shader;
 
 use vulkano;
 use vulkano_util;
 
 pub mod vertex_types{
-    pub use super::general_graphics::{ColouredVertex, PositionVertex, Normal};
+    pub use super::general_graphics::{ColouredVertex, PositionVertex, TexturedVertex, Normal, TexCoord, Tangent, InstanceModel, ColouredInstance};
 }
 
 pub mod all_vulkano{
@@ -0,0 +1,303 @@
+//! lights and the bundled blinn phong shaders that use them
+use bytemuck::{Pod, Zeroable};
+use vulkano::buffer::{allocator::SubbufferAllocator, Subbuffer};
+use super::Error;
+pub use blinn_phong::BlinnPhongMaterial;
+
+
+/// the most lights the bundled shaders will read, any more in a LightSet are ignored
+pub const MAX_LIGHTS: usize = 16;
+
+
+/// How a point or spot light fades with distance, the light is divided by constant + linear * d + quadratic * d^2
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Default for Attenuation {
+    /// reaches roughly 50 units
+    fn default() -> Self {
+        Attenuation {
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+        }
+    }
+}
+
+impl Attenuation {
+    /// no fading at all
+    pub const NONE: Attenuation = Attenuation {constant: 1.0, linear: 0.0, quadratic: 0.0};
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Light {
+    /// a light infinitely far away, such as the sun, shining in the given direction
+    Directional {
+        direction: [f32; 3],
+        colour: [f32; 3],
+        intensity: f32,
+    },
+    /// a light shining equally in every direction from a position
+    Point {
+        position: [f32; 3],
+        colour: [f32; 3],
+        intensity: f32,
+        attenuation: Attenuation,
+    },
+    /// a cone of light, fully lit inside the inner angle and fading out to the outer angle, both in radians from the centre
+    Spot {
+        position: [f32; 3],
+        direction: [f32; 3],
+        colour: [f32; 3],
+        intensity: f32,
+        attenuation: Attenuation,
+        inner_angle: f32,
+        outer_angle: f32,
+    },
+}
+
+impl Light {
+    pub fn directional(direction: [f32; 3], colour: [f32; 3]) -> Self {
+        Light::Directional {direction, colour, intensity: 1.0}
+    }
+
+    pub fn point(position: [f32; 3], colour: [f32; 3]) -> Self {
+        Light::Point {position, colour, intensity: 1.0, attenuation: Attenuation::default()}
+    }
+
+    pub fn spot(position: [f32; 3], direction: [f32; 3], colour: [f32; 3], inner_angle: f32, outer_angle: f32) -> Self {
+        Light::Spot {position, direction, colour, intensity: 1.0, attenuation: Attenuation::default(), inner_angle, outer_angle}
+    }
+
+    pub fn with_intensity(mut self, new_intensity: f32) -> Self {
+        match &mut self {
+            Light::Directional {intensity, ..} | Light::Point {intensity, ..} | Light::Spot {intensity, ..} => *intensity = new_intensity,
+        }
+        self
+    }
+
+    /// sets the attenuation of a point or spot light, directional lights are left unchanged
+    pub fn with_attenuation(mut self, new_attenuation: Attenuation) -> Self {
+        match &mut self {
+            Light::Point {attenuation, ..} | Light::Spot {attenuation, ..} => *attenuation = new_attenuation,
+            Light::Directional {..} => {},
+        }
+        self
+    }
+
+    fn to_gpu(&self) -> GpuLight {
+        match *self {
+            Light::Directional {direction, colour, intensity} => GpuLight {
+                position: [0.0, 0.0, 0.0, 0.0],
+                direction: [direction[0], direction[1], direction[2], 0.0],
+                colour: [colour[0], colour[1], colour[2], intensity],
+                attenuation: [1.0, 0.0, 0.0, 0.0],
+            },
+            Light::Point {position, colour, intensity, attenuation} => GpuLight {
+                position: [position[0], position[1], position[2], 1.0],
+                direction: [0.0, 0.0, 0.0, 0.0],
+                colour: [colour[0], colour[1], colour[2], intensity],
+                attenuation: [attenuation.constant, attenuation.linear, attenuation.quadratic, 0.0],
+            },
+            Light::Spot {position, direction, colour, intensity, attenuation, inner_angle, outer_angle} => GpuLight {
+                position: [position[0], position[1], position[2], 2.0],
+                direction: [direction[0], direction[1], direction[2], outer_angle.cos()],
+                colour: [colour[0], colour[1], colour[2], intensity],
+                attenuation: [attenuation.constant, attenuation.linear, attenuation.quadratic, inner_angle.cos()],
+            },
+        }
+    }
+}
+
+
+/// A light as the shaders see it, every field is a vec4 so the layout is the same in std140 and std430
+///
+/// - position - xyz position, w is the kind of light, 0 directional, 1 point, 2 spot
+/// - direction - xyz direction the light shines in, w is the cosine of a spot light's outer angle
+/// - colour - rgb colour, w is the intensity
+/// - attenuation - constant, linear and quadratic attenuation, w is the cosine of a spot light's inner angle
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+pub struct GpuLight {
+    pub position: [f32; 4],
+    pub direction: [f32; 4],
+    pub colour: [f32; 4],
+    pub attenuation: [f32; 4],
+}
+
+/// The lights uniform read by the bundled shaders at set 0, binding 1
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+pub struct LightsUniform {
+    /// xyz is the camera position, used for specular highlights
+    pub camera_position: [f32; 4],
+    /// rgb ambient light added everywhere
+    pub ambient: [f32; 4],
+    /// x is the number of lights in use
+    pub count: [u32; 4],
+    pub lights: [GpuLight; MAX_LIGHTS],
+}
+
+
+/// Every light in a scene, plus the ambient light
+#[derive(Clone, Debug, Default)]
+pub struct LightSet {
+    pub ambient: [f32; 3],
+    pub lights: Vec<Light>,
+}
+
+impl LightSet {
+    pub fn new(ambient: [f32; 3]) -> Self {
+        LightSet {
+            ambient,
+            lights: Vec::new(),
+        }
+    }
+
+    /// adds a light, returning its index in lights
+    pub fn add(&mut self, light: Light) -> usize {
+        self.lights.push(light);
+        self.lights.len() - 1
+    }
+
+    /// packs the lights for the gpu, only the first MAX_LIGHTS are kept
+    pub fn uniform(&self, camera_position: [f32; 3]) -> LightsUniform {
+        let mut uniform = LightsUniform {
+            camera_position: [camera_position[0], camera_position[1], camera_position[2], 1.0],
+            ambient: [self.ambient[0], self.ambient[1], self.ambient[2], 1.0],
+            count: [self.lights.len().min(MAX_LIGHTS) as u32, 0, 0, 0],
+            ..Default::default()
+        };
+        for (gpu_light, light) in uniform.lights.iter_mut().zip(self.lights.iter()) {
+            *gpu_light = light.to_gpu();
+        }
+        uniform
+    }
+
+    /// allocates a uniform buffer holding the lights, to be made once per frame
+    pub fn create_buffer(
+        &self,
+        allocator: &SubbufferAllocator,
+        camera_position: [f32; 3],
+    ) -> Result<Subbuffer<LightsUniform>, Error> {
+        let buffer = allocator.allocate_sized()?;
+        *buffer.write()? = self.uniform(camera_position);
+        Ok(buffer)
+    }
+}
+
+
+/// Blinn-Phong shaders for meshes drawn with positions and normals, use them with vertex_defs::position_normal
+///
+/// Set 0 is laid out as:
+/// - binding 0 - vs::Data, the world, view and projection matrices
+/// - binding 1 - LightsUniform
+/// - binding 2 - BlinnPhongMaterial
+/// - binding 3 - ShadowsUniform, only in fs_shadowed
+/// - binding 4 - the ShadowMap, only in fs_shadowed
+pub mod blinn_phong {
+    use bytemuck::{Pod, Zeroable};
+    use vulkano::{buffer::Subbuffer, descriptor_set::WriteDescriptorSet};
+    use super::LightsUniform;
+    use crate::{ShadowMap, ShadowsUniform};
+
+    pub mod vs {
+        vulkano_shaders::shader!{
+            ty: "vertex",
+            src: r"
+                #version 450
+
+                layout(location = 0) in vec3 position;
+                layout(location = 1) in vec3 normal;
+
+                layout(location = 0) out vec3 v_position;
+                layout(location = 1) out vec3 v_normal;
+
+                layout(set = 0, binding = 0) uniform Data {
+                    mat4 world;
+                    mat4 view;
+                    mat4 proj;
+                } uniforms;
+
+                void main() {
+                    vec4 world_position = uniforms.world * vec4(position, 1.0);
+                    v_position = world_position.xyz;
+                    v_normal = mat3(transpose(inverse(uniforms.world))) * normal;
+                    gl_Position = uniforms.proj * uniforms.view * world_position;
+                }
+            ",
+        }
+    }
+
+    pub mod fs {
+        vulkano_shaders::shader!{
+            ty: "fragment",
+            path: "src/shaders/blinn_phong.frag",
+        }
+    }
+
+    /// the fragment shader with the directional light at ShadowsUniform's light index shadowed by a ShadowMap
+    pub mod fs_shadowed {
+        vulkano_shaders::shader!{
+            ty: "fragment",
+            path: "src/shaders/blinn_phong.frag",
+            define: [("SHADOWS", "1")],
+        }
+    }
+
+    /// the surface of a mesh drawn with the blinn phong shaders
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Zeroable, Pod)]
+    pub struct BlinnPhongMaterial {
+        pub colour: [f32; 4],
+        /// rgb specular colour, w is the shininess
+        pub specular: [f32; 4],
+    }
+
+    impl BlinnPhongMaterial {
+        pub fn new(colour: [f32; 4], specular: [f32; 3], shininess: f32) -> Self {
+            BlinnPhongMaterial {
+                colour,
+                specular: [specular[0], specular[1], specular[2], shininess],
+            }
+        }
+    }
+
+    impl Default for BlinnPhongMaterial {
+        fn default() -> Self {
+            BlinnPhongMaterial::new([0.8, 0.8, 0.8, 1.0], [0.5; 3], 32.0)
+        }
+    }
+
+    /// the descriptor writes for the bundled shaders' set 0, for MultiSampleFrame::draw_with_descriptors
+    pub fn writes(
+        uniforms: &Subbuffer<vs::Data>,
+        lights: &Subbuffer<LightsUniform>,
+        material: &Subbuffer<BlinnPhongMaterial>,
+    ) -> Vec<WriteDescriptorSet> {
+        vec![
+            WriteDescriptorSet::buffer(0, uniforms.clone()),
+            WriteDescriptorSet::buffer(1, lights.clone()),
+            WriteDescriptorSet::buffer(2, material.clone()),
+        ]
+    }
+
+    /// the descriptor writes for fs_shadowed's set 0
+    pub fn shadowed_writes(
+        uniforms: &Subbuffer<vs::Data>,
+        lights: &Subbuffer<LightsUniform>,
+        material: &Subbuffer<BlinnPhongMaterial>,
+        shadows: &Subbuffer<ShadowsUniform>,
+        shadow_map: &ShadowMap,
+    ) -> Vec<WriteDescriptorSet> {
+        let mut writes = writes(uniforms, lights, material);
+        writes.push(WriteDescriptorSet::buffer(3, shadows.clone()));
+        writes.push(shadow_map.write(4));
+        writes
+    }
+}
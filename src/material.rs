@@ -0,0 +1,371 @@
+//! a metallic-roughness pbr material, built from obj or gltf materials, and the bundled shaders that draw it
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use bytemuck::{Pod, Zeroable};
+use vulkano::{
+    buffer::{Buffer, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::allocator::StandardCommandBufferAllocator,
+    descriptor_set::WriteDescriptorSet,
+    memory::allocator::{AllocationCreateInfo, MemoryUsage},
+};
+use vulkano_util::context::VulkanoContext;
+use super::{AlphaMode, BlendMode, Error, GltfImage, GltfMaterial, ObjMaterial, Texture};
+
+
+/// Where the pixels of one of a material's textures come from
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TextureSource {
+    /// an image file on disk
+    File(PathBuf),
+    /// an index into the images of a GltfScene
+    Gltf(usize),
+    /// separate greyscale roughness and metallic images, as obj files use, packed into the green and blue channels the shaders read
+    RoughnessMetallicFiles {
+        roughness: Option<PathBuf>,
+        metallic: Option<PathBuf>,
+    },
+}
+
+
+/// A metallic-roughness material
+///
+/// Every factor is multiplied by its texture, a slot left as None acts as a plain white texture (or a flat normal)
+///
+/// The metallic-roughness texture follows gltf, roughness is read from green and metallic from blue
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+    pub name: Option<String>,
+    pub base_colour: [f32; 4],
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: [f32; 3],
+    pub normal_scale: f32,
+    pub occlusion_strength: f32,
+    pub alpha_mode: AlphaMode,
+    pub double_sided: bool,
+
+    pub base_colour_texture: Option<TextureSource>,
+    pub metallic_roughness_texture: Option<TextureSource>,
+    pub normal_texture: Option<TextureSource>,
+    pub occlusion_texture: Option<TextureSource>,
+    pub emissive_texture: Option<TextureSource>,
+}
+
+impl Default for Material {
+    /// a rough white dielectric
+    fn default() -> Self {
+        Material {
+            name: None,
+            base_colour: [1.0; 4],
+            metallic: 0.0,
+            roughness: 1.0,
+            emissive: [0.0; 3],
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+            alpha_mode: AlphaMode::Opaque,
+            double_sided: false,
+
+            base_colour_texture: None,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+        }
+    }
+}
+
+impl Material {
+    pub fn new(base_colour: [f32; 4], metallic: f32, roughness: f32) -> Self {
+        Material {
+            base_colour,
+            metallic,
+            roughness,
+            ..Default::default()
+        }
+    }
+
+    /// converts an mtl material
+    ///
+    /// The Pr and Pm pbr extensions are used if present, otherwise the roughness is estimated from the shininess and the material is taken as non metallic
+    pub fn from_obj(material: &ObjMaterial) -> Self {
+        let roughness = material.roughness.unwrap_or_else(|| (2.0 / (material.shininess.max(0.0) + 2.0)).sqrt());
+        let metallic = material.metallic.unwrap_or(0.0);
+
+        let metallic_roughness_texture = if material.roughness_texture.is_some() || material.metallic_texture.is_some() {
+            Some(TextureSource::RoughnessMetallicFiles {
+                roughness: material.roughness_texture.clone(),
+                metallic: material.metallic_texture.clone(),
+            })
+        } else {
+            None
+        };
+
+        Material {
+            name: Some(material.name.clone()),
+            base_colour: [material.diffuse[0], material.diffuse[1], material.diffuse[2], material.dissolve],
+            metallic: if material.metallic_texture.is_some() {1.0} else {metallic},
+            roughness: if material.roughness_texture.is_some() {1.0} else {roughness},
+            emissive: if material.emissive_texture.is_some() && material.emissive == [0.0; 3] {[1.0; 3]} else {material.emissive},
+            alpha_mode: if material.dissolve < 1.0 {AlphaMode::Blend} else {AlphaMode::Opaque},
+
+            base_colour_texture: material.diffuse_texture.clone().map(TextureSource::File),
+            metallic_roughness_texture,
+            normal_texture: material.normal_texture.clone().map(TextureSource::File),
+            emissive_texture: material.emissive_texture.clone().map(TextureSource::File),
+            ..Default::default()
+        }
+    }
+
+    /// converts a gltf material, textures refer to the images of the scene it came from
+    pub fn from_gltf(material: &GltfMaterial) -> Self {
+        Material {
+            name: material.name.clone(),
+            base_colour: material.base_colour_factor,
+            metallic: material.metallic_factor,
+            roughness: material.roughness_factor,
+            emissive: material.emissive_factor,
+            normal_scale: material.normal_scale,
+            occlusion_strength: material.occlusion_strength,
+            alpha_mode: material.alpha_mode,
+            double_sided: material.double_sided,
+
+            base_colour_texture: material.base_colour_texture.map(TextureSource::Gltf),
+            metallic_roughness_texture: material.metallic_roughness_texture.map(TextureSource::Gltf),
+            normal_texture: material.normal_texture.map(TextureSource::Gltf),
+            occlusion_texture: material.occlusion_texture.map(TextureSource::Gltf),
+            emissive_texture: material.emissive_texture.map(TextureSource::Gltf),
+        }
+    }
+
+    /// the blend mode a pipeline drawing this material should use
+    pub fn blend_mode(&self) -> BlendMode {
+        match self.alpha_mode {
+            AlphaMode::Blend => BlendMode::Alpha,
+            AlphaMode::Opaque | AlphaMode::Mask(_) => BlendMode::None,
+        }
+    }
+
+    /// packs the factors for the gpu
+    pub fn uniform(&self) -> MaterialUniform {
+        let (alpha_flag, alpha_cutoff) = match self.alpha_mode {
+            AlphaMode::Opaque => (0, 0.0),
+            AlphaMode::Mask(cutoff) => (1, cutoff),
+            AlphaMode::Blend => (2, 0.0),
+        };
+        MaterialUniform {
+            base_colour: self.base_colour,
+            emissive: [self.emissive[0], self.emissive[1], self.emissive[2], self.normal_scale],
+            factors: [self.metallic, self.roughness, self.occlusion_strength, alpha_cutoff],
+            flags: [alpha_flag, 0, 0, 0],
+        }
+    }
+}
+
+
+/// The material uniform read by the pbr shaders at set 0, binding 2
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+pub struct MaterialUniform {
+    pub base_colour: [f32; 4],
+    /// rgb emissive colour, w is the normal scale
+    pub emissive: [f32; 4],
+    /// metallic, roughness, occlusion strength and alpha cutoff
+    pub factors: [f32; 4],
+    /// x is the alpha mode, 0 opaque, 1 mask, 2 blend
+    pub flags: [u32; 4],
+}
+
+
+/// A material uploaded to the gpu, ready to be drawn with the pbr shaders
+#[derive(Clone)]
+pub struct GpuMaterial {
+    pub uniform: Subbuffer<MaterialUniform>,
+    pub base_colour: Texture,
+    pub metallic_roughness: Texture,
+    pub normal: Texture,
+    pub occlusion: Texture,
+    pub emissive: Texture,
+    pub blend_mode: BlendMode,
+    pub double_sided: bool,
+}
+
+
+/// Uploads materials, sharing textures between materials that use the same image
+pub struct MaterialLoader {
+    command_buffer_allocator: Arc<StandardCommandBufferAllocator>,
+    textures: HashMap<(TextureSource, bool), Texture>,
+    white_srgb: Texture,
+    white_linear: Texture,
+    flat_normal: Texture,
+}
+
+impl MaterialLoader {
+    pub fn new(
+        context: &VulkanoContext,
+        command_buffer_allocator: &Arc<StandardCommandBufferAllocator>,
+    ) -> Result<Self, Error> {
+        // empty slots are filled with 1x1 textures that leave the factors unchanged
+        let white_srgb = Texture::from_rgba(context, command_buffer_allocator, vec![255; 4], [1, 1], true)?;
+        let white_linear = Texture::from_rgba(context, command_buffer_allocator, vec![255; 4], [1, 1], false)?;
+        let flat_normal = Texture::from_rgba(context, command_buffer_allocator, vec![128, 128, 255, 255], [1, 1], false)?;
+
+        Ok(MaterialLoader {
+            command_buffer_allocator: command_buffer_allocator.clone(),
+            textures: HashMap::new(),
+            white_srgb,
+            white_linear,
+            flat_normal,
+        })
+    }
+
+    /// uploads a material's uniform and textures, gltf_images is only read by materials with TextureSource::Gltf textures
+    pub fn load(
+        &mut self,
+        context: &VulkanoContext,
+        material: &Material,
+        gltf_images: &[GltfImage],
+    ) -> Result<GpuMaterial, Error> {
+        let uniform = Buffer::from_data(
+            context.memory_allocator(),
+            BufferCreateInfo {
+                usage: BufferUsage::UNIFORM_BUFFER,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                usage: MemoryUsage::Upload,
+                ..Default::default()
+            },
+            material.uniform(),
+        )?;
+
+        let white_srgb = self.white_srgb.clone();
+        let white_linear = self.white_linear.clone();
+        let flat_normal = self.flat_normal.clone();
+
+        Ok(GpuMaterial {
+            uniform,
+            base_colour: self.texture(context, material.base_colour_texture.as_ref(), true, gltf_images)?.unwrap_or_else(|| white_srgb.clone()),
+            metallic_roughness: self.texture(context, material.metallic_roughness_texture.as_ref(), false, gltf_images)?.unwrap_or_else(|| white_linear.clone()),
+            normal: self.texture(context, material.normal_texture.as_ref(), false, gltf_images)?.unwrap_or(flat_normal),
+            occlusion: self.texture(context, material.occlusion_texture.as_ref(), false, gltf_images)?.unwrap_or(white_linear),
+            emissive: self.texture(context, material.emissive_texture.as_ref(), true, gltf_images)?.unwrap_or(white_srgb),
+            blend_mode: material.blend_mode(),
+            double_sided: material.double_sided,
+        })
+    }
+
+    /// returns the texture for a slot, uploading it the first time it is used, or None for an empty slot
+    fn texture(
+        &mut self,
+        context: &VulkanoContext,
+        source: Option<&TextureSource>,
+        srgb: bool,
+        gltf_images: &[GltfImage],
+    ) -> Result<Option<Texture>, Error> {
+        let source = match source {
+            Some(source) => source,
+            None => return Ok(None),
+        };
+
+        let key = (source.clone(), srgb);
+        if let Some(texture) = self.textures.get(&key) {
+            return Ok(Some(texture.clone()));
+        }
+
+        let (pixels, dimensions) = match source {
+            TextureSource::File(path) => {
+                let image = image::open(path)?.to_rgba8();
+                let dimensions = [image.width(), image.height()];
+                (image.into_raw(), dimensions)
+            },
+            TextureSource::Gltf(index) => {
+                let image = gltf_images.get(*index).ok_or_else(|| Error::UnsupportedGltf(format!("material refers to image {} which does not exist", index)))?;
+                (image.pixels.clone(), [image.width, image.height])
+            },
+            TextureSource::RoughnessMetallicFiles {roughness, metallic} => pack_roughness_metallic(roughness.as_ref(), metallic.as_ref())?,
+        };
+
+        let texture = Texture::from_rgba(context, &self.command_buffer_allocator, pixels, dimensions, srgb)?;
+        self.textures.insert(key, texture.clone());
+        Ok(Some(texture))
+    }
+}
+
+
+/// packs greyscale roughness and metallic images into one texture, the metallic image is resized to match the roughness one if they differ
+fn pack_roughness_metallic(roughness: Option<&PathBuf>, metallic: Option<&PathBuf>) -> Result<(Vec<u8>, [u32; 2]), Error> {
+    let roughness = roughness.map(|path| image::open(path)).transpose()?.map(|image| image.to_luma8());
+    let metallic = metallic.map(|path| image::open(path)).transpose()?.map(|image| image.to_luma8());
+
+    let (width, height) = match (&roughness, &metallic) {
+        (Some(image), _) | (None, Some(image)) => image.dimensions(),
+        (None, None) => (1, 1),
+    };
+
+    let metallic = metallic.map(|image| {
+        if image.dimensions() == (width, height) {
+            image
+        } else {
+            image::imageops::resize(&image, width, height, image::imageops::FilterType::Triangle)
+        }
+    });
+
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let roughness = roughness.as_ref().map_or(255, |image| image.get_pixel(x, y)[0]);
+            let metallic = metallic.as_ref().map_or(255, |image| image.get_pixel(x, y)[0]);
+            pixels.extend_from_slice(&[255, roughness, metallic, 255]);
+        }
+    }
+    Ok((pixels, [width, height]))
+}
+
+
+/// Cook-Torrance pbr shaders for meshes drawn with texture coordinates and normals, use them with vertex_defs::textured_normal
+///
+/// Set 0 is laid out as:
+/// - binding 0 - vs::Data, the world, view and projection matrices
+/// - binding 1 - LightsUniform
+/// - binding 2 - MaterialUniform
+/// - binding 3 - base colour texture
+/// - binding 4 - metallic-roughness texture
+/// - binding 5 - normal texture
+/// - binding 6 - occlusion texture
+/// - binding 7 - emissive texture
+pub mod pbr {
+    use vulkano::{buffer::Subbuffer, descriptor_set::WriteDescriptorSet};
+    use super::GpuMaterial;
+    use crate::LightsUniform;
+
+    pub mod vs {
+        vulkano_shaders::shader!{
+            ty: "vertex",
+            path: "src/shaders/pbr.vert",
+        }
+    }
+
+    pub mod fs {
+        vulkano_shaders::shader!{
+            ty: "fragment",
+            path: "src/shaders/pbr.frag",
+        }
+    }
+
+    /// the descriptor writes for the pbr shaders' set 0, for MultiSampleFrame::draw_with_descriptors
+    pub fn writes(
+        uniforms: &Subbuffer<vs::Data>,
+        lights: &Subbuffer<LightsUniform>,
+        material: &GpuMaterial,
+    ) -> Vec<WriteDescriptorSet> {
+        vec![
+            WriteDescriptorSet::buffer(0, uniforms.clone()),
+            WriteDescriptorSet::buffer(1, lights.clone()),
+            WriteDescriptorSet::buffer(2, material.uniform.clone()),
+            material.base_colour.write(3),
+            material.metallic_roughness.write(4),
+            material.normal.write(5),
+            material.occlusion.write(6),
+            material.emissive.write(7),
+        ]
+    }
+}
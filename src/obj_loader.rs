@@ -0,0 +1,526 @@
+//! loading of wavefront obj files and their companion mtl materials
+use std::{collections::HashMap, fmt, fs::File, io::{BufRead, BufReader}, path::{Path, PathBuf}};
+use maths::Vector3;
+use super::{Mesh, PositionVertex, TexturedVertex, Normal, TexCoord, combine_meshes, Error};
+
+
+/// An error from reading an obj or mtl file
+#[derive(Debug)]
+pub enum ObjError {
+    /// The file could not be opened or read
+    Io {path: PathBuf, error: std::io::Error},
+    /// A line of the file could not be understood, lines are counted from 1
+    Parse {path: PathBuf, line: usize, message: String},
+    /// The file given does not have the .obj extension
+    NotObj(PathBuf),
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io {path, error} => write!(f, "could not read \"{}\": {}", path.display(), error),
+            ObjError::Parse {path, line, message} => write!(f, "{}:{}: {}", path.display(), line, message),
+            ObjError::NotObj(path) => write!(f, "cannot read \"{}\", it is not an obj file", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ObjError::Io {error, ..} => Some(error),
+            _ => None,
+        }
+    }
+}
+
+
+/// A material read from an mtl file
+///
+/// Texture paths are resolved relative to the mtl file they were read from
+#[derive(Debug, Clone)]
+pub struct ObjMaterial {
+    pub name: String,
+    pub ambient: [f32; 3],
+    pub diffuse: [f32; 3],
+    pub specular: [f32; 3],
+    pub emissive: [f32; 3],
+    pub shininess: f32,
+    pub dissolve: f32,
+    pub optical_density: f32,
+    pub illumination_model: Option<u32>,
+    /// pbr extension values (Pr and Pm), if the exporter wrote them
+    pub roughness: Option<f32>,
+    pub metallic: Option<f32>,
+
+    pub ambient_texture: Option<PathBuf>,
+    pub diffuse_texture: Option<PathBuf>,
+    pub specular_texture: Option<PathBuf>,
+    pub emissive_texture: Option<PathBuf>,
+    pub dissolve_texture: Option<PathBuf>,
+    pub normal_texture: Option<PathBuf>,
+    pub roughness_texture: Option<PathBuf>,
+    pub metallic_texture: Option<PathBuf>,
+}
+
+impl ObjMaterial {
+    /// a material with the default mtl values
+    pub fn new(name: &str) -> Self {
+        ObjMaterial {
+            name: name.to_string(),
+            ambient: [0.0; 3],
+            diffuse: [0.8; 3],
+            specular: [0.0; 3],
+            emissive: [0.0; 3],
+            shininess: 0.0,
+            dissolve: 1.0,
+            optical_density: 1.0,
+            illumination_model: None,
+            roughness: None,
+            metallic: None,
+
+            ambient_texture: None,
+            diffuse_texture: None,
+            specular_texture: None,
+            emissive_texture: None,
+            dissolve_texture: None,
+            normal_texture: None,
+            roughness_texture: None,
+            metallic_texture: None,
+        }
+    }
+}
+
+
+/// A single mesh from an obj file, faces are split into meshes by object, group and material
+#[derive(Debug, Clone)]
+pub struct ObjMesh {
+    /// the name of the object ("o") this mesh belongs to
+    pub name: String,
+    /// the group ("g") this mesh belongs to, if any
+    pub group: Option<String>,
+    /// index into the materials of the scene
+    pub material: Option<usize>,
+    pub mesh: Mesh<PositionVertex>,
+    /// one texture coordinate per vertex, empty if the faces did not reference any
+    ///
+    /// v is flipped from the obj convention so that (0, 0) is the top left of an image
+    pub tex_coords: Vec<TexCoord>,
+}
+
+impl ObjMesh {
+    /// returns the mesh with its texture coordinates, (0, 0) for every vertex if it has none
+    pub fn textured_mesh(&self) -> Mesh<TexturedVertex> {
+        let vertices = self.mesh.vertices.iter().enumerate().map(|(i, vertex)| TexturedVertex {
+            position: vertex.position,
+            tex_coord: self.tex_coords.get(i).map_or([0.0; 2], |coord| coord.tex_coord),
+        }).collect();
+        let mut mesh = Mesh::new(vertices, self.mesh.indices.clone());
+        mesh.set_normals(self.mesh.normals.clone());
+        mesh
+    }
+}
+
+
+/// The contents of an obj file and any mtl files it references
+#[derive(Debug, Clone, Default)]
+pub struct ObjScene {
+    pub meshes: Vec<ObjMesh>,
+    pub materials: Vec<ObjMaterial>,
+}
+
+impl ObjScene {
+    /// returns the material assigned to a mesh of this scene
+    pub fn material(&self, mesh: &ObjMesh) -> Option<&ObjMaterial> {
+        mesh.material.and_then(|index| self.materials.get(index))
+    }
+
+    /// returns every mesh that belongs to the object with the given name
+    pub fn object<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a ObjMesh> {
+        self.meshes.iter().filter(move |mesh| mesh.name == name)
+    }
+
+    /// combines every mesh of the scene into one, ignoring materials
+    pub fn combined_mesh(&self) -> Mesh<PositionVertex> {
+        if self.meshes.len() == 0 {return Mesh::EMPTY;}
+        combine_meshes(&self.meshes.iter().map(|mesh| mesh.mesh.clone()).collect::<Vec<_>>())
+    }
+}
+
+
+
+/// where the normal of a face corner comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum NormalSource {
+    File(usize),
+    // generated and shared with every face in the same smoothing group
+    Smooth(u32),
+    // generated for a single face
+    Face(usize),
+}
+
+struct MeshBuilder {
+    name: String,
+    group: Option<String>,
+    material: Option<String>,
+
+    vertices: Vec<PositionVertex>,
+    normals: Vec<[f32; 3]>,
+    generated_normal: Vec<bool>,
+    tex_coords: Vec<TexCoord>,
+    has_tex_coords: bool,
+    indices: Vec<u32>,
+    lookup: HashMap<(usize, Option<usize>, NormalSource), u32>,
+}
+
+impl MeshBuilder {
+    fn new(name: String, group: Option<String>, material: Option<String>) -> Self {
+        MeshBuilder {
+            name, group, material,
+            vertices: Vec::new(),
+            normals: Vec::new(),
+            generated_normal: Vec::new(),
+            tex_coords: Vec::new(),
+            has_tex_coords: false,
+            indices: Vec::new(),
+            lookup: HashMap::new(),
+        }
+    }
+
+    fn corner_index(
+        &mut self,
+        corner: (usize, Option<usize>, NormalSource),
+        positions: &Vec<[f32; 3]>,
+        tex_coords: &Vec<[f32; 2]>,
+        normals: &Vec<[f32; 3]>,
+    ) -> u32 {
+        if let Some(index) = self.lookup.get(&corner) {
+            return *index;
+        }
+        let index = self.vertices.len() as u32;
+        self.vertices.push(positions[corner.0].into());
+        self.tex_coords.push(TexCoord {tex_coord: corner.1.map(|i| tex_coords[i]).unwrap_or([0.0; 2])});
+        self.has_tex_coords |= corner.1.is_some();
+        match corner.2 {
+            NormalSource::File(i) => {
+                self.normals.push(normals[i]);
+                self.generated_normal.push(false);
+            }
+            _ => {
+                self.normals.push([0.0; 3]);
+                self.generated_normal.push(true);
+            }
+        }
+        self.lookup.insert(corner, index);
+        index
+    }
+
+    /// adds a polygon to the mesh, triangulating it as a fan around the first corner
+    fn add_face(
+        &mut self,
+        corners: &Vec<(usize, Option<usize>, NormalSource)>,
+        positions: &Vec<[f32; 3]>,
+        tex_coords: &Vec<[f32; 2]>,
+        normals: &Vec<[f32; 3]>,
+    ) {
+        let indices: Vec<u32> = corners.iter().map(|corner| self.corner_index(*corner, positions, tex_coords, normals)).collect();
+
+        // face normal, summed over the fan so it is area weighted
+        let mut face_normal = Vector3::ZERO;
+        for i in 1..(indices.len() - 1) {
+            let tri = [indices[0], indices[i], indices[i + 1]];
+            self.indices.extend_from_slice(&tri);
+
+            let v_one: Vector3 = self.vertices[tri[0] as usize].position.into();
+            let v_two: Vector3 = self.vertices[tri[1] as usize].position.into();
+            let v_thr: Vector3 = self.vertices[tri[2] as usize].position.into();
+            face_normal += (v_two - v_thr).cross(v_one - v_thr);
+        }
+
+        let face_normal: [f32; 3] = face_normal.into();
+        for index in indices {
+            let index = index as usize;
+            if self.generated_normal[index] {
+                self.normals[index][0] += face_normal[0];
+                self.normals[index][1] += face_normal[1];
+                self.normals[index][2] += face_normal[2];
+            }
+        }
+    }
+
+    fn build(self, materials: &HashMap<String, usize>) -> ObjMesh {
+        let normals = self.normals.iter().zip(self.generated_normal.iter()).map(|(normal, generated)| {
+            let normal: Vector3 = (*normal).into();
+            if *generated && normal != Vector3::ZERO {
+                Normal::from(normal.normalised())
+            } else {
+                Normal::from(normal)
+            }
+        }).collect();
+
+        let mut mesh = Mesh::new(self.vertices, self.indices);
+        mesh.set_normals(normals);
+
+        ObjMesh {
+            name: self.name,
+            group: self.group,
+            material: self.material.and_then(|name| materials.get(&name).copied()),
+            mesh,
+            tex_coords: if self.has_tex_coords {self.tex_coords} else {Vec::new()},
+        }
+    }
+}
+
+
+fn parse_floats<const N: usize>(args: &[&str], min: usize, defaults: [f32; N], path: &Path, line: usize) -> Result<[f32; N], ObjError> {
+    if args.len() < min || args.len() > N {
+        return Err(ObjError::Parse {path: path.to_path_buf(), line, message: format!("expected {} to {} values, found {}", min, N, args.len())});
+    }
+    let mut out = defaults;
+    for (i, arg) in args.iter().enumerate() {
+        out[i] = arg.parse::<f32>().map_err(|_| ObjError::Parse {path: path.to_path_buf(), line, message: format!("\"{}\" is not a number", arg)})?;
+    }
+    Ok(out)
+}
+
+/// resolves a 1 based, possibly negative, obj index into an index into a list of the given length
+fn resolve_index(arg: &str, len: usize, path: &Path, line: usize) -> Result<usize, ObjError> {
+    let index = arg.parse::<i64>().map_err(|_| ObjError::Parse {path: path.to_path_buf(), line, message: format!("\"{}\" is not a valid index", arg)})?;
+    let resolved = if index > 0 {index - 1} else {len as i64 + index};
+    if index == 0 || resolved < 0 || resolved >= len as i64 {
+        return Err(ObjError::Parse {path: path.to_path_buf(), line, message: format!("index {} is out of range", index)});
+    }
+    Ok(resolved as usize)
+}
+
+fn read_lines(path: &Path) -> Result<impl Iterator<Item = (usize, Result<String, std::io::Error>)>, ObjError> {
+    let file = File::open(path).map_err(|error| ObjError::Io {path: path.to_path_buf(), error})?;
+    Ok(BufReader::new(file).lines().enumerate().map(|(i, line)| (i + 1, line)))
+}
+
+/// strips comments and splits a line into its keyword and arguments
+fn split_line(line: &str) -> Option<(&str, Vec<&str>)> {
+    let line = line.split('#').next().unwrap_or("");
+    let mut split = line.split_whitespace();
+    let keyword = split.next()?;
+    Some((keyword, split.collect()))
+}
+
+
+/// loads an obj file and any mtl files it references
+///
+/// Polygons with more than three corners are triangulated, and normals are generated for faces that do not reference any,
+/// smooth or flat depending on the smoothing group. Statements that are not understood, such as lines and curves, are ignored
+///
+/// A material library that cannot be found is skipped, and the materials it would have defined get the default values
+pub fn load_obj(path: &str) -> Result<ObjScene, Error> {
+    Ok(read_obj(Path::new(path))?)
+}
+
+/// loads every material in an mtl file
+pub fn load_mtl(path: &str) -> Result<Vec<ObjMaterial>, Error> {
+    Ok(read_mtl(Path::new(path))?)
+}
+
+
+fn read_obj(path: &Path) -> Result<ObjScene, ObjError> {
+    if path.extension().map_or(true, |ext| ext != "obj") {
+        return Err(ObjError::NotObj(path.to_path_buf()));
+    }
+    let directory = path.parent().unwrap_or(Path::new(""));
+
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut tex_coords: Vec<[f32; 2]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+
+    let mut materials: Vec<ObjMaterial> = Vec::new();
+    let mut builders: Vec<MeshBuilder> = Vec::new();
+    let mut builder_lookup: HashMap<(String, Option<String>, Option<String>), usize> = HashMap::new();
+
+    let mut object = "default".to_string();
+    let mut group: Option<String> = None;
+    let mut material: Option<String> = None;
+    let mut smoothing_group: u32 = 0;
+    let mut face_count: usize = 0;
+
+    for (line_num, line) in read_lines(path)? {
+        let line = line.map_err(|error| ObjError::Io {path: path.to_path_buf(), error})?;
+        let (keyword, args) = match split_line(&line) {
+            Some(split) => split,
+            None => continue,
+        };
+
+        match keyword {
+            "v" => {
+                // the optional w and any vertex colours some exporters append are ignored
+                let args = if args.len() > 3 {&args[0..3]} else {&args[..]};
+                positions.push(parse_floats(args, 3, [0.0; 3], path, line_num)?);
+            }
+            "vt" => {
+                let [u, v, _] = parse_floats(&args, 1, [0.0; 3], path, line_num)?;
+                tex_coords.push([u, 1.0 - v]);
+            }
+            "vn" => {
+                // a zero normal is kept as it is rather than normalised into nan
+                let normal: Vector3 = parse_floats(&args, 3, [0.0; 3], path, line_num)?.into();
+                normals.push(if normal == Vector3::ZERO {normal.into()} else {normal.normalised().into()});
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(ObjError::Parse {path: path.to_path_buf(), line: line_num, message: format!("a face needs at least 3 corners, found {}", args.len())});
+                }
+                let mut corners = Vec::new();
+                for arg in args.iter() {
+                    let mut split = arg.split('/');
+                    let vertex = resolve_index(split.next().unwrap_or(""), positions.len(), path, line_num)?;
+                    let tex_coord = match split.next() {
+                        Some("") | None => None,
+                        Some(index) => Some(resolve_index(index, tex_coords.len(), path, line_num)?),
+                    };
+                    let normal = match split.next() {
+                        Some("") | None if smoothing_group == 0 => NormalSource::Face(face_count),
+                        Some("") | None => NormalSource::Smooth(smoothing_group),
+                        Some(index) => NormalSource::File(resolve_index(index, normals.len(), path, line_num)?),
+                    };
+                    if split.next().is_some() {
+                        return Err(ObjError::Parse {path: path.to_path_buf(), line: line_num, message: format!("\"{}\" is not a valid face corner", arg)});
+                    }
+                    corners.push((vertex, tex_coord, normal));
+                }
+                face_count += 1;
+
+                let key = (object.clone(), group.clone(), material.clone());
+                let index = match builder_lookup.get(&key) {
+                    Some(index) => *index,
+                    None => {
+                        builders.push(MeshBuilder::new(key.0.clone(), key.1.clone(), key.2.clone()));
+                        builder_lookup.insert(key, builders.len() - 1);
+                        builders.len() - 1
+                    }
+                };
+                builders[index].add_face(&corners, &positions, &tex_coords, &normals);
+            }
+            "o" => {
+                object = if args.len() == 0 {"default".to_string()} else {args.join(" ")};
+                group = None;
+            }
+            "g" => {
+                group = if args.len() == 0 {None} else {Some(args.join(" "))};
+            }
+            "s" => {
+                smoothing_group = match args.get(0) {
+                    None | Some(&"off") => 0,
+                    Some(&"on") => 1,
+                    Some(arg) => arg.parse::<u32>().map_err(|_| ObjError::Parse {path: path.to_path_buf(), line: line_num, message: format!("\"{}\" is not a smoothing group", arg)})?,
+                };
+            }
+            "usemtl" => {
+                material = if args.len() == 0 {None} else {Some(args.join(" "))};
+            }
+            "mtllib" => {
+                // the names may contain spaces, so try the whole line first
+                let joined = directory.join(args.join(" "));
+                let files = if joined.is_file() {vec![joined]} else {args.iter().map(|arg| directory.join(arg)).collect()};
+                // a missing library is not an error, its materials get the defaults like any other undefined material
+                for file in files.iter().filter(|file| file.is_file()) {
+                    materials.append(&mut read_mtl(file)?);
+                }
+            }
+            _ => ()
+        }
+    }
+
+    // materials that were used but never defined still get an entry so every mesh can be matched up
+    let mut material_lookup: HashMap<String, usize> = HashMap::new();
+    for (i, material) in materials.iter().enumerate() {
+        material_lookup.entry(material.name.clone()).or_insert(i);
+    }
+    for builder in builders.iter() {
+        if let Some(name) = &builder.material {
+            if !material_lookup.contains_key(name) {
+                material_lookup.insert(name.clone(), materials.len());
+                materials.push(ObjMaterial::new(name));
+            }
+        }
+    }
+
+    Ok(ObjScene {
+        meshes: builders.into_iter().map(|builder| builder.build(&material_lookup)).collect(),
+        materials,
+    })
+}
+
+
+fn read_mtl(path: &Path) -> Result<Vec<ObjMaterial>, ObjError> {
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let mut materials: Vec<ObjMaterial> = Vec::new();
+
+    for (line_num, line) in read_lines(path)? {
+        let line = line.map_err(|error| ObjError::Io {path: path.to_path_buf(), error})?;
+        let (keyword, args) = match split_line(&line) {
+            Some(split) => split,
+            None => continue,
+        };
+
+        if keyword == "newmtl" {
+            materials.push(ObjMaterial::new(&args.join(" ")));
+            continue;
+        }
+
+        let material = match materials.last_mut() {
+            Some(material) => material,
+            None => return Err(ObjError::Parse {path: path.to_path_buf(), line: line_num, message: format!("\"{}\" found before any newmtl", keyword)}),
+        };
+
+        // texture statements can have options before the file name, so take the last argument
+        let texture = || -> Result<PathBuf, ObjError> {
+            match args.last() {
+                Some(name) => Ok(directory.join(name)),
+                None => Err(ObjError::Parse {path: path.to_path_buf(), line: line_num, message: format!("\"{}\" needs a file name", keyword)}),
+            }
+        };
+
+        match keyword {
+            "Ka" => material.ambient = parse_colour(&args, path, line_num)?,
+            "Kd" => material.diffuse = parse_colour(&args, path, line_num)?,
+            "Ks" => material.specular = parse_colour(&args, path, line_num)?,
+            "Ke" => material.emissive = parse_colour(&args, path, line_num)?,
+            "Ns" => material.shininess = parse_floats(&args, 1, [0.0], path, line_num)?[0],
+            "d" => material.dissolve = parse_floats(&args, 1, [0.0], path, line_num)?[0],
+            "Tr" => material.dissolve = 1.0 - parse_floats(&args, 1, [0.0], path, line_num)?[0],
+            "Ni" => material.optical_density = parse_floats(&args, 1, [0.0], path, line_num)?[0],
+            "Pr" => material.roughness = Some(parse_floats(&args, 1, [0.0], path, line_num)?[0]),
+            "Pm" => material.metallic = Some(parse_floats(&args, 1, [0.0], path, line_num)?[0]),
+            "illum" => {
+                let model = args.get(0).and_then(|arg| arg.parse::<u32>().ok());
+                if model.is_none() {
+                    return Err(ObjError::Parse {path: path.to_path_buf(), line: line_num, message: "illum needs an illumination model number".to_string()});
+                }
+                material.illumination_model = model;
+            }
+            "map_Ka" => material.ambient_texture = Some(texture()?),
+            "map_Kd" => material.diffuse_texture = Some(texture()?),
+            "map_Ks" => material.specular_texture = Some(texture()?),
+            "map_Ke" => material.emissive_texture = Some(texture()?),
+            "map_d" => material.dissolve_texture = Some(texture()?),
+            "map_Bump" | "map_bump" | "bump" | "norm" => material.normal_texture = Some(texture()?),
+            "map_Pr" => material.roughness_texture = Some(texture()?),
+            "map_Pm" => material.metallic_texture = Some(texture()?),
+            _ => ()
+        }
+    }
+
+    Ok(materials)
+}
+
+/// colours can be given as a single grey value
+fn parse_colour(args: &[&str], path: &Path, line: usize) -> Result<[f32; 3], ObjError> {
+    let colour = parse_floats(args, 1, [0.0; 3], path, line)?;
+    if args.len() == 1 {
+        return Ok([colour[0]; 3]);
+    }
+    if args.len() == 2 {
+        return Err(ObjError::Parse {path: path.to_path_buf(), line, message: "a colour needs 1 or 3 values".to_string()});
+    }
+    Ok(colour)
+}
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+use vulkano::{
+    buffer::{Buffer, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::{allocator::StandardCommandBufferAllocator, AutoCommandBufferBuilder, CommandBufferUsage, CopyImageToBufferInfo},
+    descriptor_set::allocator::StandardDescriptorSetAllocator,
+    device::Queue,
+    format::Format,
+    image::{view::ImageView, AttachmentImage, ImageAccess, ImageUsage},
+    memory::allocator::{AllocationCreateInfo, MemoryUsage},
+    sync::GpuFuture,
+};
+use vulkano_util::context::VulkanoContext;
+use super::{Error, get_general_compute_data};
+
+
+/// creates everything needed to render without a window, for use with an OffscreenTarget
+pub fn get_headless_graphics_data() -> (VulkanoContext, Arc<StandardCommandBufferAllocator>, Arc<StandardDescriptorSetAllocator>) {
+    get_general_compute_data()
+}
+
+
+/// A colour image that can be drawn into instead of a swapchain image, and read back to the cpu
+pub struct OffscreenTarget {
+    image: Arc<AttachmentImage>,
+    view: Arc<ImageView<AttachmentImage>>,
+    buffer: Subbuffer<[u8]>,
+    queue: Arc<Queue>,
+    command_buffer_allocator: Arc<StandardCommandBufferAllocator>,
+}
+
+impl OffscreenTarget {
+    /// creates a target of the given size
+    ///
+    /// The format must be an 8 bit rgba or bgra format, and must match the colour format of the pipeline drawing into it
+    pub fn new(
+        context: &VulkanoContext,
+        command_buffer_allocator: &Arc<StandardCommandBufferAllocator>,
+        dimensions: [u32; 2],
+        format: Format,
+    ) -> Result<Self, Error> {
+        let image = AttachmentImage::with_usage(
+            context.memory_allocator(),
+            dimensions,
+            format,
+            ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_SRC | ImageUsage::SAMPLED,
+        )?;
+
+        let buffer = Buffer::new_slice::<u8>(
+            context.memory_allocator(),
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                usage: MemoryUsage::Download,
+                ..Default::default()
+            },
+            (dimensions[0] * dimensions[1] * 4) as u64,
+        )?;
+
+        Ok(OffscreenTarget {
+            view: ImageView::new_default(image.clone())?,
+            image,
+            buffer,
+            queue: context.graphics_queue().clone(),
+            command_buffer_allocator: command_buffer_allocator.clone(),
+        })
+    }
+
+    /// the view to pass to a pipeline's draw functions
+    pub fn view(&self) -> Arc<ImageView<AttachmentImage>> {
+        self.view.clone()
+    }
+
+    pub fn dimensions(&self) -> [u32; 2] {
+        self.image.dimensions().width_height()
+    }
+
+    /// waits for the given future, then copies the image back to the cpu
+    ///
+    /// Returns tightly packed 8 bit rgba pixels, row by row from the top left, regardless of whether the target is rgba or bgra
+    pub fn read_pixels(
+        &self,
+        before_future: Box<dyn GpuFuture>,
+    ) -> Result<Vec<u8>, Error> {
+        let mut builder = AutoCommandBufferBuilder::primary(
+            &self.command_buffer_allocator,
+            self.queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+        builder.copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(self.image.clone(), self.buffer.clone()))?;
+        let command_buffer = builder.build()?;
+
+        before_future
+            .then_execute(self.queue.clone(), command_buffer)?
+            .then_signal_fence_and_flush()?
+            .wait(None)?;
+
+        let mut pixels = self.buffer.read()?.to_vec();
+        if is_bgra(self.image.format()) {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        Ok(pixels)
+    }
+
+    /// waits for the given future, then saves the image as a png
+    pub fn save_png(
+        &self,
+        before_future: Box<dyn GpuFuture>,
+        path: &str,
+    ) -> Result<(), Error> {
+        let pixels = self.read_pixels(before_future)?;
+        let [width, height] = self.dimensions();
+        image::save_buffer(path, &pixels, width, height, image::ColorType::Rgba8)?;
+        Ok(())
+    }
+}
+
+fn is_bgra(format: Format) -> bool {
+    match format {
+        Format::B8G8R8A8_SRGB | Format::B8G8R8A8_UNORM | Format::B8G8R8A8_SNORM | Format::B8G8R8A8_UINT | Format::B8G8R8A8_SINT => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,279 @@
+//! rays from the cursor into the world, and finding which mesh triangles they hit
+use maths::{Vector3, Matrix4};
+use super::{invert_affine, Aabb, Camera, Mesh, Position};
+use vulkano::buffer::BufferContents;
+
+
+/// A half line starting at origin
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vector3,
+    /// distances along the ray are measured in lengths of this
+    pub direction: Vector3,
+}
+
+impl Ray {
+    /// a ray with its direction normalised, so distances along it are in world units
+    pub fn new(origin: impl Into<Vector3>, direction: impl Into<Vector3>) -> Self {
+        let direction: Vector3 = direction.into();
+        Ray {
+            origin: origin.into(),
+            direction: if direction == Vector3::ZERO {direction} else {direction.normalised()},
+        }
+    }
+
+    /// the ray under the cursor, in pixels from the top left of a viewport of the given size
+    ///
+    /// Perspective rays start at the camera, orthographic rays start on the near plane
+    pub fn from_screen(cursor: [f32; 2], viewport_size: [u32; 2], camera: &Camera) -> Self {
+        let aspect_ratio = viewport_size[0] as f32 / viewport_size[1] as f32;
+        let projection = camera.projection.matrix(aspect_ratio, camera.reverse_z);
+
+        // vulkan's clip space has y pointing down the screen, like the cursor
+        let x = (cursor[0] / viewport_size[0] as f32 * 2.0 - 1.0) / projection[0][0];
+        let y = (cursor[1] / viewport_size[1] as f32 * 2.0 - 1.0) / projection[1][1];
+        let perspective = projection[2][3] != 0.0;
+        let (view_origin, view_direction) = if perspective {
+            (Vector3::ZERO, Vector3::new(x, y, -1.0))
+        } else {
+            (Vector3::new(x, y, -camera.projection.near()), Vector3::new(0.0, 0.0, -1.0))
+        };
+
+        // the rows of the view matrix's rotation are the camera's axes in world space
+        let view: [[f32; 4]; 4] = camera.get_view_matrix().into();
+        let axis = |row: usize| Vector3::new(view[0][row], view[1][row], view[2][row]);
+        let to_world = |vector: Vector3| axis(0) * vector.x + axis(1) * vector.y + axis(2) * vector.z;
+
+        Ray::new(camera.position + to_world(view_origin), to_world(view_direction))
+    }
+
+    pub fn at(&self, distance: f32) -> Vector3 {
+        self.origin + self.direction * distance
+    }
+
+    /// the ray in the space of a matrix, the direction is not renormalised so distances along it stay the same
+    pub fn transformed(&self, matrix: &Matrix4) -> Ray {
+        let columns: [[f32; 4]; 4] = (*matrix).into();
+        let column = |index: usize| Vector3::new(columns[index][0], columns[index][1], columns[index][2]);
+        let linear = |vector: Vector3| column(0) * vector.x + column(1) * vector.y + column(2) * vector.z;
+        Ray {
+            origin: linear(self.origin) + column(3),
+            direction: linear(self.direction),
+        }
+    }
+
+    /// the distance to a triangle, from either side, and the barycentric weights of the hit point
+    pub fn intersect_triangle(&self, a: impl Into<Vector3>, b: impl Into<Vector3>, c: impl Into<Vector3>) -> Option<(f32, [f32; 3])> {
+        // moller-trumbore
+        let (a, b, c): (Vector3, Vector3, Vector3) = (a.into(), b.into(), c.into());
+        let edge_1 = b - a;
+        let edge_2 = c - a;
+        let p = self.direction.cross(edge_2);
+        let determinant = edge_1.dot(p);
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+
+        let to_origin = self.origin - a;
+        let u = to_origin.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = to_origin.cross(edge_1);
+        let v = self.direction.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let distance = edge_2.dot(q) * inverse;
+        if distance < 0.0 {
+            return None;
+        }
+        Some((distance, [1.0 - u - v, u, v]))
+    }
+
+    /// the distance to where the ray enters the box, 0 if it starts inside
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let origin: [f32; 3] = self.origin.into();
+        let direction: [f32; 3] = self.direction.into();
+        let (mut near, mut far) = (0.0f32, f32::INFINITY);
+        for axis in 0..3 {
+            // a zero direction gives infinite distances, or nan on the edge of the slab, which max and min skip
+            let inverse = 1.0 / direction[axis];
+            let mut t_0 = (aabb.min[axis] - origin[axis]) * inverse;
+            let mut t_1 = (aabb.max[axis] - origin[axis]) * inverse;
+            if t_0 > t_1 {
+                std::mem::swap(&mut t_0, &mut t_1);
+            }
+            near = near.max(t_0);
+            far = far.min(t_1);
+            if near > far {
+                return None;
+            }
+        }
+        Some(near)
+    }
+}
+
+
+/// Where a ray hit a mesh
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    /// the position of the mesh in the list that was searched, 0 when a single mesh was searched
+    pub mesh: usize,
+    /// the triangle's position in the mesh's indices, its vertices are indices[triangle * 3..triangle * 3 + 3]
+    pub triangle: usize,
+    /// how far along the ray, in lengths of its direction
+    pub distance: f32,
+    /// the weights of the triangle's three vertices at the hit point
+    pub barycentric: [f32; 3],
+    /// the hit point, in the ray's space
+    pub point: Vector3,
+}
+
+
+/// A bounding volume hierarchy over a mesh's triangles, for finding ray hits without testing every triangle
+///
+/// The triangles are copied in, so it must be rebuilt if the mesh changes
+#[derive(Clone, Debug)]
+pub struct MeshBvh {
+    nodes: Vec<BvhNode>,
+    /// triangle corners, sorted so every node's triangles are next to each other
+    triangles: Vec<[[f32; 3]; 3]>,
+    /// the index in the mesh of each sorted triangle
+    triangle_indices: Vec<usize>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct BvhNode {
+    bounds: Aabb,
+    /// for a leaf the first triangle, otherwise the index of the second child, the first child always follows its parent
+    start: usize,
+    /// the number of triangles in a leaf, 0 for a branch
+    count: usize,
+}
+
+/// leaves are split until they hold this many triangles or fewer
+const LEAF_SIZE: usize = 4;
+
+impl MeshBvh {
+    pub fn new<T: Position + Clone + Copy + BufferContents>(mesh: &Mesh<T>) -> Self {
+        let mut triangles: Vec<([[f32; 3]; 3], usize)> = mesh
+            .indices
+            .chunks_exact(3)
+            .enumerate()
+            .map(|(index, corners)| ([
+                mesh.vertices[corners[0] as usize].pos(),
+                mesh.vertices[corners[1] as usize].pos(),
+                mesh.vertices[corners[2] as usize].pos(),
+            ], index))
+            .collect();
+
+        let mut nodes = Vec::new();
+        if !triangles.is_empty() {
+            let count = triangles.len();
+            build(&mut nodes, &mut triangles, 0, count);
+        }
+
+        MeshBvh {
+            nodes,
+            triangle_indices: triangles.iter().map(|(_, index)| *index).collect(),
+            triangles: triangles.into_iter().map(|(corners, _)| corners).collect(),
+        }
+    }
+
+    /// the box around every triangle, None for an empty mesh
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.nodes.first().map(|node| node.bounds)
+    }
+
+    /// the closest triangle the ray hits
+    pub fn intersect(&self, ray: &Ray) -> Option<RayHit> {
+        let mut closest: Option<RayHit> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let limit = closest.map_or(f32::INFINITY, |hit| hit.distance);
+            match ray.intersect_aabb(&node.bounds) {
+                Some(distance) if distance <= limit => (),
+                _ => continue,
+            }
+
+            if node.count > 0 {
+                for sorted in node.start..node.start + node.count {
+                    let [a, b, c] = self.triangles[sorted];
+                    if let Some((distance, barycentric)) = ray.intersect_triangle(a, b, c) {
+                        if closest.map_or(true, |hit| distance < hit.distance) {
+                            closest = Some(RayHit {
+                                mesh: 0,
+                                triangle: self.triangle_indices[sorted],
+                                distance,
+                                barycentric,
+                                point: ray.at(distance),
+                            });
+                        }
+                    }
+                }
+            } else {
+                stack.push(node.start);
+                stack.push(index + 1);
+            }
+        }
+        closest
+    }
+
+    /// the closest hit on the mesh placed in the world by a matrix, with the distance and point in world space
+    pub fn intersect_transformed(&self, ray: &Ray, world: &Matrix4) -> Option<RayHit> {
+        let local = ray.transformed(&invert_affine(*world)?);
+        self.intersect(&local).map(|hit| RayHit {
+            point: ray.at(hit.distance),
+            ..hit
+        })
+    }
+}
+
+/// builds the node for triangles[start..end], and everything below it
+fn build(nodes: &mut Vec<BvhNode>, triangles: &mut [([[f32; 3]; 3], usize)], start: usize, end: usize) {
+    let slice = &mut triangles[start..end];
+    let bounds = Aabb::from_points(slice.iter().flat_map(|(corners, _)| corners.iter().copied())).unwrap();
+    let index = nodes.len();
+    nodes.push(BvhNode {bounds, start, count: end - start});
+    if end - start <= LEAF_SIZE {
+        return;
+    }
+
+    // split at the median centre along the axis the centres are most spread over
+    let centre = |corners: &[[f32; 3]; 3], axis: usize| corners[0][axis] + corners[1][axis] + corners[2][axis];
+    let centres = Aabb::from_points(slice.iter().map(|(corners, _)| [centre(corners, 0), centre(corners, 1), centre(corners, 2)])).unwrap();
+    let extents = centres.extents();
+    let axis = (0..3).max_by(|a, b| extents[*a].total_cmp(&extents[*b])).unwrap();
+    let middle = slice.len() / 2;
+    slice.select_nth_unstable_by(middle, |a, b| centre(&a.0, axis).total_cmp(&centre(&b.0, axis)));
+
+    build(nodes, triangles, start, start + middle);
+    let second = nodes.len();
+    build(nodes, triangles, start + middle, end);
+    nodes[index].start = second;
+    nodes[index].count = 0;
+}
+
+
+/// the closest hit across several meshes, each placed in the world by a matrix such as a scene node's world matrix
+///
+/// The hit's mesh is the position of the mesh it hit in the iterator
+pub fn pick<'a, I>(ray: &Ray, meshes: I) -> Option<RayHit>
+where
+    I: IntoIterator<Item = (&'a MeshBvh, Matrix4)>,
+{
+    meshes
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, (bvh, world))| bvh.intersect_transformed(ray, &world).map(|hit| RayHit {mesh: index, ..hit}))
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
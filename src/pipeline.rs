@@ -1,25 +1,342 @@
 use std::sync::Arc;
 use vulkano::{
     memory::allocator::StandardMemoryAllocator,
-    device::Queue,
+    device::{Device, Queue},
     render_pass::{RenderPass, Subpass, Framebuffer, FramebufferCreateInfo},
-    pipeline::{Pipeline, GraphicsPipeline, graphics::{viewport::{Viewport, ViewportState}, vertex_input::VertexBufferDescription, input_assembly::InputAssemblyState, multisample::MultisampleState, depth_stencil::DepthStencilState}, PipelineBindPoint},
-    image::{view::ImageView, AttachmentImage, ImageAccess, ImageViewAbstract, SampleCount},
-    command_buffer::{allocator::StandardCommandBufferAllocator, AutoCommandBufferBuilder, CommandBufferUsage, RenderPassBeginInfo, SubpassContents},
+    pipeline::{
+        Pipeline, GraphicsPipeline, PipelineBindPoint, StateMode,
+        graphics::{
+            viewport::{Viewport, ViewportState},
+            vertex_input::{VertexBufferDescription, VertexBuffersCollection},
+            input_assembly::{InputAssemblyState, PrimitiveTopology},
+            rasterization::{RasterizationState, CullMode, FrontFace, PolygonMode, DepthBias, DepthBiasState},
+            color_blend::{ColorBlendState, AttachmentBlend, BlendFactor, BlendOp},
+            multisample::MultisampleState,
+            depth_stencil::{DepthStencilState, DepthState, CompareOp},
+        },
+    },
+    image::{view::ImageView, AttachmentImage, ImageAccess, ImageAspects, ImageViewAbstract, SampleCount},
+    command_buffer::{allocator::StandardCommandBufferAllocator, AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer, RenderPassBeginInfo, SubpassContents},
     sync::GpuFuture,
-    buffer::Subbuffer,
+    buffer::{BufferContents, Subbuffer},
     single_pass_renderpass,
-    format::Format,
-    shader::ShaderModule,
+    format::{Format, FormatFeatures, ClearValue},
+    shader::{ShaderModule, SpecializationConstants},
     descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet, allocator::StandardDescriptorSetAllocator}
     };
-use vulkano_util::{renderer::SwapchainImageView, context::VulkanoContext};
-use super::Normal;
+use vulkano_util::context::VulkanoContext;
+use super::{Normal, Error, Texture, ShaderFile};
 
 
 
 
+/// How the fragment shader output is combined with what is already in the image
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+    /// the output replaces the image
+    None,
+    /// standard transparency, blended by the output's alpha
+    Alpha,
+    /// the output is added on top, for glows and particles
+    Additive,
+    /// transparency for colours that have already been multiplied by their alpha
+    PremultipliedAlpha,
+}
+
+impl BlendMode {
+    fn colour_blend_state(&self, attachments: u32) -> ColorBlendState {
+        let state = ColorBlendState::new(attachments);
+        match self {
+            BlendMode::None => state,
+            BlendMode::Alpha => state.blend_alpha(),
+            BlendMode::Additive => state.blend_additive(),
+            BlendMode::PremultipliedAlpha => state.blend(AttachmentBlend {
+                color_op: BlendOp::Add,
+                color_source: BlendFactor::One,
+                color_destination: BlendFactor::OneMinusSrcAlpha,
+                alpha_op: BlendOp::Add,
+                alpha_source: BlendFactor::One,
+                alpha_destination: BlendFactor::OneMinusSrcAlpha,
+            }),
+        }
+    }
+}
+
+
+/// The fixed function state of a graphics pipeline, the default matches what MultiSamplePipeline3D has always used
+#[derive(Clone, Debug)]
+pub struct GraphicsPipelineSettings {
+    pub vertex_entry_point: String,
+    pub fragment_entry_point: String,
+    pub topology: PrimitiveTopology,
+    /// lets an index of u32::MAX restart strips and fans
+    pub primitive_restart: bool,
+    pub cull_mode: CullMode,
+    pub front_face: FrontFace,
+    /// anything other than fill needs the fill_mode_non_solid device feature
+    pub polygon_mode: PolygonMode,
+    pub blend_mode: BlendMode,
+    pub depth_test: bool,
+    pub depth_write: bool,
+    pub depth_compare: CompareOp,
+    /// offsets the depth of each fragment, mostly used to stop shadow acne when drawing shadow maps
+    pub depth_bias: Option<DepthBias>,
+}
+
+impl Default for GraphicsPipelineSettings {
+    fn default() -> Self {
+        GraphicsPipelineSettings {
+            vertex_entry_point: "main".to_string(),
+            fragment_entry_point: "main".to_string(),
+            topology: PrimitiveTopology::TriangleList,
+            primitive_restart: false,
+            cull_mode: CullMode::None,
+            front_face: FrontFace::CounterClockwise,
+            polygon_mode: PolygonMode::Fill,
+            blend_mode: BlendMode::None,
+            depth_test: true,
+            depth_write: true,
+            depth_compare: CompareOp::Less,
+            depth_bias: None,
+        }
+    }
+}
+
+
+/// Builds a graphics pipeline for any subpass
+/// 
+/// Specialization constants are set with the shader's generated SpecializationConstants struct
+pub struct GraphicsPipelineBuilder<VertexSpecialization = (), FragmentSpecialization = ()> {
+    vertex_shader: Arc<ShaderModule>,
+    fragment_shader: Arc<ShaderModule>,
+    vertex_def: Vec<VertexBufferDescription>,
+    settings: GraphicsPipelineSettings,
+    sample_count: SampleCount,
+    vertex_specialization: VertexSpecialization,
+    fragment_specialization: FragmentSpecialization,
+}
+
+impl GraphicsPipelineBuilder {
+    pub fn new(
+        vertex_shader: &Arc<ShaderModule>,
+        fragment_shader: &Arc<ShaderModule>,
+        vertex_def: &[VertexBufferDescription],
+    ) -> Self {
+        GraphicsPipelineBuilder {
+            vertex_shader: vertex_shader.clone(),
+            fragment_shader: fragment_shader.clone(),
+            vertex_def: vertex_def.to_vec(),
+            settings: GraphicsPipelineSettings::default(),
+            sample_count: SampleCount::Sample1,
+            vertex_specialization: (),
+            fragment_specialization: (),
+        }
+    }
+}
+
+impl<VertexSpecialization, FragmentSpecialization> GraphicsPipelineBuilder<VertexSpecialization, FragmentSpecialization>
+where
+    VertexSpecialization: SpecializationConstants + Clone,
+    FragmentSpecialization: SpecializationConstants + Clone,
+{
+    /// replaces every setting at once
+    pub fn settings(mut self, settings: GraphicsPipelineSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn entry_points(mut self, vertex_entry_point: &str, fragment_entry_point: &str) -> Self {
+        self.settings.vertex_entry_point = vertex_entry_point.to_string();
+        self.settings.fragment_entry_point = fragment_entry_point.to_string();
+        self
+    }
+
+    pub fn topology(mut self, topology: PrimitiveTopology) -> Self {
+        self.settings.topology = topology;
+        self
+    }
+
+    pub fn primitive_restart(mut self, primitive_restart: bool) -> Self {
+        self.settings.primitive_restart = primitive_restart;
+        self
+    }
+
+    pub fn cull_mode(mut self, cull_mode: CullMode) -> Self {
+        self.settings.cull_mode = cull_mode;
+        self
+    }
+
+    pub fn front_face(mut self, front_face: FrontFace) -> Self {
+        self.settings.front_face = front_face;
+        self
+    }
+
+    pub fn polygon_mode(mut self, polygon_mode: PolygonMode) -> Self {
+        self.settings.polygon_mode = polygon_mode;
+        self
+    }
+
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.settings.blend_mode = blend_mode;
+        self
+    }
+
+    /// sets whether fragments are tested against and written to the depth buffer, and how they are compared
+    pub fn depth(mut self, test: bool, write: bool, compare: CompareOp) -> Self {
+        self.settings.depth_test = test;
+        self.settings.depth_write = write;
+        self.settings.depth_compare = compare;
+        self
+    }
+
+    pub fn depth_bias(mut self, depth_bias: Option<DepthBias>) -> Self {
+        self.settings.depth_bias = depth_bias;
+        self
+    }
+
+    /// the sample count must match the subpass the pipeline is built for
+    pub fn sample_count(mut self, sample_count: SampleCount) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    pub fn vertex_specialization<Constants>(self, constants: Constants) -> GraphicsPipelineBuilder<Constants, FragmentSpecialization>
+    where Constants: SpecializationConstants + Clone {
+        GraphicsPipelineBuilder {
+            vertex_shader: self.vertex_shader,
+            fragment_shader: self.fragment_shader,
+            vertex_def: self.vertex_def,
+            settings: self.settings,
+            sample_count: self.sample_count,
+            vertex_specialization: constants,
+            fragment_specialization: self.fragment_specialization,
+        }
+    }
+
+    pub fn fragment_specialization<Constants>(self, constants: Constants) -> GraphicsPipelineBuilder<VertexSpecialization, Constants>
+    where Constants: SpecializationConstants + Clone {
+        GraphicsPipelineBuilder {
+            vertex_shader: self.vertex_shader,
+            fragment_shader: self.fragment_shader,
+            vertex_def: self.vertex_def,
+            settings: self.settings,
+            sample_count: self.sample_count,
+            vertex_specialization: self.vertex_specialization,
+            fragment_specialization: constants,
+        }
+    }
+
+    /// builds the pipeline for the given subpass, with a dynamic viewport
+    pub fn build(
+        &self,
+        device: &Arc<Device>,
+        subpass: Subpass,
+    ) -> Result<Arc<GraphicsPipeline>, Error> {
+        let settings = &self.settings;
+        let vertex_entry = self.vertex_shader.entry_point(&settings.vertex_entry_point)
+            .ok_or_else(|| Error::MissingEntryPoint(settings.vertex_entry_point.clone()))?;
+        let fragment_entry = self.fragment_shader.entry_point(&settings.fragment_entry_point)
+            .ok_or_else(|| Error::MissingEntryPoint(settings.fragment_entry_point.clone()))?;
+
+        let mut input_assembly = InputAssemblyState::new().topology(settings.topology);
+        if settings.primitive_restart {
+            input_assembly = input_assembly.primitive_restart_enable();
+        }
+
+        let depth_stencil = if settings.depth_test {
+            DepthStencilState {
+                depth: Some(DepthState {
+                    enable_dynamic: false,
+                    write_enable: StateMode::Fixed(settings.depth_write),
+                    compare_op: StateMode::Fixed(settings.depth_compare),
+                }),
+                ..DepthStencilState::disabled()
+            }
+        } else {
+            DepthStencilState::disabled()
+        };
+
+        let mut rasterization = RasterizationState::new()
+            .cull_mode(settings.cull_mode)
+            .front_face(settings.front_face)
+            .polygon_mode(settings.polygon_mode);
+        rasterization.depth_bias = settings.depth_bias.map(|bias| DepthBiasState {
+            enable_dynamic: false,
+            bias: StateMode::Fixed(bias),
+        });
+
+        Ok(GraphicsPipeline::start()
+            .vertex_input_state(self.vertex_def.as_slice())
+            .vertex_shader(vertex_entry, self.vertex_specialization.clone())
+            .input_assembly_state(input_assembly)
+            .rasterization_state(rasterization)
+            .fragment_shader(fragment_entry, self.fragment_specialization.clone())
+            .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
+            .color_blend_state(settings.blend_mode.colour_blend_state(subpass.num_color_attachments()))
+            .multisample_state(MultisampleState {
+                rasterization_samples: self.sample_count,
+                ..Default::default()
+            })
+            .depth_stencil_state(depth_stencil)
+            .render_pass(subpass)
+            .build(device.clone())?)
+    }
+}
+
+
+/// Settings for a MultiSamplePipeline3D
+#[derive(Clone, Debug)]
+pub struct MultiSamplePipelineConfig {
+    /// how many samples are taken per pixel
+    pub sample_count: SampleCount,
+    /// the format of the images drawn into, None will match whatever image is given to the draw functions, such as the swapchain image
+    pub colour_format: Option<Format>,
+    /// the format of the depth buffer, None will pick the most precise depth stencil format the device supports
+    pub depth_format: Option<Format>,
+    pub clear_colour: [f32; 4],
+    /// the depth the depth buffer is cleared to, 1.0 normally or 0.0 with a reverse z projection
+    pub clear_depth: f32,
+    /// if false the target image is loaded rather than cleared, so each draw lands on top of what is already in it,
+    /// this draws straight into the target so the sample count must be Sample1, and the depth buffer is still cleared every frame
+    pub clear: bool,
+    /// the fixed function state of the pipeline, such as culling, blending and entry points
+    pub pipeline_settings: GraphicsPipelineSettings,
+}
+
+impl Default for MultiSamplePipelineConfig {
+    fn default() -> Self {
+        MultiSamplePipelineConfig {
+            sample_count: SampleCount::Sample2,
+            colour_format: None,
+            depth_format: None,
+            clear_colour: [0.0, 0.0, 0.0, 1.0],
+            clear_depth: 1.0,
+            clear: true,
+            pipeline_settings: GraphicsPipelineSettings::default(),
+        }
+    }
+}
+
+impl MultiSamplePipelineConfig {
+    /// sets up the depth buffer for a camera with a reverse z projection, where near is 1.0 and far is 0.0
+    pub fn reverse_z(mut self) -> Self {
+        self.clear_depth = 0.0;
+        self.pipeline_settings.depth_compare = CompareOp::Greater;
+        self
+    }
+}
+
+/// depth formats in order of preference, the first the device supports is used when no depth format is given
+const DEPTH_FORMATS: [Format; 4] = [Format::D32_SFLOAT_S8_UINT, Format::D24_UNORM_S8_UINT, Format::D32_SFLOAT, Format::D16_UNORM];
+
+/// the colour format the render pass is first built with when the config leaves it to the target image
+const PLACEHOLDER_COLOUR_FORMAT: Format = Format::B8G8R8A8_SRGB;
+
+
+/// A multisampled 3D pipeline, each draw resolves into the image given to it,
+/// either a swapchain image or an offscreen target
 pub struct MultiSamplePipeline3D {
+    device: Arc<Device>,
     allocator: Arc<StandardMemoryAllocator>,
     queue: Arc<Queue>,
     render_pass: Arc<RenderPass>,
@@ -27,9 +344,15 @@ pub struct MultiSamplePipeline3D {
     command_buffer_allocator: Arc<StandardCommandBufferAllocator>,
     descriptor_set_allocator: Arc<StandardDescriptorSetAllocator>,
 
+    vertex_shader: Arc<ShaderModule>,
+    fragment_shader: Arc<ShaderModule>,
+    vertex_def: Vec<VertexBufferDescription>,
+
     intermediary: Arc<ImageView<AttachmentImage>>,
     depth: Arc<ImageView<AttachmentImage>>,
-    sample_count: SampleCount,
+    config: MultiSamplePipelineConfig,
+    colour_format: Format,
+    depth_format: Format,
 }
 
 impl MultiSamplePipeline3D {
@@ -46,66 +369,190 @@ impl MultiSamplePipeline3D {
         fragment_shader: &Arc<ShaderModule>,
         vertex_def: &[VertexBufferDescription],
         sample_count: Option<SampleCount>,
-    ) -> Self {
+    ) -> Result<Self, Error> {
+        Self::new_with_config(
+            context,
+            command_buffer_allocator,
+            descriptor_set_allocator,
+            vertex_shader,
+            fragment_shader,
+            vertex_def,
+            MultiSamplePipelineConfig {
+                sample_count: sample_count.unwrap_or(SampleCount::Sample2),
+                ..Default::default()
+            },
+        )
+    }
 
-        let samples = sample_count.unwrap_or(SampleCount::Sample2);
+    /// creates a new multisample pipeline for the given shaders, with formats and clearing set by the config
+    pub fn new_with_config(
+        context: &VulkanoContext,
+        command_buffer_allocator: &Arc<StandardCommandBufferAllocator>,
+        descriptor_set_allocator: &Arc<StandardDescriptorSetAllocator>,
+        vertex_shader: &Arc<ShaderModule>,
+        fragment_shader: &Arc<ShaderModule>,
+        vertex_def: &[VertexBufferDescription],
+        config: MultiSamplePipelineConfig,
+    ) -> Result<Self, Error> {
+        // a single sampled target can't be loaded into a multisampled attachment
+        if !config.clear && config.sample_count != SampleCount::Sample1 {
+            return Err(Error::MultisampledWithoutClear);
+        }
+
+        let colour_format = config.colour_format.unwrap_or(PLACEHOLDER_COLOUR_FORMAT);
+        let depth_format = match config.depth_format {
+            Some(format) => format,
+            None => Self::find_depth_format(context)?,
+        };
 
-        let render_pass = Self::create_render_pass(context, samples);
-        let pipeline = Self::create_pipeline(vertex_shader, fragment_shader, vertex_def, &render_pass, context, samples);
+        let render_pass = Self::create_render_pass(context.device(), &config, colour_format, depth_format)?;
+        let pipeline = Self::create_pipeline(vertex_shader, fragment_shader, vertex_def, &render_pass, context.device(), &config)?;
 
-        let intermediary_image = ImageView::new_default(
-            AttachmentImage::transient_multisampled(context.memory_allocator(), [1, 1], samples, Format::B8G8R8A8_SRGB).unwrap()
-        ).unwrap();
-        let depth = ImageView::new_default(
-            AttachmentImage::transient_multisampled(context.memory_allocator(), [1, 1], samples, Format::D16_UNORM).unwrap()
-        ).unwrap();
+        let intermediary = Self::create_attachment(context.memory_allocator(), &config, [1, 1], colour_format)?;
+        let depth = Self::create_attachment(context.memory_allocator(), &config, [1, 1], depth_format)?;
         
-        Self {
+        Ok(Self {
+            device: context.device().clone(),
             allocator: context.memory_allocator().clone(),
             queue: context.graphics_queue().clone(),
             render_pass,
             pipeline,
             command_buffer_allocator: command_buffer_allocator.clone(),
             descriptor_set_allocator: descriptor_set_allocator.clone(),
-            intermediary: intermediary_image,
-            depth: depth,
-            sample_count: samples
+            vertex_shader: vertex_shader.clone(),
+            fragment_shader: fragment_shader.clone(),
+            vertex_def: vertex_def.to_vec(),
+            intermediary,
+            depth,
+            config,
+            colour_format,
+            depth_format,
+        })
+    }
+
+    pub fn config(&self) -> &MultiSamplePipelineConfig {
+        &self.config
+    }
+
+    /// the depth format in use, either the one given in the config or the one picked for the device
+    pub fn depth_format(&self) -> Format {
+        self.depth_format
+    }
+
+    pub fn set_clear_colour(&mut self, clear_colour: [f32; 4]) {
+        self.config.clear_colour = clear_colour;
+    }
+
+    /// rebuilds the pipeline with new shaders, if building fails the previous shaders are kept
+    pub fn set_shaders(
+        &mut self,
+        vertex_shader: &Arc<ShaderModule>,
+        fragment_shader: &Arc<ShaderModule>,
+    ) -> Result<(), Error> {
+        self.pipeline = Self::create_pipeline(vertex_shader, fragment_shader, &self.vertex_def, &self.render_pass, &self.device, &self.config)?;
+        self.vertex_shader = vertex_shader.clone();
+        self.fragment_shader = fragment_shader.clone();
+        Ok(())
+    }
+
+    /// checks both shader files for changes and rebuilds the pipeline if either has changed, returning true if it was rebuilt
+    /// 
+    /// Compile errors are returned without touching the pipeline, so they can be printed and drawing carried on with the old shaders
+    pub fn hot_reload(
+        &mut self,
+        vertex_shader: &mut ShaderFile,
+        fragment_shader: &mut ShaderFile,
+    ) -> Result<bool, Error> {
+        // both are polled first so a change to each is picked up in one reload
+        let vertex_changed = vertex_shader.poll()?;
+        let fragment_changed = fragment_shader.poll()?;
+        if !vertex_changed && !fragment_changed {
+            return Ok(false);
         }
+        self.set_shaders(vertex_shader.module(), fragment_shader.module())?;
+        Ok(true)
+    }
+
+    fn find_depth_format(context: &VulkanoContext) -> Result<Format, Error> {
+        DEPTH_FORMATS.into_iter()
+            .find(|format| {
+                context.device().physical_device().format_properties(*format)
+                    .map(|properties| properties.optimal_tiling_features.intersects(FormatFeatures::DEPTH_STENCIL_ATTACHMENT))
+                    .unwrap_or(false)
+            })
+            .ok_or(Error::UnsupportedDepthFormat)
+    }
+
+    fn create_attachment(
+        allocator: &Arc<StandardMemoryAllocator>,
+        config: &MultiSamplePipelineConfig,
+        dimensions: [u32; 2],
+        format: Format,
+    ) -> Result<Arc<ImageView<AttachmentImage>>, Error> {
+        let image = AttachmentImage::transient_multisampled(allocator, dimensions, config.sample_count, format)?;
+        Ok(ImageView::new_default(image)?)
     }
 
     fn create_render_pass(
-        context: &VulkanoContext,
-        sample_num: SampleCount,
-    ) -> Arc<RenderPass> {
-        single_pass_renderpass!(
-            context.device().clone(),
-            attachments: {
-                intermediary: {
-                    load: Clear,
-                    store: DontCare,
-                    format: Format::B8G8R8A8_SRGB,
-                    samples: sample_num,
+        device: &Arc<Device>,
+        config: &MultiSamplePipelineConfig,
+        colour_format: Format,
+        depth_format: Format,
+    ) -> Result<Arc<RenderPass>, Error> {
+        let sample_num = config.sample_count;
+        if config.clear {
+            Ok(single_pass_renderpass!(
+                device.clone(),
+                attachments: {
+                    intermediary: {
+                        load: Clear,
+                        store: DontCare,
+                        format: colour_format,
+                        samples: sample_num,
+                    },
+                    depth: {
+                        load: Clear,
+                        store: DontCare,
+                        format: depth_format,
+                        samples: sample_num,
+                    },
+                    end: {
+                        load: DontCare,
+                        store: Store,
+                        format: colour_format,
+                        samples: 1,
+                    }
                 },
-                depth: {
-                    load: Clear,
-                    store: DontCare,
-                    format: Format::D16_UNORM,
-                    samples: sample_num,
+                pass: {
+                    color: [intermediary],
+                    depth_stencil: {depth},
+                    resolve: [end],
+                }
+            )?)
+        } else {
+            // the target is loaded and drawn into directly, the resolve in the multisampled pass would overwrite it
+            Ok(single_pass_renderpass!(
+                device.clone(),
+                attachments: {
+                    end: {
+                        load: Load,
+                        store: Store,
+                        format: colour_format,
+                        samples: 1,
+                    },
+                    depth: {
+                        load: Clear,
+                        store: DontCare,
+                        format: depth_format,
+                        samples: 1,
+                    }
                 },
-                end: {
-                    load: DontCare,
-                    store: Store,
-                    format: Format::B8G8R8A8_SRGB,
-                    samples: 1,
+                pass: {
+                    color: [end],
+                    depth_stencil: {depth},
                 }
-            },
-            pass: {
-                color: [intermediary],
-                depth_stencil: {depth},
-                resolve: [end],
-            }
-        )
-        .unwrap()
+            )?)
+        }
     }
 
     fn create_pipeline(
@@ -113,233 +560,378 @@ impl MultiSamplePipeline3D {
         fragment_shader: &Arc<ShaderModule>,
         vertex_def: &[VertexBufferDescription],
         render_pass: &Arc<RenderPass>,
-        context: &VulkanoContext,
-        sample_count: SampleCount
-    ) -> Arc<GraphicsPipeline> {
-
+        device: &Arc<Device>,
+        config: &MultiSamplePipelineConfig,
+    ) -> Result<Arc<GraphicsPipeline>, Error> {
         let subpass = Subpass::from(render_pass.clone(), 0).unwrap();
-        GraphicsPipeline::start()
-            .vertex_input_state(vertex_def)
-            .vertex_shader(vertex_shader.entry_point("main").unwrap(), ())
-            .input_assembly_state(InputAssemblyState::new())
-            .fragment_shader(fragment_shader.entry_point("main").unwrap(), ())
-            .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
-            .render_pass(subpass.clone())
-            .multisample_state(MultisampleState {
-                rasterization_samples: sample_count,
-                ..Default::default()
-            })
-            .depth_stencil_state(DepthStencilState::simple_depth_test())
-            .build(context.device().clone())
-            .unwrap()
+        GraphicsPipelineBuilder::new(vertex_shader, fragment_shader, vertex_def)
+            .settings(config.pipeline_settings.clone())
+            .sample_count(config.sample_count)
+            .build(device, subpass)
     }
 
-    /// draws a single mesh with no normals
-    pub fn draw_from_vertices<VertexType, UniformBufferType>(
+    /// rebuilds the render pass and pipeline if the config leaves the colour format to the target and the target's format has changed
+    fn match_target_format(&mut self, format: Format) -> Result<(), Error> {
+        if self.config.colour_format.is_some() || format == self.colour_format {
+            return Ok(());
+        }
+        self.render_pass = Self::create_render_pass(&self.device, &self.config, format, self.depth_format)?;
+        self.pipeline = Self::create_pipeline(&self.vertex_shader, &self.fragment_shader, &self.vertex_def, &self.render_pass, &self.device, &self.config)?;
+        self.colour_format = format;
+        Ok(())
+    }
+
+    /// starts recording a frame that draws into the given image
+    /// 
+    /// Any number of meshes can be drawn into the frame, the image is only cleared once at the start.
+    /// The frame must be ended to get the future to present or wait on
+    pub fn begin_frame(
         &mut self,
         before_future: Box<dyn GpuFuture>,
-        image: SwapchainImageView,
-
-        vertex_buffer: &Subbuffer<[VertexType]>,
-        index_buffer: &Subbuffer<[u32]>,
-        uniforms: &Subbuffer<UniformBufferType>
-    ) -> Box<dyn GpuFuture> {
+        image: Arc<dyn ImageViewAbstract>,
+    ) -> Result<MultiSampleFrame, Error> {
 
         let mut builder = AutoCommandBufferBuilder::primary(
             &self.command_buffer_allocator,
             self.queue.queue_family_index(),
             CommandBufferUsage::OneTimeSubmit,
-        )
-        .unwrap();
+        )?;
 
         let dimensions = image.image().dimensions().width_height();
-        // Resize intermediary image
-        if dimensions != self.intermediary.dimensions().width_height() {
-            self.intermediary = ImageView::new_default(
-                AttachmentImage::transient_multisampled(
-                    &self.allocator,
-                    dimensions,
-                    self.sample_count,
-                    image.image().format(),
-                )
-                .unwrap(),
-            )
-            .unwrap();
+        self.match_target_format(image.image().format())?;
+        // Resize intermediary image, which is only used when multisampling
+        if self.config.clear && (dimensions != self.intermediary.dimensions().width_height() || self.intermediary.format() != Some(self.colour_format)) {
+            self.intermediary = Self::create_attachment(&self.allocator, &self.config, dimensions, self.colour_format)?;
         }
         // Resize depth image
         if dimensions != self.depth.dimensions().width_height() {
-            self.depth = ImageView::new_default(
-                AttachmentImage::transient_multisampled(
-                    &self.allocator,
-                    dimensions,
-                    self.sample_count,
-                    Format::D16_UNORM,
-                )
-                .unwrap(),
-            )
-            .unwrap();
+            self.depth = Self::create_attachment(&self.allocator, &self.config, dimensions, self.depth_format)?;
         }
 
-        let layout = self.pipeline.layout().set_layouts().get(0).unwrap();
-        let set = PersistentDescriptorSet::new(
-            &self.descriptor_set_allocator,
-            layout.clone(),
-            [WriteDescriptorSet::buffer(0, uniforms.clone())],
-        )
-        .unwrap();
-
+        let attachments = if self.config.clear {
+            vec![self.intermediary.clone(), self.depth.clone(), image]
+        } else {
+            vec![image, self.depth.clone()]
+        };
         let framebuffer = Framebuffer::new(self.render_pass.clone(),
             FramebufferCreateInfo {
-                attachments: vec![self.intermediary.clone(), self.depth.clone(), image],
+                attachments,
             ..Default::default()
-        })
-        .unwrap();
+        })?;
 
         // Begin render pipeline commands
         builder
             .begin_render_pass(
                 RenderPassBeginInfo {
-                    clear_values: vec![
-                        Some([0.0, 0.0, 0.0, 1.0].into()),
-                        Some(1f32.into()),
-                        Some([0.0, 0.0, 0.0, 1.0].into()),
-                    ],
+                    clear_values: self.clear_values(),
                     ..RenderPassBeginInfo::framebuffer(framebuffer)
                 },
                 SubpassContents::Inline,
-            )
-            .unwrap();
-
+            )?;
 
         builder
             .bind_pipeline_graphics(self.pipeline.clone())
-            .bind_descriptor_sets(
-                PipelineBindPoint::Graphics,
-                self.pipeline.layout().clone(),
-                0,
-                set,
-            )
             .set_viewport(0, vec![Viewport {
                 origin: [0.0, 0.0],
                 dimensions: [dimensions[0] as f32, dimensions[1] as f32],
                 depth_range: 0.0..1.0,
-            }])
-            .bind_vertex_buffers(0, vertex_buffer.clone())
-            .bind_index_buffer(index_buffer.clone())
-            .draw_indexed(index_buffer.len() as u32, 1, 0, 0, 0)
-            .unwrap();
-
-
-        builder.end_render_pass().unwrap();
-        let command_buffer = builder.build().unwrap();
-        let after_future = before_future.then_execute(self.queue.clone(), command_buffer).unwrap();
+            }]);
+
+        Ok(MultiSampleFrame {
+            builder,
+            before_future,
+            pipeline: self.pipeline.clone(),
+            queue: self.queue.clone(),
+            descriptor_set_allocator: self.descriptor_set_allocator.clone(),
+        })
+    }
 
-        after_future.boxed()
+    /// draws a single mesh with no normals
+    pub fn draw_from_vertices<VertexType, UniformBufferType>(
+        &mut self,
+        before_future: Box<dyn GpuFuture>,
+        image: Arc<dyn ImageViewAbstract>,
 
+        vertex_buffer: &Subbuffer<[VertexType]>,
+        index_buffer: &Subbuffer<[u32]>,
+        uniforms: &Subbuffer<UniformBufferType>
+    ) -> Result<Box<dyn GpuFuture>, Error> {
+        let mut frame = self.begin_frame(before_future, image)?;
+        frame.draw_vertices(vertex_buffer, index_buffer, uniforms)?;
+        frame.end()
     }
 
     /// draws a single mesh with normals
     pub fn draw_from_vertices_and_normals<VertexType, UniformBufferType>(
         &mut self,
         before_future: Box<dyn GpuFuture>,
-        image: SwapchainImageView,
+        image: Arc<dyn ImageViewAbstract>,
 
         vertex_buffer: &Subbuffer<[VertexType]>,
         normal_buffer: &Subbuffer<[Normal]>,
         index_buffer: &Subbuffer<[u32]>,
         uniforms: &Subbuffer<UniformBufferType>
-    ) -> Box<dyn GpuFuture>{
+    ) -> Result<Box<dyn GpuFuture>, Error> {
+        let mut frame = self.begin_frame(before_future, image)?;
+        frame.draw_vertices_and_normals(vertex_buffer, normal_buffer, index_buffer, uniforms)?;
+        frame.end()
+    }
 
-        let mut builder = AutoCommandBufferBuilder::primary(
-            &self.command_buffer_allocator,
-            self.queue.queue_family_index(),
-            CommandBufferUsage::OneTimeSubmit,
-        )
-        .unwrap();
+    /// draws instance_count copies of a mesh with no normals, reading per instance data from the instance buffer
+    pub fn draw_instanced_from_vertices<VertexType, InstanceType, UniformBufferType>(
+        &mut self,
+        before_future: Box<dyn GpuFuture>,
+        image: Arc<dyn ImageViewAbstract>,
 
-        let dimensions = image.image().dimensions().width_height();
-        // Resize intermediary image
-        if dimensions != self.intermediary.dimensions().width_height() {
-            self.intermediary = ImageView::new_default(
-                AttachmentImage::transient_multisampled(
-                    &self.allocator,
-                    dimensions,
-                    self.sample_count,
-                    image.image().format(),
-                )
-                .unwrap(),
-            )
-            .unwrap();
-        }
-        // Resize depth image
-        if dimensions != self.depth.dimensions().width_height() {
-            self.depth = ImageView::new_default(
-                AttachmentImage::transient_multisampled(
-                    &self.allocator,
-                    dimensions,
-                    self.sample_count,
-                    Format::D16_UNORM,
-                )
-                .unwrap(),
-            )
-            .unwrap();
+        vertex_buffer: &Subbuffer<[VertexType]>,
+        instance_buffer: &Subbuffer<[InstanceType]>,
+        instance_count: u32,
+        index_buffer: &Subbuffer<[u32]>,
+        uniforms: &Subbuffer<UniformBufferType>
+    ) -> Result<Box<dyn GpuFuture>, Error> {
+        let mut frame = self.begin_frame(before_future, image)?;
+        frame.draw_instanced_vertices(vertex_buffer, instance_buffer, instance_count, index_buffer, uniforms)?;
+        frame.end()
+    }
+
+    /// draws instance_count copies of a mesh with normals, reading per instance data from the instance buffer
+    pub fn draw_instanced_from_vertices_and_normals<VertexType, InstanceType, UniformBufferType>(
+        &mut self,
+        before_future: Box<dyn GpuFuture>,
+        image: Arc<dyn ImageViewAbstract>,
+
+        vertex_buffer: &Subbuffer<[VertexType]>,
+        normal_buffer: &Subbuffer<[Normal]>,
+        instance_buffer: &Subbuffer<[InstanceType]>,
+        instance_count: u32,
+        index_buffer: &Subbuffer<[u32]>,
+        uniforms: &Subbuffer<UniformBufferType>
+    ) -> Result<Box<dyn GpuFuture>, Error> {
+        let mut frame = self.begin_frame(before_future, image)?;
+        frame.draw_instanced_vertices_and_normals(vertex_buffer, normal_buffer, instance_buffer, instance_count, index_buffer, uniforms)?;
+        frame.end()
+    }
+
+    /// draws a single mesh with no normals, binding the textures as combined image samplers
+    /// 
+    /// The uniforms are bound at set 0, binding 0, and the textures at bindings 1, 2, 3... in the order given
+    pub fn draw_textured_from_vertices<VertexType, UniformBufferType>(
+        &mut self,
+        before_future: Box<dyn GpuFuture>,
+        image: Arc<dyn ImageViewAbstract>,
+
+        vertex_buffer: &Subbuffer<[VertexType]>,
+        index_buffer: &Subbuffer<[u32]>,
+        uniforms: &Subbuffer<UniformBufferType>,
+        textures: &[&Texture],
+    ) -> Result<Box<dyn GpuFuture>, Error> {
+        let mut frame = self.begin_frame(before_future, image)?;
+        frame.draw_textured_vertices(vertex_buffer, index_buffer, uniforms, textures)?;
+        frame.end()
+    }
+
+    /// draws a single mesh with normals, binding the textures as combined image samplers
+    /// 
+    /// The uniforms are bound at set 0, binding 0, and the textures at bindings 1, 2, 3... in the order given
+    pub fn draw_textured_from_vertices_and_normals<VertexType, UniformBufferType>(
+        &mut self,
+        before_future: Box<dyn GpuFuture>,
+        image: Arc<dyn ImageViewAbstract>,
+
+        vertex_buffer: &Subbuffer<[VertexType]>,
+        normal_buffer: &Subbuffer<[Normal]>,
+        index_buffer: &Subbuffer<[u32]>,
+        uniforms: &Subbuffer<UniformBufferType>,
+        textures: &[&Texture],
+    ) -> Result<Box<dyn GpuFuture>, Error> {
+        let mut frame = self.begin_frame(before_future, image)?;
+        frame.draw_textured_vertices_and_normals(vertex_buffer, normal_buffer, index_buffer, uniforms, textures)?;
+        frame.end()
+    }
+
+    /// in the order of the framebuffer's attachments, the depth is cleared even when the colour is kept
+    fn clear_values(&self) -> Vec<Option<ClearValue>> {
+        let depth = if self.depth_format.aspects().intersects(ImageAspects::STENCIL) {
+            ClearValue::DepthStencil((self.config.clear_depth, 0))
+        } else {
+            ClearValue::Depth(self.config.clear_depth)
+        };
+        if !self.config.clear {
+            return vec![None, Some(depth)];
         }
+        vec![Some(self.config.clear_colour.into()), Some(depth), None]
+    }
+    
+}
+
+
+
+/// A frame being recorded by a MultiSamplePipeline3D, created with begin_frame
+/// 
+/// Each draw binds its own descriptor set, so every mesh can have its own uniforms
+pub struct MultiSampleFrame {
+    builder: AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    before_future: Box<dyn GpuFuture>,
+    pipeline: Arc<GraphicsPipeline>,
+    queue: Arc<Queue>,
+    descriptor_set_allocator: Arc<StandardDescriptorSetAllocator>,
+}
+
+impl MultiSampleFrame {
+    /// draws a mesh with no normals, with the uniforms at set 0, binding 0
+    pub fn draw_vertices<VertexType, UniformBufferType>(
+        &mut self,
+        vertex_buffer: &Subbuffer<[VertexType]>,
+        index_buffer: &Subbuffer<[u32]>,
+        uniforms: &Subbuffer<UniformBufferType>
+    ) -> Result<(), Error> {
+        self.draw_with_descriptors(vertex_buffer.clone(), index_buffer, vec![WriteDescriptorSet::buffer(0, uniforms.clone())])
+    }
+
+    /// draws a mesh with normals, with the uniforms at set 0, binding 0
+    pub fn draw_vertices_and_normals<VertexType, UniformBufferType>(
+        &mut self,
+        vertex_buffer: &Subbuffer<[VertexType]>,
+        normal_buffer: &Subbuffer<[Normal]>,
+        index_buffer: &Subbuffer<[u32]>,
+        uniforms: &Subbuffer<UniformBufferType>
+    ) -> Result<(), Error> {
+        self.draw_with_descriptors((vertex_buffer.clone(), normal_buffer.clone()), index_buffer, vec![WriteDescriptorSet::buffer(0, uniforms.clone())])
+    }
 
-        let layout = self.pipeline.layout().set_layouts().get(0).unwrap();
-        let set = PersistentDescriptorSet::new(
-            &self.descriptor_set_allocator,
-            layout.clone(),
-            [WriteDescriptorSet::buffer(0, uniforms.clone())],
+    /// draws a mesh with no normals, with the uniforms at set 0, binding 0 and the textures at bindings 1, 2, 3...
+    pub fn draw_textured_vertices<VertexType, UniformBufferType>(
+        &mut self,
+        vertex_buffer: &Subbuffer<[VertexType]>,
+        index_buffer: &Subbuffer<[u32]>,
+        uniforms: &Subbuffer<UniformBufferType>,
+        textures: &[&Texture],
+    ) -> Result<(), Error> {
+        self.draw_with_descriptors(vertex_buffer.clone(), index_buffer, uniform_and_texture_writes(uniforms, textures))
+    }
+
+    /// draws a mesh with normals, with the uniforms at set 0, binding 0 and the textures at bindings 1, 2, 3...
+    pub fn draw_textured_vertices_and_normals<VertexType, UniformBufferType>(
+        &mut self,
+        vertex_buffer: &Subbuffer<[VertexType]>,
+        normal_buffer: &Subbuffer<[Normal]>,
+        index_buffer: &Subbuffer<[u32]>,
+        uniforms: &Subbuffer<UniformBufferType>,
+        textures: &[&Texture],
+    ) -> Result<(), Error> {
+        self.draw_with_descriptors((vertex_buffer.clone(), normal_buffer.clone()), index_buffer, uniform_and_texture_writes(uniforms, textures))
+    }
+
+    /// draws instance_count copies of a mesh with no normals, with the instance buffer bound after the vertex buffer
+    /// 
+    /// The count can be less than the length of the instance buffer, to only draw the instances in use
+    pub fn draw_instanced_vertices<VertexType, InstanceType, UniformBufferType>(
+        &mut self,
+        vertex_buffer: &Subbuffer<[VertexType]>,
+        instance_buffer: &Subbuffer<[InstanceType]>,
+        instance_count: u32,
+        index_buffer: &Subbuffer<[u32]>,
+        uniforms: &Subbuffer<UniformBufferType>
+    ) -> Result<(), Error> {
+        self.draw_internal(
+            (vertex_buffer.clone(), instance_buffer.clone()),
+            index_buffer,
+            instance_count,
+            vec![WriteDescriptorSet::buffer(0, uniforms.clone())],
         )
-        .unwrap();
+    }
 
-        let framebuffer = Framebuffer::new(self.render_pass.clone(),
-            FramebufferCreateInfo {
-                attachments: vec![self.intermediary.clone(), self.depth.clone(), image],
-            ..Default::default()
-        })
-        .unwrap();
+    /// draws instance_count copies of a mesh with normals, with the instance buffer bound after the normal buffer
+    pub fn draw_instanced_vertices_and_normals<VertexType, InstanceType, UniformBufferType>(
+        &mut self,
+        vertex_buffer: &Subbuffer<[VertexType]>,
+        normal_buffer: &Subbuffer<[Normal]>,
+        instance_buffer: &Subbuffer<[InstanceType]>,
+        instance_count: u32,
+        index_buffer: &Subbuffer<[u32]>,
+        uniforms: &Subbuffer<UniformBufferType>
+    ) -> Result<(), Error> {
+        self.draw_internal(
+            (vertex_buffer.clone(), normal_buffer.clone(), instance_buffer.clone()),
+            index_buffer,
+            instance_count,
+            vec![WriteDescriptorSet::buffer(0, uniforms.clone())],
+        )
+    }
 
-        // Begin render pipeline commands
-        builder
-            .begin_render_pass(
-                RenderPassBeginInfo {
-                    clear_values: vec![
-                        Some([0.0, 0.0, 0.0, 1.0].into()),
-                        Some(1f32.into()),
-                        Some([0.0, 0.0, 0.0, 1.0].into()),
-                    ],
-                    ..RenderPassBeginInfo::framebuffer(framebuffer)
-                },
-                SubpassContents::Inline,
-            )
-            .unwrap();
+    /// draws with any vertex buffers and set 0 descriptor writes
+    /// 
+    /// If no writes are given no descriptor set is bound, for shaders that only use push constants
+    pub fn draw_with_descriptors(
+        &mut self,
+        vertex_buffers: impl VertexBuffersCollection,
+        index_buffer: &Subbuffer<[u32]>,
+        descriptor_writes: Vec<WriteDescriptorSet>,
+    ) -> Result<(), Error> {
+        self.draw_internal(vertex_buffers, index_buffer, 1, descriptor_writes)
+    }
 
+    /// draws instance_count instances with any vertex and instance buffers and set 0 descriptor writes
+    pub fn draw_instanced_with_descriptors(
+        &mut self,
+        vertex_buffers: impl VertexBuffersCollection,
+        index_buffer: &Subbuffer<[u32]>,
+        instance_count: u32,
+        descriptor_writes: Vec<WriteDescriptorSet>,
+    ) -> Result<(), Error> {
+        self.draw_internal(vertex_buffers, index_buffer, instance_count, descriptor_writes)
+    }
 
-        builder
-            .bind_pipeline_graphics(self.pipeline.clone())
-            .bind_descriptor_sets(
+    fn draw_internal(
+        &mut self,
+        vertex_buffers: impl VertexBuffersCollection,
+        index_buffer: &Subbuffer<[u32]>,
+        instance_count: u32,
+        descriptor_writes: Vec<WriteDescriptorSet>,
+    ) -> Result<(), Error> {
+        if !descriptor_writes.is_empty() {
+            let layout = self.pipeline.layout().set_layouts().get(0).ok_or(Error::MissingDescriptorSetLayout(0))?;
+            let set = PersistentDescriptorSet::new(
+                &self.descriptor_set_allocator,
+                layout.clone(),
+                descriptor_writes,
+            )?;
+            self.builder.bind_descriptor_sets(
                 PipelineBindPoint::Graphics,
                 self.pipeline.layout().clone(),
                 0,
                 set,
-            )
-            .set_viewport(0, vec![Viewport {
-                origin: [0.0, 0.0],
-                dimensions: [dimensions[0] as f32, dimensions[1] as f32],
-                depth_range: 0.0..1.0,
-            }])
-            .bind_vertex_buffers(0, (vertex_buffer.clone(), normal_buffer.clone()))
+            );
+        }
+
+        self.builder
+            .bind_vertex_buffers(0, vertex_buffers)
             .bind_index_buffer(index_buffer.clone())
-            .draw_indexed(index_buffer.len() as u32, 1, 0, 0, 0)
-            .unwrap();
+            .draw_indexed(index_buffer.len() as u32, instance_count, 0, 0, 0)?;
+        Ok(())
+    }
 
+    /// sets the push constants used by the following draws, starting at offset 0
+    pub fn push_constants<PushConstants: BufferContents>(&mut self, push_constants: PushConstants) {
+        self.builder.push_constants(self.pipeline.layout().clone(), 0, push_constants);
+    }
 
-        builder.end_render_pass().unwrap();
-        let command_buffer = builder.build().unwrap();
-        let after_future = before_future.then_execute(self.queue.clone(), command_buffer).unwrap();
+    /// finishes the render pass and submits the frame after the future given to begin_frame
+    pub fn end(mut self) -> Result<Box<dyn GpuFuture>, Error> {
+        self.builder.end_render_pass()?;
+        let command_buffer = self.builder.build()?;
+        let after_future = self.before_future.then_execute(self.queue.clone(), command_buffer)?;
 
-        after_future.boxed()
+        Ok(after_future.boxed())
     }
-    
-}
\ No newline at end of file
+}
+
+
+fn uniform_and_texture_writes<UniformBufferType>(
+    uniforms: &Subbuffer<UniformBufferType>,
+    textures: &[&Texture],
+) -> Vec<WriteDescriptorSet> {
+    let mut writes = vec![WriteDescriptorSet::buffer(0, uniforms.clone())];
+    writes.extend(textures.iter().enumerate().map(|(i, texture)| texture.write(i as u32 + 1)));
+    writes
+}
@@ -0,0 +1,271 @@
+//! a hierarchy of transformed nodes, each of which can draw a mesh with a material
+use maths::Matrix4;
+use super::{Aabb, Error, Frustum, MultiSampleFrame, Transform};
+
+
+/// A handle to a node of a Scene, only valid for the scene that created it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    /// the position of the node in the scene, in the order nodes were added
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+
+/// One node of a Scene
+///
+/// The transform is relative to the parent, changing it marks the node so its world matrix, and those of its descendants, are recalculated on the next update
+#[derive(Clone, Debug)]
+pub struct SceneNode {
+    pub name: Option<String>,
+    /// index into the meshes of the scene
+    pub mesh: Option<usize>,
+    /// index into the materials of the scene
+    pub material: Option<usize>,
+    /// hidden nodes are skipped along with all of their descendants
+    pub visible: bool,
+    /// the box around the node's mesh in its own space, nodes without one are never culled
+    pub bounds: Option<Aabb>,
+    transform: Transform,
+    world: Matrix4,
+    dirty: bool,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+impl SceneNode {
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+        self.dirty = true;
+    }
+
+    /// the transform to edit in place, the node is marked as changed
+    pub fn transform_mut(&mut self) -> &mut Transform {
+        self.dirty = true;
+        &mut self.transform
+    }
+
+    /// the matrix from this node's space to world space, as of the last Scene::update_world_matrices
+    pub fn world_matrix(&self) -> Matrix4 {
+        self.world
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+
+/// A transform hierarchy, with the meshes and materials its nodes draw
+///
+/// Meshes and materials can be anything, such as buffers and BlinnPhongMaterials, many nodes can share one
+pub struct Scene<M, Mat = ()> {
+    nodes: Vec<SceneNode>,
+    roots: Vec<NodeId>,
+    pub meshes: Vec<M>,
+    pub materials: Vec<Mat>,
+}
+
+impl<M, Mat> Default for Scene<M, Mat> {
+    fn default() -> Self {
+        Scene {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+            meshes: Vec::new(),
+            materials: Vec::new(),
+        }
+    }
+}
+
+impl<M, Mat> Scene<M, Mat> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds a mesh, returning its index
+    pub fn add_mesh(&mut self, mesh: M) -> usize {
+        self.meshes.push(mesh);
+        self.meshes.len() - 1
+    }
+
+    /// adds a material, returning its index
+    pub fn add_material(&mut self, material: Mat) -> usize {
+        self.materials.push(material);
+        self.materials.len() - 1
+    }
+
+    /// adds an empty node under the parent, or at the top of the hierarchy
+    pub fn add_node(&mut self, parent: Option<NodeId>, transform: Transform) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(SceneNode {
+            name: None,
+            mesh: None,
+            material: None,
+            visible: true,
+            bounds: None,
+            transform,
+            world: transform.matrix(),
+            dirty: true,
+            parent,
+            children: Vec::new(),
+        });
+        match parent {
+            Some(parent) => self.nodes[parent.0].children.push(id),
+            None => self.roots.push(id),
+        }
+        id
+    }
+
+    /// adds a node that draws a mesh with a material
+    pub fn add_mesh_node(&mut self, parent: Option<NodeId>, transform: Transform, mesh: usize, material: Option<usize>) -> NodeId {
+        let id = self.add_node(parent, transform);
+        self.nodes[id.0].mesh = Some(mesh);
+        self.nodes[id.0].material = material;
+        id
+    }
+
+    /// sets the bounds used to cull a node, usually from Mesh::aabb
+    pub fn set_bounds(&mut self, id: NodeId, bounds: Option<Aabb>) {
+        self.nodes[id.0].bounds = bounds;
+    }
+
+    pub fn node(&self, id: NodeId) -> &SceneNode {
+        &self.nodes[id.0]
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> &mut SceneNode {
+        &mut self.nodes[id.0]
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &SceneNode)> {
+        self.nodes.iter().enumerate().map(|(index, node)| (NodeId(index), node))
+    }
+
+    /// the nodes without a parent
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    /// the first node with the given name
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes.iter().position(|node| node.name.as_deref() == Some(name)).map(NodeId)
+    }
+
+    /// moves a node, along with its descendants, under a new parent, keeping its local transform
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> Result<(), Error> {
+        // walking up from the new parent must not reach the node being moved
+        let mut ancestor = parent;
+        while let Some(current) = ancestor {
+            if current == id {
+                return Err(Error::SceneCycle);
+            }
+            ancestor = self.nodes[current.0].parent;
+        }
+
+        match self.nodes[id.0].parent {
+            Some(old_parent) => self.nodes[old_parent.0].children.retain(|child| *child != id),
+            None => self.roots.retain(|root| *root != id),
+        }
+        match parent {
+            Some(parent) => self.nodes[parent.0].children.push(id),
+            None => self.roots.push(id),
+        }
+        self.nodes[id.0].parent = parent;
+        self.nodes[id.0].dirty = true;
+        Ok(())
+    }
+
+    /// recalculates the world matrices of changed nodes and everything below them
+    pub fn update_world_matrices(&mut self) {
+        let identity = Transform::IDENTITY.matrix();
+        let mut stack: Vec<(NodeId, Matrix4, bool)> = self.roots.iter().rev().map(|root| (*root, identity, false)).collect();
+
+        while let Some((id, parent_world, parent_changed)) = stack.pop() {
+            let node = &mut self.nodes[id.0];
+            let changed = parent_changed || node.dirty;
+            if changed {
+                node.world = parent_world * node.transform.matrix();
+                node.dirty = false;
+            }
+            let world = node.world;
+            stack.extend(node.children.iter().rev().map(|child| (*child, world, changed)));
+        }
+    }
+
+    /// calls visit for every visible node with a mesh, in hierarchy order, with its mesh, material and world matrix
+    ///
+    /// World matrices are as of the last update_world_matrices
+    pub fn visit_visible<F>(&self, visit: F) -> Result<(), Error>
+    where
+        F: FnMut(NodeId, &M, Option<&Mat>, [[f32; 4]; 4]) -> Result<(), Error>,
+    {
+        self.visit(None, visit)
+    }
+
+    /// like visit_visible, but nodes whose bounds are entirely outside the frustum are skipped
+    pub fn visit_in_frustum<F>(&self, frustum: &Frustum, visit: F) -> Result<(), Error>
+    where
+        F: FnMut(NodeId, &M, Option<&Mat>, [[f32; 4]; 4]) -> Result<(), Error>,
+    {
+        self.visit(Some(frustum), visit)
+    }
+
+    /// updates the world matrices then draws every visible node into the frame
+    ///
+    /// draw_node is given the frame, the node's mesh and material and its world matrix, and records the draw, usually with MultiSampleFrame::draw_with_descriptors
+    pub fn draw<F>(&mut self, frame: &mut MultiSampleFrame, mut draw_node: F) -> Result<(), Error>
+    where
+        F: FnMut(&mut MultiSampleFrame, &M, Option<&Mat>, [[f32; 4]; 4]) -> Result<(), Error>,
+    {
+        self.update_world_matrices();
+        self.visit(None, |_, mesh, material, world| draw_node(frame, mesh, material, world))
+    }
+
+    /// like draw, but skips nodes whose bounds are entirely outside the frustum, such as one from Frustum::from_camera
+    pub fn draw_culled<F>(&mut self, frame: &mut MultiSampleFrame, frustum: &Frustum, mut draw_node: F) -> Result<(), Error>
+    where
+        F: FnMut(&mut MultiSampleFrame, &M, Option<&Mat>, [[f32; 4]; 4]) -> Result<(), Error>,
+    {
+        self.update_world_matrices();
+        self.visit(Some(frustum), |_, mesh, material, world| draw_node(frame, mesh, material, world))
+    }
+
+    fn visit<F>(&self, frustum: Option<&Frustum>, mut visit: F) -> Result<(), Error>
+    where
+        F: FnMut(NodeId, &M, Option<&Mat>, [[f32; 4]; 4]) -> Result<(), Error>,
+    {
+        let mut stack: Vec<NodeId> = self.roots.iter().rev().copied().collect();
+        while let Some(id) = stack.pop() {
+            let node = &self.nodes[id.0];
+            if !node.visible {
+                continue;
+            }
+            // children are still visited, as the bounds only cover the node's own mesh
+            stack.extend(node.children.iter().rev());
+
+            let culled = match (frustum, node.bounds) {
+                (Some(frustum), Some(bounds)) => !frustum.intersects_aabb(&bounds.transformed(&node.world)),
+                _ => false,
+            };
+            if culled {
+                continue;
+            }
+            if let Some(mesh) = node.mesh.and_then(|mesh| self.meshes.get(mesh)) {
+                let material = node.material.and_then(|material| self.materials.get(material));
+                visit(id, mesh, material, node.world.into())?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+//! shaders compiled from files at runtime, so they can be edited while the app runs
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+use vulkano::{
+    device::Device,
+    pipeline::ComputePipeline,
+    shader::ShaderModule,
+};
+use vulkano_util::context::VulkanoContext;
+use shaderc::{Compiler, CompileOptions, ShaderKind, SourceLanguage, TargetEnv, EnvVersion};
+use super::{Error, general_compute::create_compute_pipeline};
+
+
+/// A shader module loaded from a file, that can be recompiled when the file changes
+///
+/// The stage is taken from the extension:
+/// - GLSL - .vert, .frag, .comp, .geom, .tesc, .tese
+/// - HLSL - the stage extension followed by .hlsl, such as shader.frag.hlsl
+/// - SPIR-V - any file ending in .spv, which is loaded as it is
+///
+/// Only the file itself is watched, not anything it includes
+pub struct ShaderFile {
+    path: PathBuf,
+    device: Arc<Device>,
+    module: Arc<ShaderModule>,
+    modified: Option<SystemTime>,
+}
+
+impl ShaderFile {
+    /// loads and compiles the shader at the given path
+    pub fn load(device: &Arc<Device>, path: &str) -> Result<Self, Error> {
+        let path = PathBuf::from(path);
+        let modified = modified_time(&path);
+        let module = compile(device, &path)?;
+        Ok(ShaderFile {
+            path,
+            device: device.clone(),
+            module,
+            modified,
+        })
+    }
+
+    pub fn module(&self) -> &Arc<ShaderModule> {
+        &self.module
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// recompiles the shader if the file has changed since it was last compiled, returning true if the module was replaced
+    ///
+    /// If compiling fails the previous module is kept and the error returned, the file is not tried again until it next changes
+    pub fn poll(&mut self) -> Result<bool, Error> {
+        let modified = modified_time(&self.path);
+        // editors often replace the file when saving, so a file that is missing for a moment is not an error
+        if modified.is_none() || modified == self.modified {
+            return Ok(false);
+        }
+        self.modified = modified;
+
+        self.module = compile(&self.device, &self.path)?;
+        Ok(true)
+    }
+}
+
+
+/// A compute pipeline that rebuilds itself when its shader file changes
+pub struct ReloadableComputePipeline {
+    shader: ShaderFile,
+    entry_point: String,
+    pipeline: Arc<ComputePipeline>,
+}
+
+impl ReloadableComputePipeline {
+    pub fn new(context: &VulkanoContext, path: &str, entry_point: &str) -> Result<Self, Error> {
+        let shader = ShaderFile::load(context.device(), path)?;
+        let pipeline = create_compute_pipeline(context, shader.module(), entry_point)?;
+        Ok(ReloadableComputePipeline {
+            shader,
+            entry_point: entry_point.to_string(),
+            pipeline,
+        })
+    }
+
+    pub fn pipeline(&self) -> &Arc<ComputePipeline> {
+        &self.pipeline
+    }
+
+    /// rebuilds the pipeline if the shader file has changed, returning true if it was rebuilt
+    ///
+    /// On an error the previous pipeline is kept, the context should be the one the pipeline was created with
+    pub fn poll(&mut self, context: &VulkanoContext) -> Result<bool, Error> {
+        if !self.shader.poll()? {
+            return Ok(false);
+        }
+        self.pipeline = create_compute_pipeline(context, self.shader.module(), &self.entry_point)?;
+        Ok(true)
+    }
+}
+
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn compile(device: &Arc<Device>, path: &Path) -> Result<Arc<ShaderModule>, Error> {
+    let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or("");
+
+    if extension == "spv" {
+        let bytes = std::fs::read(path)?;
+        // safety: the file is trusted to hold valid spir-v, vulkano only reflects over it
+        return Ok(unsafe {ShaderModule::from_bytes(device.clone(), &bytes)?});
+    }
+
+    let (stage_extension, language) = if extension == "hlsl" {
+        let stem = Path::new(path.file_stem().unwrap_or_default());
+        (stem.extension().and_then(|extension| extension.to_str()).unwrap_or("").to_string(), SourceLanguage::HLSL)
+    } else {
+        (extension.to_string(), SourceLanguage::GLSL)
+    };
+
+    let kind = match stage_extension.as_str() {
+        "vert" => ShaderKind::Vertex,
+        "frag" => ShaderKind::Fragment,
+        "comp" => ShaderKind::Compute,
+        "geom" => ShaderKind::Geometry,
+        "tesc" => ShaderKind::TessControl,
+        "tese" => ShaderKind::TessEvaluation,
+        _ => return Err(Error::ShaderCompile(format!("could not tell the shader stage of {} from its extension", path.display()))),
+    };
+
+    let source = std::fs::read_to_string(path)?;
+
+    let compiler = Compiler::new().ok_or_else(|| Error::ShaderCompile("could not create the shader compiler".to_string()))?;
+    let mut options = CompileOptions::new().ok_or_else(|| Error::ShaderCompile("could not create the shader compiler options".to_string()))?;
+    options.set_source_language(language);
+    options.set_target_env(TargetEnv::Vulkan, EnvVersion::Vulkan1_0 as u32);
+
+    let artifact = compiler
+        .compile_into_spirv(&source, kind, &path.to_string_lossy(), "main", Some(&options))
+        .map_err(|err| Error::ShaderCompile(err.to_string()))?;
+
+    // safety: shaderc only produces valid spir-v
+    Ok(unsafe {ShaderModule::from_words(device.clone(), artifact.as_binary())?})
+}
@@ -0,0 +1,111 @@
+#version 450
+
+// compiled twice by lighting.rs, once as is and once with SHADOWS defined
+
+const uint MAX_LIGHTS = 16u;
+
+struct Light {
+    vec4 position;
+    vec4 direction;
+    vec4 colour;
+    vec4 attenuation;
+};
+
+layout(location = 0) in vec3 v_position;
+layout(location = 1) in vec3 v_normal;
+
+layout(location = 0) out vec4 f_colour;
+
+layout(set = 0, binding = 1) uniform Lights {
+    vec4 camera_position;
+    vec4 ambient;
+    uvec4 count;
+    Light lights[MAX_LIGHTS];
+} lights;
+
+layout(set = 0, binding = 2) uniform Material {
+    vec4 colour;
+    vec4 specular;
+} material;
+
+#ifdef SHADOWS
+const uint MAX_CASCADES = 4u;
+
+layout(set = 0, binding = 3) uniform Shadows {
+    mat4 light_matrices[MAX_CASCADES];
+    vec4 splits;
+    vec4 camera_forward;
+    uvec4 info;
+} shadows;
+
+layout(set = 0, binding = 4) uniform sampler2DArrayShadow shadow_map;
+
+// 1 where the light reaches the fragment, 0 where it is in shadow, filtered over a 3x3 area
+float shadow_factor() {
+    float depth = dot(v_position - lights.camera_position.xyz, shadows.camera_forward.xyz);
+    uint cascade = 0u;
+    for (uint i = 0u; i + 1u < shadows.info.x; i++) {
+        if (depth > shadows.splits[i]) {
+            cascade = i + 1u;
+        }
+    }
+
+    vec4 shadow_position = shadows.light_matrices[cascade] * vec4(v_position, 1.0);
+    vec3 coord = shadow_position.xyz / shadow_position.w;
+    if (coord.z > 1.0) {
+        return 1.0;
+    }
+    vec2 uv = coord.xy * 0.5 + 0.5;
+
+    vec2 texel = 1.0 / vec2(textureSize(shadow_map, 0).xy);
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            lit += texture(shadow_map, vec4(uv + vec2(x, y) * texel, float(cascade), coord.z));
+        }
+    }
+    return lit / 9.0;
+}
+#endif
+
+void main() {
+    vec3 normal = normalize(v_normal);
+    vec3 view_dir = normalize(lights.camera_position.xyz - v_position);
+    vec3 colour = lights.ambient.rgb * material.colour.rgb;
+
+    for (uint i = 0u; i < min(lights.count.x, MAX_LIGHTS); i++) {
+        Light light = lights.lights[i];
+        uint kind = uint(light.position.w);
+        float strength = light.colour.w;
+        vec3 light_dir;
+
+        if (kind == 0u) {
+            light_dir = normalize(-light.direction.xyz);
+        } else {
+            vec3 to_light = light.position.xyz - v_position;
+            float distance = length(to_light);
+            light_dir = to_light / distance;
+            strength /= light.attenuation.x + light.attenuation.y * distance + light.attenuation.z * distance * distance;
+
+            if (kind == 2u) {
+                float theta = dot(light_dir, normalize(-light.direction.xyz));
+                float falloff = max(light.attenuation.w - light.direction.w, 0.0001);
+                strength *= clamp((theta - light.direction.w) / falloff, 0.0, 1.0);
+            }
+        }
+
+#ifdef SHADOWS
+        if (i == shadows.info.y) {
+            strength *= shadow_factor();
+        }
+#endif
+
+        float diffuse = max(dot(normal, light_dir), 0.0);
+        vec3 halfway = normalize(light_dir + view_dir);
+        float specular = diffuse > 0.0 ? pow(max(dot(normal, halfway), 0.0), material.specular.w) : 0.0;
+
+        colour += strength * light.colour.rgb * (diffuse * material.colour.rgb + specular * material.specular.rgb);
+    }
+
+    f_colour = vec4(colour, material.colour.a);
+}
@@ -0,0 +1,145 @@
+#version 450
+
+// metallic-roughness pbr with a cook-torrance specular term, lit by the same lights as blinn_phong
+
+const uint MAX_LIGHTS = 16u;
+const float PI = 3.14159265359;
+
+struct Light {
+    vec4 position;
+    vec4 direction;
+    vec4 colour;
+    vec4 attenuation;
+};
+
+layout(location = 0) in vec3 v_position;
+layout(location = 1) in vec2 v_tex_coord;
+layout(location = 2) in vec3 v_normal;
+
+layout(location = 0) out vec4 f_colour;
+
+layout(set = 0, binding = 1) uniform Lights {
+    vec4 camera_position;
+    vec4 ambient;
+    uvec4 count;
+    Light lights[MAX_LIGHTS];
+} lights;
+
+layout(set = 0, binding = 2) uniform Material {
+    vec4 base_colour;
+    vec4 emissive;
+    vec4 factors;
+    uvec4 flags;
+} material;
+
+layout(set = 0, binding = 3) uniform sampler2D base_colour_texture;
+layout(set = 0, binding = 4) uniform sampler2D metallic_roughness_texture;
+layout(set = 0, binding = 5) uniform sampler2D normal_texture;
+layout(set = 0, binding = 6) uniform sampler2D occlusion_texture;
+layout(set = 0, binding = 7) uniform sampler2D emissive_texture;
+
+// builds the tangent frame from screen space derivatives, so meshes do not need tangents
+vec3 perturbed_normal(vec3 normal) {
+    vec3 map = texture(normal_texture, v_tex_coord).xyz * 2.0 - 1.0;
+    map.xy *= material.emissive.w;
+
+    vec3 dp1 = dFdx(v_position);
+    vec3 dp2 = dFdy(v_position);
+    vec2 duv1 = dFdx(v_tex_coord);
+    vec2 duv2 = dFdy(v_tex_coord);
+
+    vec3 dp2perp = cross(dp2, normal);
+    vec3 dp1perp = cross(normal, dp1);
+    vec3 tangent = dp2perp * duv1.x + dp1perp * duv2.x;
+    vec3 bitangent = dp2perp * duv1.y + dp1perp * duv2.y;
+    float inverse_max = inversesqrt(max(dot(tangent, tangent), dot(bitangent, bitangent)));
+    if (isinf(inverse_max) || isnan(inverse_max)) {
+        return normal;
+    }
+
+    mat3 tbn = mat3(tangent * inverse_max, bitangent * inverse_max, normal);
+    return normalize(tbn * map);
+}
+
+float distribution_ggx(float n_dot_h, float roughness) {
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float denominator = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * denominator * denominator);
+}
+
+float geometry_smith(float n_dot_v, float n_dot_l, float roughness) {
+    float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    float view = n_dot_v / (n_dot_v * (1.0 - k) + k);
+    float light = n_dot_l / (n_dot_l * (1.0 - k) + k);
+    return view * light;
+}
+
+vec3 fresnel_schlick(float cos_theta, vec3 f0) {
+    return f0 + (1.0 - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+void main() {
+    vec4 base_colour = material.base_colour * texture(base_colour_texture, v_tex_coord);
+    // flags.x is the alpha mode, 1 is mask with the cutoff in factors.w
+    if (material.flags.x == 1u && base_colour.a < material.factors.w) {
+        discard;
+    }
+
+    vec4 metallic_roughness = texture(metallic_roughness_texture, v_tex_coord);
+    float metallic = clamp(material.factors.x * metallic_roughness.b, 0.0, 1.0);
+    float roughness = clamp(material.factors.y * metallic_roughness.g, 0.04, 1.0);
+    float occlusion = mix(1.0, texture(occlusion_texture, v_tex_coord).r, material.factors.z);
+    vec3 emissive = material.emissive.rgb * texture(emissive_texture, v_tex_coord).rgb;
+
+    vec3 normal = normalize(v_normal);
+    if (!gl_FrontFacing) {
+        normal = -normal;
+    }
+    normal = perturbed_normal(normal);
+
+    vec3 view_dir = normalize(lights.camera_position.xyz - v_position);
+    float n_dot_v = max(dot(normal, view_dir), 0.0001);
+    vec3 f0 = mix(vec3(0.04), base_colour.rgb, metallic);
+
+    vec3 colour = vec3(0.0);
+    for (uint i = 0u; i < min(lights.count.x, MAX_LIGHTS); i++) {
+        Light light = lights.lights[i];
+        uint kind = uint(light.position.w);
+        float strength = light.colour.w;
+        vec3 light_dir;
+
+        if (kind == 0u) {
+            light_dir = normalize(-light.direction.xyz);
+        } else {
+            vec3 to_light = light.position.xyz - v_position;
+            float distance = length(to_light);
+            light_dir = to_light / distance;
+            strength /= light.attenuation.x + light.attenuation.y * distance + light.attenuation.z * distance * distance;
+
+            if (kind == 2u) {
+                float theta = dot(light_dir, normalize(-light.direction.xyz));
+                float falloff = max(light.attenuation.w - light.direction.w, 0.0001);
+                strength *= clamp((theta - light.direction.w) / falloff, 0.0, 1.0);
+            }
+        }
+
+        float n_dot_l = max(dot(normal, light_dir), 0.0);
+        if (n_dot_l <= 0.0) {
+            continue;
+        }
+        vec3 halfway = normalize(light_dir + view_dir);
+        float n_dot_h = max(dot(normal, halfway), 0.0);
+
+        vec3 fresnel = fresnel_schlick(max(dot(halfway, view_dir), 0.0), f0);
+        vec3 specular = distribution_ggx(n_dot_h, roughness) * geometry_smith(n_dot_v, n_dot_l, roughness) * fresnel / (4.0 * n_dot_v * n_dot_l + 0.0001);
+        vec3 diffuse = (1.0 - fresnel) * (1.0 - metallic) * base_colour.rgb / PI;
+
+        colour += (diffuse + specular) * light.colour.rgb * strength * n_dot_l;
+    }
+
+    colour += lights.ambient.rgb * base_colour.rgb * occlusion;
+    colour += emissive;
+
+    f_colour = vec4(colour, base_colour.a);
+}
@@ -0,0 +1,23 @@
+#version 450
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec2 tex_coord;
+layout(location = 2) in vec3 normal;
+
+layout(location = 0) out vec3 v_position;
+layout(location = 1) out vec2 v_tex_coord;
+layout(location = 2) out vec3 v_normal;
+
+layout(set = 0, binding = 0) uniform Data {
+    mat4 world;
+    mat4 view;
+    mat4 proj;
+} uniforms;
+
+void main() {
+    vec4 world_position = uniforms.world * vec4(position, 1.0);
+    v_position = world_position.xyz;
+    v_tex_coord = tex_coord;
+    v_normal = mat3(transpose(inverse(uniforms.world))) * normal;
+    gl_Position = uniforms.proj * uniforms.view * world_position;
+}
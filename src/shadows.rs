@@ -0,0 +1,488 @@
+//! shadow maps for directional lights, with optional cascades split along the camera's view
+use std::sync::Arc;
+use bytemuck::{Pod, Zeroable};
+use vulkano::{
+    buffer::{allocator::SubbufferAllocator, Subbuffer},
+    command_buffer::{allocator::StandardCommandBufferAllocator, AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer, RenderPassBeginInfo, SubpassContents},
+    descriptor_set::WriteDescriptorSet,
+    device::Queue,
+    format::{Format, FormatFeatures},
+    image::{view::{ImageView, ImageViewCreateInfo, ImageViewType}, ImageAspects, ImageCreateFlags, ImageDimensions, ImageSubresourceRange, ImageUsage, StorageImage},
+    pipeline::{graphics::{depth_stencil::CompareOp, rasterization::DepthBias, vertex_input::VertexBufferDescription, viewport::Viewport}, GraphicsPipeline, Pipeline},
+    render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass, Subpass},
+    sampler::{BorderColor, Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+    single_pass_renderpass,
+    sync::GpuFuture,
+};
+use vulkano_util::context::VulkanoContext;
+use maths::{Vector3, Matrix4};
+use super::{Camera, Error, GraphicsPipelineBuilder};
+
+
+/// the most cascades a shadow map can be split into
+pub const MAX_CASCADES: usize = 4;
+
+
+#[derive(Clone, Debug)]
+pub struct ShadowMapConfig {
+    /// the width and height of each cascade's depth image
+    pub resolution: u32,
+    /// a depth only format, such as D16_UNORM or D32_SFLOAT
+    pub format: Format,
+    /// pushes the depth drawn into the map away from the light, to stop surfaces shadowing themselves
+    pub depth_bias: DepthBias,
+    /// how many slices the camera's view is split into, each with its own map, up to MAX_CASCADES
+    pub cascades: u32,
+    /// how the slices are spread, 0 splits the view evenly and 1 splits it logarithmically so near slices are smaller
+    pub split_lambda: f32,
+    /// how far from the camera shadows are drawn, no further than the camera's far plane
+    pub max_distance: f32,
+    /// how far behind each slice towards the light objects still cast shadows into it
+    pub caster_distance: f32,
+}
+
+impl Default for ShadowMapConfig {
+    fn default() -> Self {
+        ShadowMapConfig {
+            resolution: 2048,
+            format: Format::D32_SFLOAT,
+            depth_bias: DepthBias {
+                constant_factor: 1.25,
+                clamp: 0.0,
+                slope_factor: 1.75,
+            },
+            cascades: 1,
+            split_lambda: 0.5,
+            max_distance: 100.0,
+            caster_distance: 50.0,
+        }
+    }
+}
+
+
+/// Where each cascade of a shadow map sits, fitted around slices of a camera's view
+///
+/// ShadowMap::update fits these itself, fit only needs the config so it can be used without a device
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ShadowCascades {
+    pub light_matrices: [[[f32; 4]; 4]; MAX_CASCADES],
+    /// the far distance of each cascade from the camera
+    pub splits: [f32; 4],
+    pub camera_forward: [f32; 3],
+    /// how many of the cascades are used
+    pub count: u32,
+}
+
+impl ShadowCascades {
+    /// fits each cascade around its slice of the camera's view, for a light shining in the given direction
+    pub fn fit(config: &ShadowMapConfig, camera: &Camera, aspect_ratio: f32, light_direction: [f32; 3]) -> Self {
+        let forward = camera.direction().normalised();
+        let right = camera.up().cross(forward).normalised();
+        let up = forward.cross(right);
+
+        let light_forward = Vector3::from(light_direction).normalised();
+        let light_up = if light_forward.y.abs() > 0.99 {Vector3::new(0.0, 0.0, 1.0)} else {Vector3::Y};
+        let light_right = light_forward.cross(light_up).normalised();
+        let light_up = light_right.cross(light_forward);
+
+        let cascades = config.cascades.clamp(1, MAX_CASCADES as u32) as usize;
+        let mut light_matrices = [[[0.0; 4]; 4]; MAX_CASCADES];
+        let mut splits = [0.0; 4];
+        // the logarithmic splits need a near plane in front of the camera, which an orthographic one may not have
+        let near = camera.projection.near().max(0.001);
+        let far = config.max_distance.min(camera.projection.far());
+
+        let mut near_split = near;
+        for cascade in 0..cascades {
+            // the practical split scheme, blending logarithmic and even splits
+            let fraction = (cascade + 1) as f32 / cascades as f32;
+            let logarithmic = near * (far / near).powf(fraction);
+            let even = near + (far - near) * fraction;
+            let far_split = config.split_lambda * logarithmic + (1.0 - config.split_lambda) * even;
+
+            // a sphere around the slice keeps the map the same size as the camera turns, which stops shadows shimmering
+            let mut corners = Vec::with_capacity(8);
+            for distance in [near_split, far_split] {
+                let half_height = camera.projection.half_height_at(distance);
+                let half_width = half_height * aspect_ratio;
+                let centre = camera.position + forward * distance;
+                for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+                    corners.push(centre + right * (x * half_width) + up * (y * half_height));
+                }
+            }
+            let centre = corners.iter().fold(Vector3::ZERO, |sum, corner| sum + *corner) * (1.0 / 8.0);
+            let radius = corners.iter().map(|corner| (*corner - centre).dot(*corner - centre).sqrt()).fold(0.0, f32::max);
+
+            // snapping to whole texels stops the shadow edges crawling as the camera moves
+            let texel = 2.0 * radius / config.resolution as f32;
+            let centre_x = (light_right.dot(centre) / texel).floor() * texel;
+            let centre_y = (light_up.dot(centre) / texel).floor() * texel;
+            let centre_depth = light_forward.dot(centre);
+
+            light_matrices[cascade] = orthographic_light_matrix(
+                [light_right, light_up, light_forward],
+                [centre_x - radius, centre_x + radius],
+                [centre_y - radius, centre_y + radius],
+                [centre_depth - radius - config.caster_distance, centre_depth + radius],
+            );
+            splits[cascade] = far_split;
+            near_split = far_split;
+        }
+
+        ShadowCascades {
+            light_matrices,
+            splits,
+            camera_forward: forward.into(),
+            count: cascades as u32,
+        }
+    }
+
+    /// the light matrix of each used cascade
+    pub fn light_matrices(&self) -> &[[[f32; 4]; 4]] {
+        &self.light_matrices[0..self.count as usize]
+    }
+}
+
+
+/// The shadow uniform read by blinn_phong::fs_shadowed at set 0, binding 3
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+pub struct ShadowsUniform {
+    pub light_matrices: [[[f32; 4]; 4]; MAX_CASCADES],
+    /// the far distance of each cascade from the camera
+    pub splits: [f32; 4],
+    pub camera_forward: [f32; 4],
+    /// x is the number of cascades, y is the index of the shadowed light in the LightSet
+    pub info: [u32; 4],
+}
+
+
+#[repr(C)]
+#[derive(Clone, Copy, Zeroable, Pod)]
+struct ShadowPushConstants {
+    light_matrix: [[f32; 4]; 4],
+    world: [[f32; 4]; 4],
+}
+
+
+mod depth_vs {
+    vulkano_shaders::shader!{
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+
+            layout(push_constant) uniform PushConstants {
+                mat4 light_matrix;
+                mat4 world;
+            } push_constants;
+
+            void main() {
+                gl_Position = push_constants.light_matrix * push_constants.world * vec4(position, 1.0);
+            }
+        ",
+    }
+}
+
+mod depth_fs {
+    vulkano_shaders::shader!{
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            void main() {}
+        ",
+    }
+}
+
+
+/// A depth image drawn from a directional light's point of view, one layer per cascade
+pub struct ShadowMap {
+    queue: Arc<Queue>,
+    command_buffer_allocator: Arc<StandardCommandBufferAllocator>,
+    pipeline: Arc<GraphicsPipeline>,
+    framebuffers: Vec<Arc<Framebuffer>>,
+    view: Arc<ImageView<StorageImage>>,
+    sampler: Arc<Sampler>,
+    config: ShadowMapConfig,
+    cascades: ShadowCascades,
+}
+
+impl ShadowMap {
+    /// creates the shadow map and the pipeline that draws into it
+    ///
+    /// The vertex def only needs a position member, so any vertex type with one works, such as vertex_defs::position() or coloured()
+    pub fn new(
+        context: &VulkanoContext,
+        command_buffer_allocator: &Arc<StandardCommandBufferAllocator>,
+        config: ShadowMapConfig,
+        vertex_def: &[VertexBufferDescription],
+    ) -> Result<Self, Error> {
+        let cascades = config.cascades.clamp(1, MAX_CASCADES as u32);
+        let config = ShadowMapConfig {cascades, ..config};
+
+        let image = StorageImage::with_usage(
+            context.memory_allocator(),
+            ImageDimensions::Dim2d {
+                width: config.resolution,
+                height: config.resolution,
+                array_layers: cascades,
+            },
+            config.format,
+            ImageUsage::DEPTH_STENCIL_ATTACHMENT | ImageUsage::SAMPLED,
+            ImageCreateFlags::empty(),
+            [context.graphics_queue().queue_family_index()],
+        )?;
+
+        let render_pass = Self::create_render_pass(context, config.format)?;
+
+        let mut framebuffers = Vec::new();
+        for layer in 0..cascades {
+            let layer_view = ImageView::new(image.clone(), ImageViewCreateInfo {
+                view_type: ImageViewType::Dim2d,
+                subresource_range: ImageSubresourceRange {
+                    aspects: ImageAspects::DEPTH,
+                    mip_levels: 0..1,
+                    array_layers: layer..layer + 1,
+                },
+                ..ImageViewCreateInfo::from_image(&image)
+            })?;
+            framebuffers.push(Framebuffer::new(render_pass.clone(), FramebufferCreateInfo {
+                attachments: vec![layer_view],
+                ..Default::default()
+            })?);
+        }
+
+        // always an array view, so one and many cascades are read the same way
+        let view = ImageView::new(image.clone(), ImageViewCreateInfo {
+            view_type: ImageViewType::Dim2dArray,
+            subresource_range: ImageSubresourceRange {
+                aspects: ImageAspects::DEPTH,
+                mip_levels: 0..1,
+                array_layers: 0..cascades,
+            },
+            ..ImageViewCreateInfo::from_image(&image)
+        })?;
+
+        // linear filtering with a compare op gives hardware pcf on top of the shader's own filtering, where the format allows it
+        let filter = Self::shadow_filter(context, config.format);
+        let sampler = Sampler::new(context.device().clone(), SamplerCreateInfo {
+            mag_filter: filter,
+            min_filter: filter,
+            address_mode: [SamplerAddressMode::ClampToBorder; 3],
+            border_color: BorderColor::FloatOpaqueWhite,
+            compare: Some(CompareOp::LessOrEqual),
+            ..Default::default()
+        })?;
+
+        let vs = depth_vs::load(context.device().clone())?;
+        let fs = depth_fs::load(context.device().clone())?;
+        let pipeline = GraphicsPipelineBuilder::new(&vs, &fs, vertex_def)
+            .depth_bias(Some(config.depth_bias))
+            .build(context.device(), Subpass::from(render_pass, 0).unwrap())?;
+
+        Ok(ShadowMap {
+            queue: context.graphics_queue().clone(),
+            command_buffer_allocator: command_buffer_allocator.clone(),
+            pipeline,
+            framebuffers,
+            view,
+            sampler,
+            config,
+            cascades: ShadowCascades::default(),
+        })
+    }
+
+    fn shadow_filter(context: &VulkanoContext, format: Format) -> Filter {
+        let linear = context.device().physical_device().format_properties(format)
+            .map(|properties| properties.optimal_tiling_features.intersects(FormatFeatures::SAMPLED_IMAGE_FILTER_LINEAR))
+            .unwrap_or(false);
+        if linear {Filter::Linear} else {Filter::Nearest}
+    }
+
+    fn create_render_pass(
+        context: &VulkanoContext,
+        format: Format,
+    ) -> Result<Arc<RenderPass>, Error> {
+        Ok(single_pass_renderpass!(
+            context.device().clone(),
+            attachments: {
+                depth: {
+                    load: Clear,
+                    store: Store,
+                    format: format,
+                    samples: 1,
+                }
+            },
+            pass: {
+                color: [],
+                depth_stencil: {depth},
+            }
+        )?)
+    }
+
+    pub fn config(&self) -> &ShadowMapConfig {
+        &self.config
+    }
+
+    /// the light matrix of each cascade, transforming world positions into the map
+    pub fn light_matrices(&self) -> &[[[f32; 4]; 4]] {
+        self.cascades.light_matrices()
+    }
+
+    /// the cascades as last fitted by update
+    pub fn cascades(&self) -> &ShadowCascades {
+        &self.cascades
+    }
+
+    /// fits each cascade around its slice of the camera's view, for a light shining in the given direction
+    ///
+    /// Must be called whenever the camera or light moves, before render
+    pub fn update(&mut self, camera: &Camera, aspect_ratio: f32, light_direction: [f32; 3]) {
+        self.cascades = ShadowCascades::fit(&self.config, camera, aspect_ratio, light_direction);
+    }
+
+    /// the uniform for blinn_phong::fs_shadowed, the light index is the position of the shadowed light in the LightSet
+    pub fn uniform(&self, light_index: u32) -> ShadowsUniform {
+        let forward = self.cascades.camera_forward;
+        ShadowsUniform {
+            light_matrices: self.cascades.light_matrices,
+            splits: self.cascades.splits,
+            camera_forward: [forward[0], forward[1], forward[2], 0.0],
+            info: [self.cascades.count, light_index, 0, 0],
+        }
+    }
+
+    /// allocates a uniform buffer holding the shadow data, to be made once per frame
+    pub fn create_buffer(
+        &self,
+        allocator: &SubbufferAllocator,
+        light_index: u32,
+    ) -> Result<Subbuffer<ShadowsUniform>, Error> {
+        let buffer = allocator.allocate_sized()?;
+        *buffer.write()? = self.uniform(light_index);
+        Ok(buffer)
+    }
+
+    /// the view of every cascade as a 2d array
+    pub fn view(&self) -> &Arc<ImageView<StorageImage>> {
+        &self.view
+    }
+
+    /// a comparison sampler, for a sampler2DArrayShadow in glsl
+    ///
+    /// It filters linearly if the device can for the map's format, otherwise it falls back to nearest
+    pub fn sampler(&self) -> &Arc<Sampler> {
+        &self.sampler
+    }
+
+    /// returns the descriptor write binding the map and its sampler at the given binding
+    pub fn write(&self, binding: u32) -> WriteDescriptorSet {
+        WriteDescriptorSet::image_view_sampler(binding, self.view.clone(), self.sampler.clone())
+    }
+
+    /// draws the shadow casters into every cascade, the draw function is called once per cascade
+    pub fn render<F>(
+        &self,
+        before_future: Box<dyn GpuFuture>,
+        mut draw: F,
+    ) -> Result<Box<dyn GpuFuture>, Error>
+    where F: FnMut(&mut ShadowPass) -> Result<(), Error> {
+        let mut builder = AutoCommandBufferBuilder::primary(
+            &self.command_buffer_allocator,
+            self.queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+
+        let resolution = self.config.resolution as f32;
+        for (cascade, framebuffer) in self.framebuffers.iter().enumerate() {
+            builder.begin_render_pass(
+                RenderPassBeginInfo {
+                    clear_values: vec![Some(1f32.into())],
+                    ..RenderPassBeginInfo::framebuffer(framebuffer.clone())
+                },
+                SubpassContents::Inline,
+            )?;
+            builder
+                .bind_pipeline_graphics(self.pipeline.clone())
+                .set_viewport(0, vec![Viewport {
+                    origin: [0.0, 0.0],
+                    dimensions: [resolution, resolution],
+                    depth_range: 0.0..1.0,
+                }]);
+
+            draw(&mut ShadowPass {
+                builder: &mut builder,
+                pipeline: &self.pipeline,
+                light_matrix: self.light_matrices[cascade],
+                cascade,
+            })?;
+
+            builder.end_render_pass()?;
+        }
+
+        let command_buffer = builder.build()?;
+        Ok(before_future.then_execute(self.queue.clone(), command_buffer)?.boxed())
+    }
+}
+
+
+/// One cascade being drawn by ShadowMap::render
+pub struct ShadowPass<'a> {
+    builder: &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    pipeline: &'a Arc<GraphicsPipeline>,
+    light_matrix: [[f32; 4]; 4],
+    cascade: usize,
+}
+
+impl<'a> ShadowPass<'a> {
+    /// the index of the cascade being drawn
+    pub fn cascade(&self) -> usize {
+        self.cascade
+    }
+
+    /// draws a mesh that casts shadows, placed in the world by the given matrix
+    pub fn draw<VertexType>(
+        &mut self,
+        vertex_buffer: &Subbuffer<[VertexType]>,
+        index_buffer: &Subbuffer<[u32]>,
+        world: [[f32; 4]; 4],
+    ) -> Result<(), Error> {
+        self.builder
+            .push_constants(self.pipeline.layout().clone(), 0, ShadowPushConstants {
+                light_matrix: self.light_matrix,
+                world,
+            })
+            .bind_vertex_buffers(0, vertex_buffer.clone())
+            .bind_index_buffer(index_buffer.clone())
+            .draw_indexed(index_buffer.len() as u32, 1, 0, 0, 0)?;
+        Ok(())
+    }
+}
+
+
+/// an orthographic projection looking along the light's forward axis, as columns
+///
+/// The x and y bounds map to -1..1 and the depth bounds to 0..1
+fn orthographic_light_matrix(
+    axes: [Vector3; 3],
+    x_bounds: [f32; 2],
+    y_bounds: [f32; 2],
+    depth_bounds: [f32; 2],
+) -> [[f32; 4]; 4] {
+    let [right, up, forward] = axes;
+    let right = right * (2.0 / (x_bounds[1] - x_bounds[0]));
+    let up = up * (2.0 / (y_bounds[1] - y_bounds[0]));
+    let forward = forward * (1.0 / (depth_bounds[1] - depth_bounds[0]));
+
+    Matrix4::new(
+        right.x, up.x, forward.x, 0.0,
+        right.y, up.y, forward.y, 0.0,
+        right.z, up.z, forward.z, 0.0,
+        -(x_bounds[1] + x_bounds[0]) / (x_bounds[1] - x_bounds[0]),
+        -(y_bounds[1] + y_bounds[0]) / (y_bounds[1] - y_bounds[0]),
+        -depth_bounds[0] / (depth_bounds[1] - depth_bounds[0]),
+        1.0,
+    ).into()
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+use vulkano::{
+    command_buffer::{allocator::StandardCommandBufferAllocator, AutoCommandBufferBuilder, CommandBufferUsage},
+    descriptor_set::WriteDescriptorSet,
+    format::Format,
+    image::{view::ImageView, ImmutableImage, ImageDimensions, MipmapsCount},
+    sampler::{Sampler, SamplerCreateInfo},
+    sync::{self, GpuFuture},
+};
+use vulkano_util::context::VulkanoContext;
+use super::Error;
+
+
+/// An image on the gpu with a full mip chain and a sampler, ready to be bound as a combined image sampler
+#[derive(Clone)]
+pub struct Texture {
+    view: Arc<ImageView<ImmutableImage>>,
+    sampler: Arc<Sampler>,
+}
+
+impl Texture {
+    /// loads a png or jpeg image from disk
+    ///
+    /// Colour textures should be srgb, textures holding data such as normal maps should not be
+    pub fn load(
+        context: &VulkanoContext,
+        command_buffer_allocator: &Arc<StandardCommandBufferAllocator>,
+        path: &str,
+        srgb: bool,
+    ) -> Result<Self, Error> {
+        let image = image::open(path)?.to_rgba8();
+        let (width, height) = image.dimensions();
+        Self::from_rgba(context, command_buffer_allocator, image.into_raw(), [width, height], srgb)
+    }
+
+    /// creates a texture from 8 bit rgba pixels, row by row from the top left
+    pub fn from_rgba(
+        context: &VulkanoContext,
+        command_buffer_allocator: &Arc<StandardCommandBufferAllocator>,
+        pixels: Vec<u8>,
+        dimensions: [u32; 2],
+        srgb: bool,
+    ) -> Result<Self, Error> {
+        let format = if srgb {Format::R8G8B8A8_SRGB} else {Format::R8G8B8A8_UNORM};
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            command_buffer_allocator,
+            context.graphics_queue().queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+
+        // the mip levels are blitted from the full size image as part of the upload
+        let image = ImmutableImage::from_iter(
+            context.memory_allocator(),
+            pixels,
+            ImageDimensions::Dim2d {
+                width: dimensions[0],
+                height: dimensions[1],
+                array_layers: 1,
+            },
+            MipmapsCount::Log2,
+            format,
+            &mut builder,
+        )?;
+
+        let command_buffer = builder.build()?;
+        sync::now(context.device().clone())
+            .then_execute(context.graphics_queue().clone(), command_buffer)?
+            .then_signal_fence_and_flush()?
+            .wait(None)?;
+
+        let sampler = Sampler::new(context.device().clone(), SamplerCreateInfo::simple_repeat_linear())?;
+
+        Ok(Texture {
+            view: ImageView::new_default(image)?,
+            sampler,
+        })
+    }
+
+    pub fn view(&self) -> &Arc<ImageView<ImmutableImage>> {
+        &self.view
+    }
+
+    pub fn sampler(&self) -> &Arc<Sampler> {
+        &self.sampler
+    }
+
+    /// replaces the sampler, for example to clamp instead of repeat
+    pub fn set_sampler(&mut self, sampler: Arc<Sampler>) {
+        self.sampler = sampler;
+    }
+
+    /// returns the descriptor write binding this texture at the given binding
+    pub fn write(&self, binding: u32) -> WriteDescriptorSet {
+        WriteDescriptorSet::image_view_sampler(binding, self.view.clone(), self.sampler.clone())
+    }
+}
@@ -0,0 +1,256 @@
+//! rotations and translation/rotation/scale transforms
+use std::ops::Mul;
+use maths::{Vector3, Matrix4};
+use super::GltfNode;
+
+
+/// A rotation stored as a unit quaternion
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quaternion {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion::IDENTITY
+    }
+}
+
+impl Quaternion {
+    /// no rotation
+    pub const IDENTITY: Quaternion = Quaternion {x: 0.0, y: 0.0, z: 0.0, w: 1.0};
+
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Quaternion {x, y, z, w}
+    }
+
+    /// a rotation of angle radians around axis, the axis does not need to be normalised
+    pub fn from_axis_angle(axis: [f32; 3], angle: f32) -> Self {
+        let length = (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt();
+        if length == 0.0 {
+            return Quaternion::IDENTITY;
+        }
+        let (sin, cos) = (angle * 0.5).sin_cos();
+        let factor = sin / length;
+        Quaternion {
+            x: axis[0] * factor,
+            y: axis[1] * factor,
+            z: axis[2] * factor,
+            w: cos,
+        }
+    }
+
+    /// the rotation held by the columns of an orthonormal 3x3 matrix
+    pub fn from_matrix3(columns: [[f32; 3]; 3]) -> Self {
+        let m = |row: usize, column: usize| columns[column][row];
+        let trace = m(0, 0) + m(1, 1) + m(2, 2);
+
+        // the largest of w, x, y and z is found first, dividing by a small one loses precision
+        let quaternion = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Quaternion::new((m(2, 1) - m(1, 2)) / s, (m(0, 2) - m(2, 0)) / s, (m(1, 0) - m(0, 1)) / s, 0.25 * s)
+        } else if m(0, 0) > m(1, 1) && m(0, 0) > m(2, 2) {
+            let s = (1.0 + m(0, 0) - m(1, 1) - m(2, 2)).sqrt() * 2.0;
+            Quaternion::new(0.25 * s, (m(0, 1) + m(1, 0)) / s, (m(0, 2) + m(2, 0)) / s, (m(2, 1) - m(1, 2)) / s)
+        } else if m(1, 1) > m(2, 2) {
+            let s = (1.0 + m(1, 1) - m(0, 0) - m(2, 2)).sqrt() * 2.0;
+            Quaternion::new((m(0, 1) + m(1, 0)) / s, 0.25 * s, (m(1, 2) + m(2, 1)) / s, (m(0, 2) - m(2, 0)) / s)
+        } else {
+            let s = (1.0 + m(2, 2) - m(0, 0) - m(1, 1)).sqrt() * 2.0;
+            Quaternion::new((m(0, 2) + m(2, 0)) / s, (m(1, 2) + m(2, 1)) / s, 0.25 * s, (m(1, 0) - m(0, 1)) / s)
+        };
+        quaternion.normalised()
+    }
+
+    pub fn dot(&self, other: Quaternion) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(*self).sqrt()
+    }
+
+    /// scales back to unit length, repeated multiplication slowly drifts away from it
+    pub fn normalised(&self) -> Self {
+        let length = self.length();
+        if length == 0.0 {
+            return Quaternion::IDENTITY;
+        }
+        Quaternion {
+            x: self.x / length,
+            y: self.y / length,
+            z: self.z / length,
+            w: self.w / length,
+        }
+    }
+
+    /// the opposite rotation, for a unit quaternion
+    pub fn conjugate(&self) -> Self {
+        Quaternion {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+            w: self.w,
+        }
+    }
+
+    /// rotates a vector
+    pub fn rotate(&self, vector: [f32; 3]) -> [f32; 3] {
+        // v + 2w(q x v) + 2(q x (q x v))
+        let q = [self.x, self.y, self.z];
+        let t = [
+            2.0 * (q[1] * vector[2] - q[2] * vector[1]),
+            2.0 * (q[2] * vector[0] - q[0] * vector[2]),
+            2.0 * (q[0] * vector[1] - q[1] * vector[0]),
+        ];
+        [
+            vector[0] + self.w * t[0] + (q[1] * t[2] - q[2] * t[1]),
+            vector[1] + self.w * t[1] + (q[2] * t[0] - q[0] * t[2]),
+            vector[2] + self.w * t[2] + (q[0] * t[1] - q[1] * t[0]),
+        ]
+    }
+
+    /// spherical interpolation, taking the shorter way round, t of 0 gives self and 1 gives other
+    pub fn slerp(&self, other: Quaternion, t: f32) -> Self {
+        let mut other = other;
+        let mut cos = self.dot(other);
+        if cos < 0.0 {
+            other = Quaternion::new(-other.x, -other.y, -other.z, -other.w);
+            cos = -cos;
+        }
+
+        // nearly parallel rotations are lerped, as the sine below approaches zero
+        let (from_factor, to_factor) = if cos > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin = angle.sin();
+            (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+
+        Quaternion {
+            x: self.x * from_factor + other.x * to_factor,
+            y: self.y * from_factor + other.y * to_factor,
+            z: self.z * from_factor + other.z * to_factor,
+            w: self.w * from_factor + other.w * to_factor,
+        }.normalised()
+    }
+}
+
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    /// the rotation rhs followed by self
+    fn mul(self, rhs: Quaternion) -> Quaternion {
+        Quaternion {
+            x: self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            y: self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            z: self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            w: self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        }
+    }
+}
+
+impl From<[f32; 4]> for Quaternion {
+    /// from x, y, z, w order, as gltf stores them
+    fn from(value: [f32; 4]) -> Self {
+        Quaternion::new(value[0], value[1], value[2], value[3])
+    }
+}
+
+impl From<Quaternion> for [f32; 4] {
+    fn from(value: Quaternion) -> Self {
+        [value.x, value.y, value.z, value.w]
+    }
+}
+
+
+/// A translation, rotation and scale, applied to points in the order scale, rotate, translate
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: [f32; 3],
+    pub rotation: Quaternion,
+    pub scale: [f32; 3],
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        translation: [0.0; 3],
+        rotation: Quaternion::IDENTITY,
+        scale: [1.0; 3],
+    };
+
+    pub fn from_translation(translation: [f32; 3]) -> Self {
+        Transform {
+            translation,
+            ..Transform::IDENTITY
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: Quaternion) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: [f32; 3]) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// the transform as a matrix, its columns are the rotated and scaled axes followed by the translation
+    pub fn matrix(&self) -> Matrix4 {
+        let x = Vector3::from(self.rotation.rotate([1.0, 0.0, 0.0])) * self.scale[0];
+        let y = Vector3::from(self.rotation.rotate([0.0, 1.0, 0.0])) * self.scale[1];
+        let z = Vector3::from(self.rotation.rotate([0.0, 0.0, 1.0])) * self.scale[2];
+        let [tx, ty, tz] = self.translation;
+        Matrix4::new(
+            x.x, x.y, x.z, 0.0,
+            y.x, y.y, y.z, 0.0,
+            z.x, z.y, z.z, 0.0,
+            tx, ty, tz, 1.0,
+        )
+    }
+}
+
+impl From<&GltfNode> for Transform {
+    fn from(node: &GltfNode) -> Self {
+        Transform {
+            translation: node.translation,
+            rotation: node.rotation.into(),
+            scale: node.scale,
+        }
+    }
+}
+
+
+/// the inverse of an affine matrix, such as a world matrix built from transforms, None if it has no inverse
+///
+/// The bottom row is taken to be 0, 0, 0, 1
+pub fn invert_affine(matrix: Matrix4) -> Option<Matrix4> {
+    let columns: [[f32; 4]; 4] = matrix.into();
+    let column = |index: usize| Vector3::new(columns[index][0], columns[index][1], columns[index][2]);
+    let (x, y, z, translation) = (column(0), column(1), column(2), column(3));
+
+    // the rows of the inverse of the 3x3 part are the cross products of its columns, over its determinant
+    let determinant = x.dot(y.cross(z));
+    if determinant.abs() < 1e-12 {
+        return None;
+    }
+    let scale = 1.0 / determinant;
+    let (row_x, row_y, row_z) = (y.cross(z) * scale, z.cross(x) * scale, x.cross(y) * scale);
+    Some(Matrix4::new(
+        row_x.x, row_y.x, row_z.x, 0.0,
+        row_x.y, row_y.y, row_z.y, 0.0,
+        row_x.z, row_y.z, row_z.z, 0.0,
+        -row_x.dot(translation), -row_y.dot(translation), -row_z.dot(translation), 1.0,
+    ))
+}
@@ -1,8 +1,8 @@
 use vulkano::{buffer::{BufferContents, Subbuffer, Buffer, BufferCreateInfo, BufferUsage}, memory::allocator::{AllocationCreateInfo, MemoryUsage}};
 use vulkano_util::{context::VulkanoContext, window::VulkanoWindows};
-use winit::{event::{Event, WindowEvent, ElementState}, event_loop::{ControlFlow, EventLoop}, window::WindowId, platform::run_return::EventLoopExtRunReturn};
+use winit::{event::{Event, WindowEvent, ElementState, DeviceEvent, MouseScrollDelta}, event_loop::{ControlFlow, EventLoop}, window::{WindowId, CursorGrabMode}, platform::run_return::EventLoopExtRunReturn};
 
-use crate::{attempt_update_gui_window, GuiWindowData, Camera};
+use crate::{attempt_update_gui_window, GuiWindowData, Camera, Error, InputSource};
 
 pub enum BufferType {
     Vertex,
@@ -15,7 +15,7 @@ pub fn create_shader_data_buffer<T, I>(
     data: I,
     context: &VulkanoContext,
     shader_type: BufferType
-) -> Subbuffer<[T]>
+) -> Result<Subbuffer<[T]>, Error>
 where
     T: BufferContents,
     I: IntoIterator<Item = T>,
@@ -28,7 +28,7 @@ where
         BufferType::Storage => {BufferUsage::STORAGE_BUFFER}
     };
 
-    Buffer::from_iter(
+    Ok(Buffer::from_iter(
         context.memory_allocator(),
         BufferCreateInfo {
             usage: usage,
@@ -39,7 +39,7 @@ where
             ..Default::default()
         },
         data
-    ).unwrap()
+    )?)
 }
 
 pub fn generic_winit_event_handling(
@@ -57,7 +57,10 @@ pub fn generic_winit_event_handling(
                 for window in gui.iter_mut() {
                     attempt_update_gui_window(window, &event, window_id.clone());
                 }
-                let renderer = windows.get_renderer_mut(window_id.clone()).unwrap();
+                let renderer = match windows.get_renderer_mut(window_id.clone()) {
+                    Some(renderer) => renderer,
+                    None => return,
+                };
                 match event {
                     WindowEvent::Resized(_) => {
                         renderer.resize();
@@ -95,7 +98,13 @@ pub fn generic_winit_event_handling_with_camera(
                 for window in gui.iter_mut() {
                     attempt_update_gui_window(window, &event, window_id.clone());
                 }
-                let renderer = windows.get_renderer_mut(window_id.clone()).unwrap();
+                // presses over an overlay gui are for the gui, releases still reach the camera so nothing is left held
+                let gui_wants_pointer = gui.iter().any(|window| window.window_id() == *window_id && window.wants_pointer_input());
+                let gui_wants_keyboard = gui.iter().any(|window| window.window_id() == *window_id && window.wants_keyboard_input());
+                let renderer = match windows.get_renderer_mut(window_id.clone()) {
+                    Some(renderer) => renderer,
+                    None => return,
+                };
                 match event {
                     WindowEvent::Resized(_) => {
                         renderer.resize();
@@ -114,13 +123,67 @@ pub fn generic_winit_event_handling_with_camera(
                         },
                         ..
                     } => {
+                        let pressed = *state == ElementState::Pressed;
+                        if window_id == camera.1 && !(pressed && gui_wants_keyboard) {
+                            camera.0.process_key(*keycode, pressed);
+                        }
+                    }
+                    WindowEvent::MouseInput { button, state, .. } => {
+                        let pressed = *state == ElementState::Pressed;
+                        if window_id == camera.1 && !(pressed && gui_wants_pointer) {
+                            camera.0.process_input(InputSource::Mouse(*button), if pressed {1.0} else {0.0});
+                            let was_grabbed = camera.0.wants_cursor_grab();
+                            camera.0.process_mouse_button(*button, pressed);
+                            let grabbed = camera.0.wants_cursor_grab();
+                            if grabbed != was_grabbed {
+                                let window = renderer.window();
+                                // not every platform can lock the cursor in place, confining it to the window is the fallback
+                                let _ = match grabbed {
+                                    true => window.set_cursor_grab(CursorGrabMode::Locked).or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined)),
+                                    false => window.set_cursor_grab(CursorGrabMode::None),
+                                };
+                                window.set_cursor_visible(!grabbed);
+                            }
+                        }
+                    }
+                    WindowEvent::Focused(false) => {
+                        // keys released while the window is in the background are never seen, so nothing is left held
+                        if window_id == camera.1 {
+                            camera.0.release_inputs();
+                        }
+                    }
+                    WindowEvent::CursorMoved { position, .. } => {
                         if window_id == camera.1 {
-                            camera.0.process_key(*keycode, *state == ElementState::Pressed);
+                            camera.0.process_cursor_moved([position.x, position.y]);
+                        }
+                    }
+                    WindowEvent::MouseWheel { delta, .. } => {
+                        if window_id == camera.1 && !gui_wants_pointer {
+                            let lines = match delta {
+                                MouseScrollDelta::LineDelta(_, lines) => *lines,
+                                // touchpads report pixels, roughly 20 make up a line
+                                MouseScrollDelta::PixelDelta(position) => position.y as f32 / 20.0,
+                            };
+                            camera.0.process_scroll(lines);
                         }
                     }
                     _ => (),
                 }
             },
+            Event::DeviceEvent { event: DeviceEvent::MouseMotion { delta }, .. } => {
+                // device events are not tied to a window, they only turn the camera while its window has grabbed the cursor
+                if camera.0.wants_cursor_grab() {
+                    camera.0.process_mouse_motion([delta.0, delta.1]);
+                }
+            },
+            Event::DeviceEvent { event: DeviceEvent::Motion { axis, value }, .. } => {
+                // winit 0.28 only reports raw mouse and similar device axes here, never gamepads, as movement since the last event
+                let focused = windows.get_renderer(*camera.1).map_or(false, |renderer| renderer.window().has_focus());
+                let gui_wants_pointer = gui.iter().any(|window| window.window_id() == *camera.1 && window.wants_pointer_input());
+                if focused && !gui_wants_pointer {
+                    camera.0.process_axis_motion(*axis, *value as f32);
+                }
+            },
             Event::MainEventsCleared => *control_flow = ControlFlow::Exit,
             _ => ()
         }
@@ -0,0 +1,102 @@
+//! Bounding volume and frustum tests
+mod common;
+use common::*;
+use rust_vulkan_graphics::*;
+
+
+#[test]
+fn aabb_from_mesh() {
+    let vertices: Vec<PositionVertex> = vec![[1.0, -2.0, 0.5].into(), [-1.0, 3.0, 0.0].into(), [0.0, 0.0, -4.0].into()];
+    let mesh = Mesh::new(vertices, vec![0, 1, 2]);
+
+    let aabb = mesh.aabb().unwrap();
+    assert_close(aabb.min, [-1.0, -2.0, -4.0]);
+    assert_close(aabb.max, [1.0, 3.0, 0.5]);
+    assert_close(aabb.centre(), [0.0, 0.5, -1.75]);
+
+    let sphere = mesh.bounding_sphere().unwrap();
+    assert_close(sphere.centre, aabb.centre());
+    for vertex in mesh.vertices.iter() {
+        assert!(sphere.contains_point(vertex.position));
+    }
+
+    let empty: Mesh<PositionVertex> = Mesh::new(Vec::new(), Vec::new());
+    assert!(empty.aabb().is_none());
+    assert!(empty.bounding_sphere().is_none());
+}
+
+#[test]
+fn aabb_transformed() {
+    let aabb = Aabb::new([-1.0, -1.0, -1.0], [1.0, 1.0, 1.0]);
+
+    let moved = aabb.transformed(&Transform::from_translation([5.0, 0.0, -2.0]).matrix());
+    assert_close(moved.min, [4.0, -1.0, -3.0]);
+    assert_close(moved.max, [6.0, 1.0, -1.0]);
+
+    // a quarter turn keeps a cube the same, an eighth turn widens it by root two
+    let quarter = Transform::IDENTITY.with_rotation(Quaternion::from_axis_angle([0.0, 1.0, 0.0], std::f32::consts::FRAC_PI_2));
+    let turned = aabb.transformed(&quarter.matrix());
+    assert_close(turned.min, aabb.min);
+    assert_close(turned.max, aabb.max);
+
+    let eighth = Transform::IDENTITY.with_rotation(Quaternion::from_axis_angle([0.0, 1.0, 0.0], std::f32::consts::FRAC_PI_4));
+    let turned = aabb.transformed(&eighth.matrix());
+    let root_two = std::f32::consts::SQRT_2;
+    assert_close(turned.max, [root_two, 1.0, root_two]);
+
+    let sphere = BoundingSphere::new([1.0, 0.0, 0.0], 1.0).transformed(&Transform::IDENTITY.with_scale([1.0, 3.0, 2.0]).matrix());
+    assert_close(sphere.centre, [1.0, 0.0, 0.0]);
+    assert!((sphere.radius - 3.0).abs() < EPSILON);
+}
+
+#[test]
+fn frustum_culling() {
+    // looking down +x with the generic 90 degree projection
+    let camera = Camera::new(Some([0.0, 0.0, 0.0]), Some([1.0, 0.0, 0.0]), None, None);
+    let frustum = Frustum::from_camera(&camera, [500, 500]);
+
+    assert!(frustum.contains_point([10.0, 0.0, 0.0]));
+    assert!(frustum.contains_point([10.0, 5.0, -5.0]));
+    assert!(!frustum.contains_point([-10.0, 0.0, 0.0]));
+    assert!(!frustum.contains_point([200.0, 0.0, 0.0]));
+    assert!(!frustum.contains_point([10.0, 50.0, 0.0]));
+    assert!(!frustum.contains_point([10.0, 0.0, -50.0]));
+
+    let unit = |centre: [f32; 3]| Aabb::new([centre[0] - 1.0, centre[1] - 1.0, centre[2] - 1.0], [centre[0] + 1.0, centre[1] + 1.0, centre[2] + 1.0]);
+    assert!(frustum.intersects_aabb(&unit([10.0, 0.0, 0.0])));
+    assert!(!frustum.intersects_aabb(&unit([-10.0, 0.0, 0.0])));
+    assert!(!frustum.intersects_aabb(&unit([10.0, 30.0, 0.0])));
+    // mostly off the side of the view, but overlapping its edge
+    assert!(frustum.intersects_aabb(&unit([10.0, 10.5, 0.0])));
+
+    assert!(frustum.intersects_sphere(&BoundingSphere::new([50.0, 0.0, 0.0], 1.0)));
+    assert!(frustum.intersects_sphere(&BoundingSphere::new([101.0, 0.0, 0.0], 2.0)));
+    assert!(!frustum.intersects_sphere(&BoundingSphere::new([0.0, 0.0, -20.0], 5.0)));
+}
+
+#[test]
+fn scene_culling() {
+    let camera = Camera::new(Some([0.0, 0.0, 0.0]), Some([1.0, 0.0, 0.0]), None, None);
+    let frustum = Frustum::from_camera(&camera, [500, 500]);
+
+    let mut scene: Scene<&str> = Scene::new();
+    let mesh = scene.add_mesh("cube");
+    let root = scene.add_node(None, Transform::IDENTITY);
+    let ahead = scene.add_mesh_node(Some(root), Transform::from_translation([10.0, 0.0, 0.0]), mesh, None);
+    let behind = scene.add_mesh_node(Some(root), Transform::from_translation([-10.0, 0.0, 0.0]), mesh, None);
+    for node in [ahead, behind] {
+        scene.set_bounds(node, Some(Aabb::new([-1.0; 3], [1.0; 3])));
+    }
+
+    let mut drawn = Vec::new();
+    scene.update_world_matrices();
+    scene.visit_in_frustum(&frustum, |node, _, _, _| {drawn.push(node); Ok(())}).unwrap();
+    assert_eq!(drawn, vec![ahead]);
+
+    // turning the parent round swaps which child is in view
+    scene.node_mut(root).transform_mut().rotation = Quaternion::from_axis_angle([0.0, 1.0, 0.0], std::f32::consts::PI);
+    scene.update_world_matrices();
+    drawn.clear();
+    scene.visit_in_frustum(&frustum, |node, _, _, _| {drawn.push(node); Ok(())}).unwrap();
+    assert_eq!(drawn, vec![behind]);
+}
@@ -0,0 +1,191 @@
+//! Camera and projection tests
+mod common;
+use common::*;
+use rust_vulkan_graphics::*;
+
+/// projects a view space point, returning the normalised device coordinates
+fn project(matrix: &[[f32; 4]; 4], point: [f32; 3]) -> [f32; 3] {
+    let mut clip = [0.0; 4];
+    for row in 0..4 {
+        clip[row] = matrix[0][row] * point[0] + matrix[1][row] * point[1] + matrix[2][row] * point[2] + matrix[3][row];
+    }
+    [clip[0] / clip[3], clip[1] / clip[3], clip[2] / clip[3]]
+}
+
+fn assert_depth(matrix: &[[f32; 4]; 4], distance: f32, depth: f32) {
+    let projected = project(matrix, [0.0, 0.0, -distance]);
+    assert!((projected[2] - depth).abs() < EPSILON, "depth at {} was {}, expected {}", distance, projected[2], depth);
+}
+
+
+#[test]
+fn perspective_depth_range() {
+    let projection = Projection::Perspective {fov: 1.0, near: 0.5, far: 200.0};
+
+    let matrix = projection.matrix(1.5, false);
+    assert_depth(&matrix, 0.5, 0.0);
+    assert_depth(&matrix, 200.0, 1.0);
+
+    let reversed = projection.matrix(1.5, true);
+    assert_depth(&reversed, 0.5, 1.0);
+    assert_depth(&reversed, 200.0, 0.0);
+
+    // the edge of the vertical field of view lands on the edge of the screen
+    let edge = project(&matrix, [0.0, 10.0 * 0.5f32.tan(), -10.0]);
+    assert!((edge[1] - 1.0).abs() < EPSILON);
+    let edge = project(&matrix, [10.0 * 0.5f32.tan() * 1.5, 0.0, -10.0]);
+    assert!((edge[0] - 1.0).abs() < EPSILON);
+}
+
+#[test]
+fn infinite_perspective_depth_range() {
+    let projection = Projection::InfinitePerspective {fov: 1.0, near: 0.1};
+
+    let matrix = projection.matrix(1.0, false);
+    assert_depth(&matrix, 0.1, 0.0);
+    assert!(project(&matrix, [0.0, 0.0, -1.0e6])[2] < 1.0);
+
+    let reversed = projection.matrix(1.0, true);
+    assert_depth(&reversed, 0.1, 1.0);
+    assert!(project(&reversed, [0.0, 0.0, -1.0e6])[2] > 0.0);
+
+    // nothing is too far away to be in the frustum
+    let frustum = Frustum::from_matrix(&matrix);
+    assert!(frustum.contains_point([0.0, 0.0, -1.0e6]));
+    assert!(!frustum.contains_point([0.0, 0.0, -0.05]));
+}
+
+#[test]
+fn orthographic_depth_range() {
+    let projection = Projection::Orthographic {height: 10.0, near: 1.0, far: 21.0};
+
+    let matrix = projection.matrix(2.0, false);
+    assert_depth(&matrix, 1.0, 0.0);
+    assert_depth(&matrix, 11.0, 0.5);
+    assert_depth(&matrix, 21.0, 1.0);
+    let corner = project(&matrix, [10.0, 5.0, -5.0]);
+    assert!((corner[0] - 1.0).abs() < EPSILON && (corner[1] - 1.0).abs() < EPSILON);
+
+    let reversed = projection.matrix(2.0, true);
+    assert_depth(&reversed, 1.0, 1.0);
+    assert_depth(&reversed, 21.0, 0.0);
+}
+
+#[test]
+fn camera_far_plane() {
+    // the default projection stops at 100, a larger far plane sees further
+    let mut camera = Camera::new(Some([0.0, 0.0, 0.0]), Some([1.0, 0.0, 0.0]), None, None);
+    assert!(!Frustum::from_camera(&camera, [800, 600]).contains_point([150.0, 0.0, 0.0]));
+
+    camera.projection = Projection::Perspective {fov: std::f32::consts::FRAC_PI_2, near: 0.1, far: 1000.0};
+    assert!(Frustum::from_camera(&camera, [800, 600]).contains_point([150.0, 0.0, 0.0]));
+
+    camera.reverse_z = true;
+    let frustum = Frustum::from_camera(&camera, [800, 600]);
+    assert!(frustum.contains_point([150.0, 0.0, 0.0]));
+    assert!(!frustum.contains_point([1500.0, 0.0, 0.0]));
+    assert!(!frustum.contains_point([-5.0, 0.0, 0.0]));
+}
+
+fn assert_orthonormal(camera: &Camera) {
+    let view: [[f32; 4]; 4] = camera.get_view_matrix().into();
+    for i in 0..3 {
+        for j in 0..3 {
+            let dot: f32 = (0..3).map(|k| view[k][i] * view[k][j]).sum();
+            let expected = if i == j {1.0} else {0.0};
+            assert!((dot - expected).abs() < 1e-3, "rows {} and {} of the view matrix dot to {}", i, j, dot);
+        }
+    }
+}
+
+#[test]
+fn orientation_stays_orthonormal() {
+    let mut camera = Camera::new(None, Some([1.0, 2.0, 3.0]), None, None);
+    assert_orthonormal(&camera);
+    for i in 0..10000 {
+        camera.rotate_yaw(0.37);
+        camera.rotate_pitch(if i % 3 == 0 {0.21} else {-0.13});
+        camera.rotate_roll(0.05);
+    }
+    assert_orthonormal(&camera);
+    assert!((camera.orientation().length() - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn pitch_limit() {
+    let mut camera = Camera::new(None, None, None, None);
+    camera.pitch_limit = Some(1.0);
+    camera.rotate_pitch(0.6);
+    camera.rotate_pitch(0.6);
+    assert!((camera.pitch() - 1.0).abs() < 1e-4);
+    camera.rotate_pitch(-2.5);
+    assert!((camera.pitch() + 1.0).abs() < 1e-4);
+
+    // yaw and roll leave the pitch alone
+    camera.rotate_roll(0.4);
+    camera.rotate_yaw(2.0);
+    assert!((camera.pitch() + 1.0).abs() < 1e-4);
+    assert!((camera.roll() - 0.4).abs() < 1e-4);
+    assert_orthonormal(&camera);
+}
+
+#[test]
+fn roll_keeps_direction() {
+    let mut camera = Camera::new(None, Some([0.0, 0.0, 1.0]), None, None);
+    assert_close_within(camera.up(), [0.0, -1.0, 0.0], 1e-3);
+    camera.rotate_roll(std::f32::consts::FRAC_PI_2);
+    assert_close_within(camera.direction(), [0.0, 0.0, 1.0], 1e-3);
+    assert!(camera.up().y.abs() < 1e-4);
+    assert!((camera.roll() - std::f32::consts::FRAC_PI_2).abs() < 1e-4);
+
+    // setting a direction levels the camera out again
+    camera.set_direction([1.0, 0.0, 0.0].into());
+    assert_close_within(camera.up(), [0.0, -1.0, 0.0], 1e-3);
+    assert!(camera.roll().abs() < 1e-4);
+}
+
+#[test]
+fn movement_stays_level() {
+    // moves forward for a second at 2 units a second, returning where the camera ends up
+    let walk = |camera: &mut Camera| -> [f32; 3] {
+        camera.controllable();
+        camera.process_key(VirtualKeyCode::W, true);
+        camera.do_move(1.0);
+        camera.process_key(VirtualKeyCode::W, false);
+        camera.position.into()
+    };
+
+    let mut level = Camera::new(Some([0.0; 3]), Some([1.0, 0.0, 0.0]), Some(2.0), None);
+    assert_close(walk(&mut level), [2.0, 0.0, 0.0]);
+
+    // pitching up or down does not slow the camera
+    for pitch in [80.0f32, -80.0] {
+        let mut pitched = Camera::new(Some([0.0; 3]), Some([1.0, 0.0, 0.0]), Some(2.0), None);
+        pitched.rotate_pitch(pitch.to_radians());
+        assert_close_within(walk(&mut pitched), [2.0, 0.0, 0.0], 1e-3);
+    }
+
+    // straight up or down it still moves a full step along the ground
+    for direction in [[0.0, 1.0, 0.0], [0.0, -1.0, 0.0]] {
+        let mut vertical = Camera::new(Some([0.0; 3]), Some(direction), Some(2.0), None);
+        vertical.rotate_roll(0.3);
+        let position = walk(&mut vertical);
+        assert!(position[1].abs() < EPSILON, "{:?}", position);
+        assert!((position[0].hypot(position[2]) - 2.0).abs() < EPSILON, "{:?}", position);
+    }
+}
+
+#[test]
+fn orientation_slerp() {
+    let mut camera = Camera::new(None, Some([1.0, 0.0, 0.0]), None, None);
+    let target = Camera::orientation_for([0.0, 0.0, 1.0]);
+
+    camera.slerp_orientation(target, 0.5);
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+    assert_close_within(camera.direction(), [half, 0.0, half], 1e-3);
+    assert_orthonormal(&camera);
+
+    camera.slerp_orientation(target, 1.0);
+    assert_close_within(camera.direction(), [0.0, 0.0, 1.0], 1e-3);
+    assert!((camera.yaw() - std::f32::consts::FRAC_PI_2).abs() < 1e-4);
+}
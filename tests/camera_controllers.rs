@@ -0,0 +1,102 @@
+//! Orbit and pan controller tests
+mod common;
+use common::*;
+use maths::Vector3;
+use rust_vulkan_graphics::*;
+
+fn orbit_distance(camera: &Camera) -> f32 {
+    match camera.controller() {
+        CameraController::Orbit(orbit) => orbit.distance,
+        _ => panic!("the camera is not orbiting"),
+    }
+}
+
+fn drag(camera: &mut Camera, button: MouseButton, delta: [f64; 2]) {
+    camera.process_cursor_moved([200.0, 200.0]);
+    camera.process_mouse_button(button, true);
+    camera.process_cursor_moved([200.0 + delta[0], 200.0 + delta[1]]);
+    camera.process_mouse_button(button, false);
+}
+
+
+#[test]
+fn orbit_starts_from_the_camera() {
+    let mut camera = Camera::new(Some([0.0, 5.0, 10.0]), None, None, None);
+    camera.set_controller(CameraController::Orbit(OrbitController::new([0.0; 3])));
+    assert_close(camera.position, [0.0, 5.0, 10.0]);
+    assert_close(camera.direction(), Vector3::new(0.0, -5.0, -10.0).normalised());
+    assert!((orbit_distance(&camera) - 125.0f32.sqrt()).abs() < EPSILON);
+
+    // turning around the target keeps the distance and keeps looking at it
+    camera.controllable();
+    drag(&mut camera, MouseButton::Left, [150.0, 40.0]);
+    let offset = camera.position;
+    assert!((offset.dot(offset).sqrt() - 125.0f32.sqrt()).abs() < 1e-3);
+    assert_close(camera.direction(), (-offset).normalised());
+
+    // a camera too far away is pulled in along the same line
+    let mut camera = Camera::new(Some([0.0, 0.0, 20.0]), None, None, None);
+    let mut orbit = OrbitController::new([0.0; 3]);
+    orbit.max_distance = 5.0;
+    camera.set_controller(CameraController::Orbit(orbit));
+    assert_close(camera.position, [0.0, 0.0, 5.0]);
+}
+
+#[test]
+fn orbit_zoom_is_clamped() {
+    let mut camera = Camera::new(Some([10.0, 0.0, 0.0]), None, None, None);
+    let mut orbit = OrbitController::new([0.0; 3]);
+    orbit.min_distance = 0.5;
+    orbit.max_distance = 20.0;
+    camera.set_controller(CameraController::Orbit(orbit));
+    camera.controllable();
+
+    camera.process_scroll(-1.0);
+    assert!((orbit_distance(&camera) - 11.0).abs() < EPSILON);
+    assert_close(camera.position, [11.0, 0.0, 0.0]);
+
+    camera.process_scroll(-50.0);
+    assert_eq!(orbit_distance(&camera), 20.0);
+    assert_close(camera.position, [20.0, 0.0, 0.0]);
+
+    camera.process_scroll(50.0);
+    assert_eq!(orbit_distance(&camera), 0.5);
+    assert_close(camera.position, [0.5, 0.0, 0.0]);
+    assert_close(camera.direction(), [-1.0, 0.0, 0.0]);
+}
+
+#[test]
+fn pan_drags_across_the_screen() {
+    let mut camera = Camera::new(Some([0.0; 3]), Some([1.0, 0.0, 0.0]), None, None);
+    camera.set_controller(CameraController::Pan(PanController::new(0.01, MouseButton::Middle)));
+    camera.controllable();
+
+    // the camera moves against the drag, so the view follows the cursor
+    drag(&mut camera, MouseButton::Middle, [100.0, 0.0]);
+    assert_close(camera.position, [0.0, 0.0, -1.0]);
+    drag(&mut camera, MouseButton::Middle, [0.0, 100.0]);
+    assert_close(camera.position, [0.0, 1.0, -1.0]);
+    assert_close(camera.direction(), [1.0, 0.0, 0.0]);
+
+    // moving the cursor without the button held does nothing
+    camera.process_cursor_moved([0.0, 0.0]);
+    assert_close(camera.position, [0.0, 1.0, -1.0]);
+
+    camera.process_scroll(2.0);
+    assert_close(camera.position, [2.0, 1.0, -1.0]);
+}
+
+#[test]
+fn pan_looking_straight_down() {
+    let mut camera = Camera::new(Some([0.0, 10.0, 0.0]), Some([0.0, -1.0, 0.0]), None, None);
+    camera.set_controller(CameraController::Pan(PanController::new(0.01, MouseButton::Middle)));
+    camera.controllable();
+
+    drag(&mut camera, MouseButton::Middle, [100.0, 0.0]);
+    drag(&mut camera, MouseButton::Middle, [0.0, 100.0]);
+    let position: [f32; 3] = camera.position.into();
+    assert!(position.iter().all(|axis| axis.is_finite()), "{:?}", position);
+    // the drags stay in the horizontal plane, one world unit each
+    assert!((position[1] - 10.0).abs() < EPSILON);
+    assert!((position[0].hypot(position[2]) - 2.0f32.sqrt()).abs() < EPSILON);
+}
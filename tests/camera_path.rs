@@ -0,0 +1,168 @@
+//! Camera path tests
+mod common;
+use common::*;
+use rust_vulkan_graphics::*;
+
+fn keyframe(time: f32, position: [f32; 3], direction: [f32; 3], fov: f32) -> CameraKeyframe {
+    CameraKeyframe {time, position, direction, fov: Some(fov)}
+}
+
+fn square_path() -> CameraPath {
+    CameraPath::new(vec![
+        keyframe(2.0, [10.0, 0.0, 10.0], [0.0, 0.0, 1.0], 1.0),
+        keyframe(0.0, [0.0, 0.0, 0.0], [1.0, 0.0, 0.0], 1.0),
+        keyframe(1.0, [10.0, 0.0, 0.0], [1.0, 0.0, 0.0], 2.0),
+        keyframe(3.0, [0.0, 0.0, 10.0], [-1.0, 0.0, 0.0], 1.0),
+    ])
+}
+
+
+#[test]
+fn sample_passes_through_keyframes() {
+    let path = square_path();
+    assert_eq!(path.duration(), 3.0);
+    assert!(path.keyframes.windows(2).all(|pair| pair[0].time <= pair[1].time));
+
+    for keyframe in path.keyframes.iter() {
+        let sample = path.sample(keyframe.time).unwrap();
+        assert_close(sample.position, keyframe.position);
+        assert_close(sample.direction, keyframe.direction);
+        assert!((sample.fov.unwrap() - keyframe.fov.unwrap()).abs() < EPSILON);
+    }
+
+    // halfway between keyframes the direction has turned halfway
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+    let sample = path.sample(1.5).unwrap();
+    assert_close(sample.direction, [half, 0.0, half]);
+    assert!((sample.fov.unwrap() - 1.5).abs() < EPSILON);
+
+    // before the start and after the end the path holds still
+    assert_close(path.sample(-1.0).unwrap().position, [0.0, 0.0, 0.0]);
+    assert_close(path.sample(10.0).unwrap().position, [0.0, 0.0, 10.0]);
+    assert!(CameraPath::default().sample(0.0).is_none());
+}
+
+#[test]
+fn easing() {
+    for easing in [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut] {
+        assert_eq!(easing.apply(0.0), 0.0);
+        assert_eq!(easing.apply(1.0), 1.0);
+    }
+    assert!(Easing::EaseIn.apply(0.25) < 0.25);
+    assert!(Easing::EaseOut.apply(0.25) > 0.25);
+    assert!((Easing::EaseInOut.apply(0.5) - 0.5).abs() < EPSILON);
+
+    // easing in is slow to leave the first keyframe
+    let linear = CameraPath::new(vec![keyframe(0.0, [0.0; 3], [1.0, 0.0, 0.0], 1.0), keyframe(1.0, [1.0, 0.0, 0.0], [1.0, 0.0, 0.0], 1.0)]);
+    let eased = linear.clone().with_easing(Easing::EaseIn);
+    assert!(eased.sample(0.25).unwrap().position[0] < linear.sample(0.25).unwrap().position[0]);
+
+    // every keyframe is still reached at its own time
+    let path = square_path().with_easing(Easing::EaseInOut);
+    for keyframe in path.keyframes.iter() {
+        assert_close(path.sample(keyframe.time).unwrap().position, keyframe.position);
+    }
+}
+
+#[test]
+fn looping_has_no_seam() {
+    let path = CameraPath::new(vec![
+        keyframe(0.0, [0.0, 0.0, 0.0], [1.0, 0.0, 0.0], 1.0),
+        keyframe(1.0, [10.0, 0.0, 0.0], [1.0, 0.0, 0.0], 1.0),
+        keyframe(2.0, [10.0, 0.0, 10.0], [1.0, 0.0, 0.0], 1.0),
+        keyframe(3.0, [0.0, 0.0, 10.0], [1.0, 0.0, 0.0], 1.0),
+        keyframe(4.0, [0.0, 0.0, 0.0], [1.0, 0.0, 0.0], 1.0),
+    ]).with_looping(true);
+
+    // just before and just after the join the camera moves the same way
+    let before: [f32; 3] = path.sample(-0.01).unwrap().position;
+    let after: [f32; 3] = path.sample(0.01).unwrap().position;
+    assert_close_within([-before[0], -before[1], -before[2]], after, 5e-3);
+}
+
+#[test]
+fn playback_through_do_move() {
+    let mut camera = Camera::new(None, None, None, None);
+    camera.play_path(square_path());
+    assert!(camera.is_playing_path());
+
+    for _ in 0..10 {
+        camera.do_move(0.1);
+    }
+    let position: [f32; 3] = camera.position.into();
+    assert_close(position, [10.0, 0.0, 0.0]);
+    assert!((camera.projection.fov().unwrap() - 2.0).abs() < EPSILON);
+
+    // a path that does not loop stops at its end
+    camera.do_move(5.0);
+    assert!(!camera.is_playing_path());
+    let position: [f32; 3] = camera.position.into();
+    assert_close(position, [0.0, 0.0, 10.0]);
+
+    // a looping path starts again
+    camera.play_path(square_path().with_looping(true));
+    camera.do_move(3.0);
+    camera.do_move(1.0);
+    assert!(camera.is_playing_path());
+    let position: [f32; 3] = camera.position.into();
+    assert_close(position, [10.0, 0.0, 0.0]);
+}
+
+#[test]
+fn record_and_replay() {
+    let mut camera = Camera::new(Some([0.0, 0.0, 0.0]), Some([1.0, 0.0, 0.0]), Some(1.0), None);
+    camera.controllable();
+    camera.start_recording(0.5);
+    camera.process_key(VirtualKeyCode::W, true);
+    for _ in 0..8 {
+        camera.do_move(0.25);
+    }
+    camera.process_key(VirtualKeyCode::W, false);
+    let path = camera.stop_recording().unwrap();
+    assert!(!camera.is_recording());
+    assert_eq!(path.keyframes.len(), 5);
+    assert!((path.duration() - 2.0).abs() < EPSILON);
+
+    // the saved path replays to the same place
+    let loaded = CameraPath::from_ron(&path.to_ron().unwrap()).unwrap();
+    assert_eq!(loaded, path);
+    let mut replay = Camera::new(None, Some([0.0, 0.0, 1.0]), None, None);
+    replay.play_path(loaded);
+    replay.do_move(1.0);
+    let position: [f32; 3] = replay.position.into();
+    assert_close(position, [1.0, 0.0, 0.0]);
+    replay.do_move(1.0);
+    let position: [f32; 3] = replay.position.into();
+    assert_close(position, [2.0, 0.0, 0.0]);
+    assert_close(replay.direction(), [1.0, 0.0, 0.0]);
+}
+
+#[test]
+fn recording_keeps_its_spacing() {
+    // frames that do not divide the interval carry the remainder over to the next keyframe
+    let mut camera = Camera::new(None, None, None, None);
+    camera.start_recording(1.0);
+    for _ in 0..4 {
+        camera.do_move(0.75);
+    }
+    let path = camera.stop_recording().unwrap();
+    let times: Vec<f32> = path.keyframes.iter().map(|keyframe| keyframe.time).collect();
+    assert_eq!(times, vec![0.0, 1.5, 2.25, 3.0]);
+}
+
+#[test]
+fn orthographic_keyframes_have_no_fov() {
+    let mut camera = Camera::new(None, None, None, None);
+    camera.projection = Projection::Orthographic {height: 10.0, near: 0.01, far: 100.0};
+    let keyframe = camera.keyframe(0.0);
+    assert_eq!(keyframe.fov, None);
+
+    // a perspective camera following it keeps its own field of view
+    let mut replay = Camera::new(None, None, None, None);
+    replay.apply_keyframe(&keyframe);
+    assert_eq!(replay.projection.fov(), Some(std::f32::consts::FRAC_PI_2));
+
+    // a keyframe saved without a field of view loads as None
+    let path = CameraPath::from_ron("(keyframes: [(time: 0.0, position: (0.0, 0.0, 0.0), direction: (1.0, 0.0, 0.0))])").unwrap();
+    assert_eq!(path.keyframes[0].fov, None);
+}
@@ -0,0 +1,31 @@
+//! helpers shared by the tests that only run on the cpu, each test file includes them with mod common
+#![allow(dead_code)]
+
+/// the largest difference allowed between floats that should be equal
+pub const EPSILON: f32 = 1e-4;
+
+/// asserts every axis of a is within EPSILON of b
+pub fn assert_close(a: impl Into<[f32; 3]>, b: impl Into<[f32; 3]>) {
+    assert_close_within(a, b, EPSILON);
+}
+
+/// asserts every axis of a is within epsilon of b, for results that build up more rounding
+pub fn assert_close_within(a: impl Into<[f32; 3]>, b: impl Into<[f32; 3]>, epsilon: f32) {
+    let (a, b) = (a.into(), b.into());
+    for axis in 0..3 {
+        assert!((a[axis] - b[axis]).abs() < epsilon, "{:?} != {:?}", a, b);
+    }
+}
+
+/// writes files into an empty directory of their own under the system temp directory, returning the directory
+///
+/// name keeps tests that run at the same time apart, so it should be unique to the test
+pub fn temp_files(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+    let directory = std::env::temp_dir().join(format!("rust_vulkan_graphics_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    for (file, contents) in files {
+        std::fs::write(directory.join(file), contents).unwrap();
+    }
+    directory
+}
@@ -0,0 +1,125 @@
+//! Gltf loading tests
+mod common;
+use common::*;
+use rust_vulkan_graphics::*;
+
+/// a square in the xy plane drawn once as a fan, with a parent node moved along x and a child holding the mesh,
+/// its material uses a single orange pixel for every texture
+///
+/// extensions is spliced into the top level object
+fn square(extensions: &str) -> String {
+    format!(r#"{{
+        "asset": {{"version": "2.0"}},
+        {}
+        "buffers": [{{"byteLength": 48, "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAACAPwAAgD8AAAAAAAAAAAAAgD8AAAAA"}}],
+        "bufferViews": [{{"buffer": 0, "byteLength": 48}}],
+        "accessors": [{{"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0]}}],
+        "images": [{{"uri": "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR4nGP438DwHwAGgAJ/EEwb4QAAAABJRU5ErkJggg=="}}],
+        "textures": [{{"source": 0}}],
+        "materials": [{{
+            "name": "red",
+            "pbrMetallicRoughness": {{"baseColorFactor": [1, 0, 0, 1], "metallicFactor": 0.5, "baseColorTexture": {{"index": 0}}, "metallicRoughnessTexture": {{"index": 0}}}},
+            "normalTexture": {{"index": 0, "scale": 0.5}},
+            "occlusionTexture": {{"index": 0, "strength": 0.25}},
+            "emissiveTexture": {{"index": 0}},
+            "emissiveFactor": [0.1, 0.2, 0.3],
+            "alphaMode": "MASK",
+            "doubleSided": true
+        }}],
+        "meshes": [{{"name": "square", "primitives": [{{"attributes": {{"POSITION": 0}}, "mode": 6, "material": 0}}]}}],
+        "nodes": [
+            {{"name": "parent", "translation": [2, 0, 0], "children": [1]}},
+            {{"name": "child", "mesh": 0, "scale": [3, 3, 3]}}
+        ],
+        "scenes": [{{"nodes": [0]}}],
+        "scene": 0
+    }}"#, extensions)
+}
+
+fn load(name: &str, source: &str) -> Result<GltfScene, Error> {
+    let directory = temp_files(name, &[("square.gltf", source)]);
+    load_gltf(directory.join("square.gltf").to_str().unwrap())
+}
+
+
+#[test]
+fn loads_meshes_materials_and_nodes() {
+    let scene = load("gltf_square", &square("")).unwrap();
+
+    let primitive = &scene.meshes[0].primitives[0];
+    assert_eq!(scene.meshes[0].name.as_deref(), Some("square"));
+    assert_eq!(primitive.mesh.vertices.len(), 4);
+    assert_eq!(primitive.mesh.vertices[2].position, [1.0, 1.0, 0.0]);
+    // the fan is split into triangles, and missing normals are generated
+    assert_eq!(primitive.mesh.indices, vec![0, 1, 2, 0, 2, 3]);
+    for normal in primitive.mesh.normals.iter() {
+        let normal = normal.normal;
+        assert!((normal[2].abs() - 1.0).abs() < EPSILON, "{:?}", normal);
+    }
+    assert!(primitive.tex_coords.is_empty() && primitive.colours.is_empty());
+
+    let material = scene.material(primitive).unwrap();
+    assert_eq!(material.name.as_deref(), Some("red"));
+    assert_eq!(material.base_colour_factor, [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(material.metallic_factor, 0.5);
+    assert_eq!(material.roughness_factor, 1.0);
+    assert_eq!(material.alpha_mode, AlphaMode::Mask(0.5));
+
+    assert_eq!(scene.roots, vec![0]);
+    assert_eq!(scene.nodes[0].children, vec![1]);
+    assert_eq!(scene.nodes[1].parent, Some(0));
+    assert_eq!(scene.nodes[1].mesh, Some(0));
+    let local: [[f32; 4]; 4] = scene.nodes[0].local_matrix().into();
+    assert_eq!(local[3], [2.0, 0.0, 0.0, 1.0]);
+    let local: [[f32; 4]; 4] = scene.nodes[1].local_matrix().into();
+    assert_eq!(local[0][0], 3.0);
+}
+
+#[test]
+fn converts_to_material() {
+    let scene = load("gltf_material", &square("")).unwrap();
+    assert_eq!(scene.images.len(), 1);
+    assert_eq!((scene.images[0].width, scene.images[0].height), (1, 1));
+    assert_eq!(scene.images[0].pixels, vec![255, 128, 0, 255]);
+
+    let material = Material::from_gltf(scene.material(&scene.meshes[0].primitives[0]).unwrap());
+    assert_eq!(material.name.as_deref(), Some("red"));
+    assert_eq!(material.base_colour, [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(material.metallic, 0.5);
+    assert_eq!(material.roughness, 1.0);
+    assert_close(material.emissive, [0.1, 0.2, 0.3]);
+    assert_eq!(material.normal_scale, 0.5);
+    assert_eq!(material.occlusion_strength, 0.25);
+    assert_eq!(material.alpha_mode, AlphaMode::Mask(0.5));
+    assert!(material.double_sided);
+
+    // every slot points at the scene's one image
+    for slot in [
+        &material.base_colour_texture,
+        &material.metallic_roughness_texture,
+        &material.normal_texture,
+        &material.occlusion_texture,
+        &material.emissive_texture,
+    ] {
+        assert_eq!(slot, &Some(TextureSource::Gltf(0)));
+    }
+}
+
+#[test]
+fn only_required_extensions_are_rejected() {
+    // an optional extension the loader does not know is skipped
+    let optional = square(r#""extensionsUsed": ["KHR_materials_emissive_strength"],"#);
+    assert!(load("gltf_optional_extension", &optional).is_ok());
+
+    let required = square(r#""extensionsUsed": ["KHR_draco_mesh_compression"], "extensionsRequired": ["KHR_draco_mesh_compression"],"#);
+    match load("gltf_required_extension", &required) {
+        Err(Error::UnsupportedGltfExtension(name)) => assert_eq!(name, "KHR_draco_mesh_compression"),
+        other => panic!("expected the required extension to be rejected, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn rejects_lines() {
+    let source = square("").replace(r#""mode": 6"#, r#""mode": 1"#);
+    assert!(matches!(load("gltf_lines", &source), Err(Error::UnsupportedGltf(_))));
+}
@@ -0,0 +1,314 @@
+//! Golden image tests, these render through the headless pipeline and compare against the pngs in tests/golden
+//!
+//! A missing reference image fails the test, set GOLDEN_UPDATE=1 to write missing ones or overwrite all of them after an intended change, then commit them.
+//! Failing tests write the rendered image and a diff image to target/golden_diffs.
+//! The tests only need a vulkan implementation, a cpu one such as lavapipe works, for example with VK_ICD_FILENAMES=/usr/share/vulkan/icd.d/lvp_icd.x86_64.json.
+//! Without any vulkan device they are skipped rather than failed.
+use std::{path::PathBuf, sync::Arc};
+use rust_vulkan_graphics::*;
+use maths::{Matrix3, Matrix4};
+use vulkano::{
+    instance::{Instance, InstanceCreateInfo},
+    sync::{self, GpuFuture},
+    VulkanLibrary,
+};
+
+mod coloured_vs {
+    rust_vulkan_graphics::shader!{
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+            layout(location = 1) in vec4 colour;
+
+            layout(location = 0) out vec4 v_colour;
+
+            layout(set = 0, binding = 0) uniform Data {
+                mat4 world;
+                mat4 view;
+                mat4 proj;
+            } uniforms;
+
+            void main() {
+                gl_Position = uniforms.proj * uniforms.view * uniforms.world * vec4(position, 1.0);
+                v_colour = colour;
+            }
+        ",
+    }
+}
+
+mod coloured_fs {
+    rust_vulkan_graphics::shader!{
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec4 v_colour;
+
+            layout(location = 0) out vec4 f_color;
+
+            void main() {
+                f_color = v_colour;
+            }
+        ",
+    }
+}
+
+mod normal_vs {
+    rust_vulkan_graphics::shader!{
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+            layout(location = 1) in vec3 normal;
+
+            layout(location = 0) out vec3 v_normal;
+
+            layout(set = 0, binding = 0) uniform Data {
+                mat4 world;
+                mat4 view;
+                mat4 proj;
+            } uniforms;
+
+            void main() {
+                gl_Position = uniforms.proj * uniforms.view * uniforms.world * vec4(position, 1.0);
+                v_normal = mat3(uniforms.world) * normal;
+            }
+        ",
+    }
+}
+
+mod normal_fs {
+    rust_vulkan_graphics::shader!{
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 v_normal;
+
+            layout(location = 0) out vec4 f_color;
+
+            const vec3 LIGHTDIR = vec3(-0.302, -0.302, 0.905);
+
+            void main() {
+                float light = max(dot(normalize(v_normal), LIGHTDIR), 0.0) + 0.2;
+                f_color = vec4(vec3(0.2) * light, 1.0);
+            }
+        ",
+    }
+}
+
+
+const DIMENSIONS: [u32; 2] = [256, 256];
+/// the largest difference allowed in any channel before a pixel counts as different
+const CHANNEL_TOLERANCE: u8 = 3;
+/// the fraction of pixels allowed to differ, multisampled edges can vary slightly between drivers
+const MAX_DIFFERENT_FRACTION: f32 = 0.002;
+
+
+#[test]
+fn test_cube() -> Result<(), Error> {
+    let (context, command_buffer_allocator, descriptor_set_allocator) = match headless_graphics_data() {
+        Some(data) => data,
+        None => return Ok(()),
+    };
+    let target = OffscreenTarget::new(&context, &command_buffer_allocator, DIMENSIONS, Format::B8G8R8A8_SRGB)?;
+
+    let vertex_buffer = create_shader_data_buffer(test_cube::COLOURED_VERTICES, &context, BufferType::Vertex)?;
+    let index_buffer = create_shader_data_buffer(test_cube::INDICES, &context, BufferType::Index)?;
+
+    let vs = coloured_vs::load(context.device().clone())?;
+    let fs = coloured_fs::load(context.device().clone())?;
+    let mut pipeline = MultiSamplePipeline3D::new(
+        &context,
+        &command_buffer_allocator,
+        &descriptor_set_allocator,
+        &vs,
+        &fs,
+        &vertex_defs::coloured(),
+        Some(SampleCount::Sample4),
+    )?;
+
+    let camera = Camera::new(Some([-2.0, 1.0, 0.0]), Some([1.0, -0.5, 0.0]), None, None);
+    let (view, proj) = get_generic_uniforms(DIMENSIONS, &camera);
+    let uniforms = create_uniform_buffer_allocator(context.memory_allocator()).allocate_sized()?;
+    *uniforms.write()? = coloured_vs::Data {
+        world: Matrix4::from(Matrix3::from_angle_y(0.6)).into(),
+        view: view.into(),
+        proj: proj.into(),
+    };
+
+    let before_future = sync::now(context.device().clone()).boxed();
+    let after_future = pipeline.draw_from_vertices(before_future, target.view(), &vertex_buffer, &index_buffer, &uniforms)?;
+
+    compare_to_golden("test_cube", &target.read_pixels(after_future)?);
+    Ok(())
+}
+
+#[test]
+fn island_obj() -> Result<(), Error> {
+    let (context, command_buffer_allocator, descriptor_set_allocator) = match headless_graphics_data() {
+        Some(data) => data,
+        None => return Ok(()),
+    };
+    let target = OffscreenTarget::new(&context, &command_buffer_allocator, DIMENSIONS, Format::B8G8R8A8_SRGB)?;
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets/island.obj");
+    let scene = load_obj(path.to_str().unwrap())?;
+    let (vertex_buffer, normal_buffer, index_buffer) = scene.combined_mesh().get_buffers(&context)?;
+
+    let vs = normal_vs::load(context.device().clone())?;
+    let fs = normal_fs::load(context.device().clone())?;
+    let mut pipeline = MultiSamplePipeline3D::new(
+        &context,
+        &command_buffer_allocator,
+        &descriptor_set_allocator,
+        &vs,
+        &fs,
+        &vertex_defs::position_normal(),
+        Some(SampleCount::Sample4),
+    )?;
+
+    let camera = Camera::new(Some([-20.0, 5.0, 0.0]), Some([1.0, -0.3, 0.0]), None, None);
+    let (view, proj) = get_generic_uniforms(DIMENSIONS, &camera);
+    let uniforms = create_uniform_buffer_allocator(context.memory_allocator()).allocate_sized()?;
+    *uniforms.write()? = normal_vs::Data {
+        world: Matrix4::from(Matrix3::from_angle_y(0.0)).into(),
+        view: view.into(),
+        proj: proj.into(),
+    };
+
+    let before_future = sync::now(context.device().clone()).boxed();
+    let after_future = pipeline.draw_from_vertices_and_normals(before_future, target.view(), &vertex_buffer, &normal_buffer, &index_buffer, &uniforms)?;
+
+    compare_to_golden("island_obj", &target.read_pixels(after_future)?);
+    Ok(())
+}
+
+
+#[test]
+fn draw_on_top() -> Result<(), Error> {
+    let (context, command_buffer_allocator, descriptor_set_allocator) = match headless_graphics_data() {
+        Some(data) => data,
+        None => return Ok(()),
+    };
+    let target = OffscreenTarget::new(&context, &command_buffer_allocator, DIMENSIONS, Format::B8G8R8A8_SRGB)?;
+
+    let vertex_buffer = create_shader_data_buffer(test_cube::COLOURED_VERTICES, &context, BufferType::Vertex)?;
+    let index_buffer = create_shader_data_buffer(test_cube::INDICES, &context, BufferType::Index)?;
+
+    let vs = coloured_vs::load(context.device().clone())?;
+    let fs = coloured_fs::load(context.device().clone())?;
+    let config = MultiSamplePipelineConfig {
+        colour_format: Some(Format::B8G8R8A8_SRGB),
+        clear_colour: [0.1, 0.2, 0.3, 1.0],
+        ..Default::default()
+    };
+    let mut clearing = MultiSamplePipeline3D::new_with_config(
+        &context,
+        &command_buffer_allocator,
+        &descriptor_set_allocator,
+        &vs,
+        &fs,
+        &vertex_defs::coloured(),
+        config.clone(),
+    )?;
+    let mut on_top = MultiSamplePipeline3D::new_with_config(
+        &context,
+        &command_buffer_allocator,
+        &descriptor_set_allocator,
+        &vs,
+        &fs,
+        &vertex_defs::coloured(),
+        MultiSamplePipelineConfig {clear: false, sample_count: SampleCount::Sample1, ..config},
+    )?;
+
+    let camera = Camera::new(Some([-4.0, 0.0, 0.0]), Some([1.0, 0.0, 0.0]), None, None);
+    let (view, proj) = get_generic_uniforms(DIMENSIONS, &camera);
+    let (view, proj): ([[f32; 4]; 4], [[f32; 4]; 4]) = (view.into(), proj.into());
+    let uniform_allocator = create_uniform_buffer_allocator(context.memory_allocator());
+    // the later cubes are further from the camera but overlap the first, they only show if depth is cleared between frames
+    let cube_at = |position: [f32; 3]| -> Result<_, Error> {
+        let uniforms = uniform_allocator.allocate_sized()?;
+        *uniforms.write()? = coloured_vs::Data {
+            world: Transform::from_translation(position).matrix().into(),
+            view,
+            proj,
+        };
+        Ok(uniforms)
+    };
+    let first = cube_at([0.0, 0.0, 0.0])?;
+    let second = cube_at([2.0, 0.0, 1.0])?;
+    let third = cube_at([2.0, 0.0, -1.0])?;
+
+    let before_future = sync::now(context.device().clone()).boxed();
+    let future = clearing.draw_from_vertices(before_future, target.view(), &vertex_buffer, &index_buffer, &first)?;
+    let future = on_top.draw_from_vertices(future, target.view(), &vertex_buffer, &index_buffer, &second)?;
+    let future = on_top.draw_from_vertices(future, target.view(), &vertex_buffer, &index_buffer, &third)?;
+
+    compare_to_golden("draw_on_top", &target.read_pixels(future)?);
+    Ok(())
+}
+
+
+/// the headless graphics data, or None if there is no vulkan device to render with
+fn headless_graphics_data() -> Option<(VulkanoContext, Arc<StandardCommandBufferAllocator>, Arc<StandardDescriptorSetAllocator>)> {
+    // get_headless_graphics_data panics without a device, so look for one first
+    let has_device = VulkanLibrary::new().ok()
+        .and_then(|library| Instance::new(library, InstanceCreateInfo::default()).ok())
+        .and_then(|instance| instance.enumerate_physical_devices().ok().map(|mut devices| devices.next().is_some()))
+        .unwrap_or(false);
+    if !has_device {
+        println!("no vulkan device found, skipping golden image test");
+        return None;
+    }
+    Some(get_headless_graphics_data())
+}
+
+/// compares rgba pixels against tests/golden/{name}.png, panicking with the number of different pixels if they do not match
+fn compare_to_golden(name: &str, pixels: &[u8]) {
+    let [width, height] = DIMENSIONS;
+    let golden_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.png", name));
+
+    if std::env::var_os("GOLDEN_UPDATE").is_some() {
+        std::fs::create_dir_all(golden_path.parent().unwrap()).unwrap();
+        image::save_buffer(&golden_path, pixels, width, height, image::ColorType::Rgba8).unwrap();
+        println!("wrote golden image {}", golden_path.display());
+        return;
+    }
+    assert!(golden_path.exists(), "no golden image at {}, run with GOLDEN_UPDATE=1 to write it", golden_path.display());
+
+    let golden = image::open(&golden_path).unwrap().to_rgba8();
+    assert_eq!(golden.dimensions(), (width, height), "golden image {} has the wrong size", golden_path.display());
+
+    let mut diff = Vec::with_capacity(pixels.len());
+    let mut different = 0;
+    for (actual, expected) in pixels.chunks_exact(4).zip(golden.as_raw().chunks_exact(4)) {
+        let matches = actual.iter().zip(expected).all(|(a, b)| a.abs_diff(*b) <= CHANNEL_TOLERANCE);
+        if matches {
+            // matching pixels are faded so the differences stand out
+            diff.extend(actual[0..3].iter().map(|channel| channel / 4));
+            diff.push(255);
+        } else {
+            different += 1;
+            diff.extend([255, 0, 255, 255]);
+        }
+    }
+
+    let allowed = (MAX_DIFFERENT_FRACTION * (width * height) as f32) as usize;
+    if different > allowed {
+        let diff_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/golden_diffs");
+        std::fs::create_dir_all(&diff_dir).unwrap();
+        let actual_path = diff_dir.join(format!("{}_actual.png", name));
+        let diff_path = diff_dir.join(format!("{}_diff.png", name));
+        image::save_buffer(&actual_path, pixels, width, height, image::ColorType::Rgba8).unwrap();
+        image::save_buffer(&diff_path, &diff, width, height, image::ColorType::Rgba8).unwrap();
+
+        panic!(
+            "{} pixels differ from {} (at most {} allowed), see {} and {}",
+            different, golden_path.display(), allowed, actual_path.display(), diff_path.display()
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! Gui widget tests, these only check the values and need no window
+use rust_vulkan_graphics::*;
+
+
+fn settings() -> GuiWidgets {
+    GuiWindowBuilder::new("Settings")
+        .section("Spinning")
+        .checkbox("Enable Spinning", true)
+        .f32_slider("Spin Speed", 0.5, -5.0..=5.0)
+        .section("Other")
+        .i32_box("Count", -3)
+        .u32_box("Samples", 4)
+        .text_box("Name", "cube")
+        .into_widgets()
+        .unwrap()
+}
+
+
+#[test]
+fn values_by_key() {
+    let mut widgets = settings();
+    assert!(widgets.get_bool("Enable Spinning").unwrap());
+    assert_eq!(widgets.get_f32("Spin Speed").unwrap(), 0.5);
+    assert_eq!(widgets.get_i32("Count").unwrap(), -3);
+    assert_eq!(widgets.get_u32("Samples").unwrap(), 4);
+    assert_eq!(widgets.get_string("Name").unwrap(), "cube");
+
+    widgets.set_f32("Spin Speed", 2.0).unwrap();
+    assert_eq!(widgets.get_f32("Spin Speed").unwrap(), 2.0);
+    // sliders stay in their range
+    widgets.set_f32("Spin Speed", 50.0).unwrap();
+    assert_eq!(widgets.get_f32("Spin Speed").unwrap(), 5.0);
+    widgets.set_string("Name", "sphere").unwrap();
+    assert_eq!(widgets.get_string("Name").unwrap(), "sphere");
+}
+
+#[test]
+fn order_is_kept() {
+    let keys: Vec<&str> = settings().iter().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(keys, vec!["Spinning", "Enable Spinning", "Spin Speed", "Other", "Count", "Samples", "Name"]);
+}
+
+#[test]
+fn wrong_keys_and_types() {
+    let mut widgets = settings();
+    assert!(matches!(widgets.get_f32("Spin speed"), Err(Error::MissingGuiWidget(_))));
+    assert!(matches!(widgets.get_f32("Enable Spinning"), Err(Error::GuiWidgetType {expected: "f32", ..})));
+    assert!(matches!(widgets.set_bool("Samples", true), Err(Error::GuiWidgetType {..})));
+    // sections hold no value
+    assert!(matches!(widgets.get_bool("Spinning"), Err(Error::MissingGuiWidget(_))));
+
+    let duplicated = GuiWindowBuilder::new("Settings")
+        .checkbox("Enable Spinning", true)
+        .f32_box("Enable Spinning", 1.0)
+        .into_widgets();
+    assert!(matches!(duplicated, Err(Error::DuplicateGuiWidget(_))));
+}
+
+#[test]
+fn more_widget_types() {
+    let mut widgets = GuiWindowBuilder::new("Render Settings")
+        .button("Reset")
+        .rgb("Colour", [1.0, 0.5, 0.0])
+        .rgba("Tint", [1.0, 1.0, 1.0, 0.5])
+        .combo("Projection", ["Perspective", "Orthographic"], 1)
+        .group("Light", false, |group| group
+            .vec3("Direction", [0.0, -1.0, 0.0])
+            .f32_slider("Strength", 1.0, 0.0..=10.0)
+        )
+        .plot("Frame Time", 3)
+        .into_widgets()
+        .unwrap();
+
+    assert!(!widgets.take_click("Reset").unwrap());
+    *widgets.get_mut("Reset").unwrap() = GuiWidget::Button(true);
+    assert!(widgets.take_click("Reset").unwrap());
+    assert!(!widgets.take_click("Reset").unwrap());
+
+    assert_eq!(widgets.get_rgb("Colour").unwrap(), [1.0, 0.5, 0.0]);
+    assert_eq!(widgets.get_rgba("Tint").unwrap()[3], 0.5);
+    assert!(widgets.get_rgba("Colour").is_err());
+
+    assert_eq!(widgets.get_selected_name("Projection").unwrap(), "Orthographic");
+    widgets.set_selected("Projection", 7).unwrap();
+    assert_eq!(widgets.get_selected("Projection").unwrap(), 1);
+    widgets.set_selected("Projection", 0).unwrap();
+    assert_eq!(widgets.get_selected_name("Projection").unwrap(), "Perspective");
+
+    // widgets in groups are found by their own keys
+    assert_eq!(widgets.get_vec3("Direction").unwrap(), [0.0, -1.0, 0.0]);
+    widgets.set_f32("Strength", 3.0).unwrap();
+    assert_eq!(widgets.get_f32("Strength").unwrap(), 3.0);
+
+    for frame_time in [16.0, 17.0, 15.0, 33.0] {
+        widgets.push_plot("Frame Time", frame_time).unwrap();
+    }
+    assert_eq!(widgets.get_plot("Frame Time").unwrap().iter().copied().collect::<Vec<_>>(), vec![17.0, 15.0, 33.0]);
+
+    let duplicated = GuiWindowBuilder::new("Settings")
+        .button("Reset")
+        .group("Advanced", true, |group| group.button("Reset"))
+        .into_widgets();
+    assert!(matches!(duplicated, Err(Error::DuplicateGuiWidget(_))));
+}
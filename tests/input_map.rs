@@ -0,0 +1,93 @@
+//! Input mapping tests
+use rust_vulkan_graphics::*;
+
+
+#[test]
+fn ron_round_trip() {
+    let mut map = InputMap::default();
+    map.bind(InputSource::Mouse(MouseButton::Left), CameraAction::Forward);
+    map.bind(InputSource::Axis {axis: 1, positive: false}, CameraAction::SpinForward);
+    map.axis_dead_zone = 0.25;
+
+    let saved = map.to_ron().unwrap();
+    assert_eq!(InputMap::from_ron(&saved).unwrap(), map);
+
+    assert!(InputMap::from_ron("(bindings: [(input: Key(W), action: Sideways)])").is_err());
+}
+
+#[test]
+fn rebinding() {
+    let mut map = InputMap::default();
+    assert_eq!(map.actions_for(InputSource::Key(VirtualKeyCode::W)).collect::<Vec<_>>(), vec![CameraAction::Forward]);
+
+    map.rebind(CameraAction::Forward, InputSource::Key(VirtualKeyCode::Z));
+    assert_eq!(map.inputs_for(CameraAction::Forward).collect::<Vec<_>>(), vec![InputSource::Key(VirtualKeyCode::Z)]);
+    assert_eq!(map.actions_for(InputSource::Key(VirtualKeyCode::W)).count(), 0);
+
+    map.unbind_input(InputSource::Key(VirtualKeyCode::Z));
+    assert_eq!(map.inputs_for(CameraAction::Forward).count(), 0);
+}
+
+#[test]
+fn axis_dead_zone() {
+    let map = InputMap::default();
+    assert_eq!(map.axis_strengths(0.05), (0.0, 0.0));
+    assert_eq!(map.axis_strengths(0.5), (0.5, 0.0));
+    assert_eq!(map.axis_strengths(-3.0), (0.0, 1.0));
+}
+
+#[test]
+fn camera_uses_bindings() {
+    let mut camera = Camera::new(Some([0.0, 0.0, 0.0]), Some([1.0, 0.0, 0.0]), Some(2.0), None);
+    camera.controllable();
+    camera.input_map.rebind(CameraAction::Forward, InputSource::Key(VirtualKeyCode::Z));
+
+    // the old key does nothing
+    camera.process_key(VirtualKeyCode::W, true);
+    camera.do_move(1.0);
+    let position: [f32; 3] = camera.position.into();
+    assert!(position.iter().all(|value| value.abs() < 1e-5));
+    camera.process_key(VirtualKeyCode::W, false);
+
+    camera.process_key(VirtualKeyCode::Z, true);
+    camera.do_move(1.0);
+    let position: [f32; 3] = camera.position.into();
+    assert!((position[0] - 2.0).abs() < 1e-5);
+
+    // half a stick moves half as far
+    camera.process_key(VirtualKeyCode::Z, false);
+    camera.input_map.bind(InputSource::Axis {axis: 1, positive: true}, CameraAction::Forward);
+    camera.process_axis(1, 0.5);
+    assert!((camera.action_strength(CameraAction::Forward) - 0.5).abs() < 1e-5);
+    camera.do_move(1.0);
+    let position: [f32; 3] = camera.position.into();
+    assert!((position[0] - 3.0).abs() < 1e-5);
+}
+
+#[test]
+fn relative_axes_last_one_frame() {
+    let mut camera = Camera::new(Some([0.0, 0.0, 0.0]), Some([1.0, 0.0, 0.0]), Some(2.0), None);
+    camera.controllable();
+    camera.input_map.bind(InputSource::Axis {axis: 0, positive: true}, CameraAction::Forward);
+
+    // movement within a frame adds up
+    camera.process_axis_motion(0, 0.25);
+    camera.process_axis_motion(0, 0.25);
+    assert!((camera.action_strength(CameraAction::Forward) - 0.5).abs() < 1e-5);
+    camera.do_move(1.0);
+    let position: [f32; 3] = camera.position.into();
+    assert!((position[0] - 1.0).abs() < 1e-5);
+
+    // and is let go of once the camera has moved
+    assert_eq!(camera.action_strength(CameraAction::Forward), 0.0);
+    camera.do_move(1.0);
+    let position: [f32; 3] = camera.position.into();
+    assert!((position[0] - 1.0).abs() < 1e-5);
+
+    // an absolute axis stays where it was put
+    camera.process_axis(0, 0.5);
+    camera.do_move(1.0);
+    camera.do_move(1.0);
+    let position: [f32; 3] = camera.position.into();
+    assert!((position[0] - 3.0).abs() < 1e-5);
+}
@@ -0,0 +1,143 @@
+//! Obj and mtl parsing tests
+mod common;
+use common::*;
+use rust_vulkan_graphics::*;
+
+fn load(name: &str, files: &[(&str, &str)]) -> Result<ObjScene, Error> {
+    let directory = temp_files(name, files);
+    load_obj(directory.join(files[0].0).to_str().unwrap())
+}
+
+const SQUARE: &str = "
+v 0 0 0
+v 1 0 0
+v 1 0 1
+v 0 0 1
+";
+
+
+const PAINT_MTL: &str = "
+# a comment
+newmtl red paint
+Kd 1 0 0
+Ka 0.5
+Ke 0.1 0.2 0.3
+Ns 10
+d 0.25
+illum 2
+Pr 0.75
+map_Kd -s 1 1 1 red.png
+map_Bump normal.png
+map_Ke glow.png
+map_Pm metal.png
+
+newmtl plain
+Ns 98
+";
+
+
+#[test]
+fn negative_indices() {
+    // negative indices count back from the last vertex read so far
+    let source = format!("{}vt 0 0\nvt 1 1\nvn 0 2 0\nf -4/-2/-1 -3/-1/-1 -2/-1/-1\nv 5 5 5\nf 1 -1 2\n", SQUARE);
+    let scene = load("negative_indices", &[("negative.obj", &source)]).unwrap();
+    let mesh = &scene.meshes[0];
+    let positions: Vec<[f32; 3]> = mesh.mesh.vertices.iter().map(|vertex| vertex.position).collect();
+    assert_eq!(positions[0..3], [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 0.0, 1.0]]);
+    assert_eq!(positions[4], [5.0, 5.0, 5.0]);
+    // v is flipped and normals from the file are normalised
+    assert_eq!(mesh.tex_coords[1].tex_coord, [1.0, 0.0]);
+    assert_close(mesh.mesh.normals[0].normal, [0.0, 1.0, 0.0]);
+
+    assert!(load("index_out_of_range", &[("bad.obj", &format!("{}f -5 1 2\n", SQUARE))]).is_err());
+    assert!(load("index_zero", &[("bad.obj", &format!("{}f 0 1 2\n", SQUARE))]).is_err());
+}
+
+#[test]
+fn fan_triangulation() {
+    let scene = load("fan_triangulation", &[("fan.obj", &format!("{}v 0.5 0 1.5\nf 1 2 3 5 4\n", SQUARE))]).unwrap();
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.mesh.indices, vec![0, 1, 2, 0, 2, 3, 0, 3, 4]);
+    // a flat polygon gets one generated normal for every corner
+    for normal in mesh.mesh.normals.iter() {
+        assert_close(normal.normal, [0.0, 1.0, 0.0]);
+    }
+}
+
+#[test]
+fn smoothing_groups_and_zero_normals() {
+    // two faces folded along an edge share their normals only when smoothed
+    let folded = "v 0 0 0\nv 1 0 0\nv 1 0 1\nv 0 1 0\n";
+    let flat = load("smoothing_off", &[("flat.obj", &format!("{}s off\nf 1 3 2\nf 1 2 4\n", folded))]).unwrap();
+    assert_eq!(flat.meshes[0].mesh.vertices.len(), 6);
+    let smooth = load("smoothing_on", &[("smooth.obj", &format!("{}s on\nf 1 3 2\nf 1 2 4\n", folded))]).unwrap();
+    assert_eq!(smooth.meshes[0].mesh.vertices.len(), 4);
+
+    let scene = load("zero_normals", &[("zero.obj", &format!("{}vn 0 0 0\nf 1//1 2//1 3//1\n", SQUARE))]).unwrap();
+    assert_eq!(scene.meshes[0].mesh.normals[0].normal, [0.0; 3]);
+}
+
+#[test]
+fn mtl_parsing() {
+    let obj = format!("mtllib paint.mtl\n{}usemtl red paint\nf 1 2 3\nusemtl missing\nf 1 3 4\n", SQUARE);
+    let directory = temp_files("mtl_parsing", &[("painted.obj", &obj), ("paint.mtl", PAINT_MTL)]);
+    let scene = load_obj(directory.join("painted.obj").to_str().unwrap()).unwrap();
+
+    let names: Vec<&str> = scene.materials.iter().map(|material| material.name.as_str()).collect();
+    assert_eq!(names, vec!["red paint", "plain", "missing"]);
+    let red = scene.material(&scene.meshes[0]).unwrap();
+    assert_eq!(red.name, "red paint");
+    assert_eq!(red.diffuse, [1.0, 0.0, 0.0]);
+    assert_eq!(red.ambient, [0.5; 3]);
+    assert_eq!(red.shininess, 10.0);
+    assert_eq!(red.dissolve, 0.25);
+    assert_eq!(red.illumination_model, Some(2));
+    assert_eq!(red.roughness, Some(0.75));
+    assert_eq!(red.metallic, None);
+    // texture options are skipped and paths are relative to the mtl file
+    assert_eq!(red.diffuse_texture, Some(directory.join("red.png")));
+    assert_eq!(red.normal_texture, Some(directory.join("normal.png")));
+    // a material that was used but never defined gets the defaults
+    assert_eq!(scene.material(&scene.meshes[1]).unwrap().diffuse, [0.8; 3]);
+
+    assert!(load_mtl(directory.join("paint.mtl").to_str().unwrap()).unwrap().len() == 2);
+    let broken = temp_files("mtl_broken", &[("broken.mtl", "Kd 1 0 0\n")]);
+    assert!(load_mtl(broken.join("broken.mtl").to_str().unwrap()).is_err());
+}
+
+#[test]
+fn converts_to_material() {
+    let directory = temp_files("mtl_material", &[("paint.mtl", PAINT_MTL)]);
+    let materials = load_mtl(directory.join("paint.mtl").to_str().unwrap()).unwrap();
+
+    let red = Material::from_obj(&materials[0]);
+    assert_eq!(red.name.as_deref(), Some("red paint"));
+    assert_eq!(red.base_colour, [1.0, 0.0, 0.0, 0.25]);
+    assert_eq!(red.alpha_mode, AlphaMode::Blend);
+    // a metallic texture is used as it is, roughness comes from Pr
+    assert_eq!(red.metallic, 1.0);
+    assert_eq!(red.roughness, 0.75);
+    assert_close(red.emissive, [0.1, 0.2, 0.3]);
+    assert_eq!(red.base_colour_texture, Some(TextureSource::File(directory.join("red.png"))));
+    assert_eq!(red.metallic_roughness_texture, Some(TextureSource::RoughnessMetallicFiles {roughness: None, metallic: Some(directory.join("metal.png"))}));
+    assert_eq!(red.normal_texture, Some(TextureSource::File(directory.join("normal.png"))));
+    assert_eq!(red.emissive_texture, Some(TextureSource::File(directory.join("glow.png"))));
+    assert_eq!(red.occlusion_texture, None);
+
+    // without the pbr values the roughness is estimated from the shininess
+    let plain = Material::from_obj(&materials[1]);
+    assert_eq!(plain.base_colour, [0.8, 0.8, 0.8, 1.0]);
+    assert_eq!(plain.alpha_mode, AlphaMode::Opaque);
+    assert_eq!(plain.metallic, 0.0);
+    assert!((plain.roughness - 0.02f32.sqrt()).abs() < EPSILON);
+    assert_eq!(plain.emissive, [0.0; 3]);
+    assert!(plain.base_colour_texture.is_none() && plain.metallic_roughness_texture.is_none() && plain.emissive_texture.is_none());
+}
+
+#[test]
+fn missing_mtllib() {
+    let obj = format!("mtllib nowhere.mtl\n{}usemtl stone\nf 1 2 3\n", SQUARE);
+    let scene = load("missing_mtllib", &[("stone.obj", &obj)]).unwrap();
+    assert_eq!(scene.materials.len(), 1);
+    assert_eq!(scene.material(&scene.meshes[0]).unwrap().name, "stone");
+}
@@ -0,0 +1,153 @@
+//! Ray picking tests
+mod common;
+use common::*;
+use maths::Vector3;
+use rust_vulkan_graphics::*;
+
+/// hits on the larger meshes build up more rounding than the other tests
+const PICK_EPSILON: f32 = 1e-3;
+
+/// a square in the x z plane, facing y
+fn quad(size: f32) -> Mesh<PositionVertex> {
+    let vertices: Vec<PositionVertex> = vec![
+        [-size, 0.0, -size].into(),
+        [size, 0.0, -size].into(),
+        [size, 0.0, size].into(),
+        [-size, 0.0, size].into(),
+    ];
+    Mesh::new(vertices, vec![0, 1, 2, 0, 2, 3])
+}
+
+/// a bumpy grid of quads, like a small terrain
+fn terrain(size: usize) -> Mesh<PositionVertex> {
+    let mut vertices: Vec<PositionVertex> = Vec::new();
+    for z in 0..=size {
+        for x in 0..=size {
+            let height = (x as f32 * 0.3).sin() * (z as f32 * 0.2).cos() * 3.0;
+            vertices.push([x as f32, height, z as f32].into());
+        }
+    }
+    let mut indices = Vec::new();
+    let row = size as u32 + 1;
+    for z in 0..size as u32 {
+        for x in 0..size as u32 {
+            let corner = z * row + x;
+            indices.extend([corner, corner + 1, corner + row + 1, corner, corner + row + 1, corner + row]);
+        }
+    }
+    Mesh::new(vertices, indices)
+}
+
+
+#[test]
+fn ray_triangle_and_aabb() {
+    let ray = Ray::new([0.2, 5.0, 0.3], [0.0, -2.0, 0.0]);
+    let (distance, barycentric) = ray.intersect_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]).unwrap();
+    assert!((distance - 5.0).abs() < PICK_EPSILON);
+    assert_close_within(barycentric, [0.5, 0.2, 0.3], PICK_EPSILON);
+
+    // outside the triangle, and behind the ray
+    assert!(ray.intersect_triangle([1.0, 0.0, 0.0], [2.0, 0.0, 0.0], [1.0, 0.0, 1.0]).is_none());
+    assert!(ray.intersect_triangle([0.0, 6.0, 0.0], [1.0, 6.0, 0.0], [0.0, 6.0, 1.0]).is_none());
+
+    let aabb = Aabb::new([-1.0, -1.0, -1.0], [1.0, 1.0, 1.0]);
+    assert!((ray.intersect_aabb(&aabb).unwrap() - 4.0).abs() < PICK_EPSILON);
+    assert_eq!(Ray::new([0.0; 3], [1.0, 0.0, 0.0]).intersect_aabb(&aabb), Some(0.0));
+    assert!(Ray::new([5.0, 0.0, 0.0], [1.0, 0.0, 0.0]).intersect_aabb(&aabb).is_none());
+    assert!(Ray::new([0.0, 5.0, 0.0], [1.0, 0.0, 0.0]).intersect_aabb(&aabb).is_none());
+}
+
+#[test]
+fn bvh_matches_every_triangle() {
+    let mesh = terrain(48);
+    let bvh = MeshBvh::new(&mesh);
+    assert_eq!(bvh.bounds(), mesh.aabb());
+
+    // a fixed spread of rays looking down and across the terrain
+    let mut seed = 12345u32;
+    let mut random = move || {
+        seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+        (seed >> 8) as f32 / (1 << 24) as f32
+    };
+    let mut hits = 0;
+    for _ in 0..500 {
+        let origin = [random() * 40.0 + 4.0, 10.0, random() * 40.0 + 4.0];
+        let direction = [random() - 0.5, -1.0, random() - 0.5];
+        let ray = Ray::new(origin, direction);
+
+        let expected = mesh.intersect_ray(&ray);
+        let found = bvh.intersect(&ray);
+        assert_eq!(expected.is_some(), found.is_some());
+        if let (Some(expected), Some(found)) = (expected, found) {
+            hits += 1;
+            assert!((expected.distance - found.distance).abs() < PICK_EPSILON);
+            assert_close_within(expected.point, found.point, PICK_EPSILON);
+
+            // the barycentric weights rebuild the hit point from the triangle's corners
+            let corners = &mesh.indices[found.triangle * 3..found.triangle * 3 + 3];
+            let mut point = [0.0; 3];
+            for (corner, weight) in corners.iter().zip(found.barycentric) {
+                let position = mesh.vertices[*corner as usize].position;
+                for axis in 0..3 {
+                    point[axis] += position[axis] * weight;
+                }
+            }
+            assert_close_within(point, found.point, PICK_EPSILON);
+        }
+    }
+    assert!(hits > 300);
+}
+
+#[test]
+fn pick_closest_mesh() {
+    let small = MeshBvh::new(&quad(1.0));
+    let large = MeshBvh::new(&quad(10.0));
+    let ray = Ray::new([0.5, 20.0, 0.5], [0.0, -1.0, 0.0]);
+
+    let meshes = [
+        (&large, Transform::from_translation([0.0, 2.0, 0.0]).matrix()),
+        (&small, Transform::from_translation([0.0, 5.0, 0.0]).with_scale([3.0, 3.0, 3.0]).matrix()),
+        (&small, Transform::from_translation([50.0, 10.0, 0.0]).matrix()),
+    ];
+    let hit = pick(&ray, meshes).unwrap();
+    assert_eq!(hit.mesh, 1);
+    assert!((hit.distance - 15.0).abs() < PICK_EPSILON);
+    assert_close_within(hit.point, [0.5, 5.0, 0.5], PICK_EPSILON);
+
+    assert!(pick(&Ray::new([0.0, 20.0, 0.0], [0.0, 1.0, 0.0]), meshes).is_none());
+}
+
+#[test]
+fn screen_rays() {
+    let viewport = [800, 600];
+    let mut camera = Camera::new(Some([1.0, 2.0, 3.0]), Some([1.0, 0.2, -0.5]), None, None);
+
+    // the centre of the screen looks straight ahead
+    let ray = Ray::from_screen([400.0, 300.0], viewport, &camera);
+    assert_close_within(ray.direction, camera.direction(), PICK_EPSILON);
+
+    // a ray through the pixel a point lands on passes through the point
+    for reverse_z in [false, true] {
+        camera.reverse_z = reverse_z;
+        let view_proj: [[f32; 4]; 4] = camera.get_view_projection_matrix(800.0 / 600.0).into();
+        let point = [6.0, 3.5, 1.0];
+        let mut clip = [0.0; 4];
+        for row in 0..4 {
+            clip[row] = (0..3).map(|column| view_proj[column][row] * point[column]).sum::<f32>() + view_proj[3][row];
+        }
+        let pixel = [(clip[0] / clip[3] + 1.0) * 400.0, (clip[1] / clip[3] + 1.0) * 300.0];
+
+        let ray = Ray::from_screen(pixel, viewport, &camera);
+        assert_close_within(ray.origin, camera.position, PICK_EPSILON);
+        let along = (Vector3::from(point) - ray.origin).dot(ray.direction);
+        assert_close_within(ray.at(along), point, PICK_EPSILON);
+    }
+
+    // orthographic rays are all parallel
+    camera.projection = Projection::Orthographic {height: 10.0, near: 0.1, far: 100.0};
+    let corner = Ray::from_screen([0.0, 0.0], viewport, &camera);
+    assert_close_within(corner.direction, camera.direction(), PICK_EPSILON);
+    let centre = Ray::from_screen([400.0, 300.0], viewport, &camera);
+    let offset = corner.origin - centre.origin;
+    assert!((offset.dot(offset).sqrt() - (25.0f32 + 25.0 * 16.0 / 9.0).sqrt()).abs() < PICK_EPSILON);
+}
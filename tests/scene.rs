@@ -0,0 +1,66 @@
+//! Scene graph tests
+use rust_vulkan_graphics::*;
+
+fn translation(node: &SceneNode) -> [f32; 3] {
+    let world: [[f32; 4]; 4] = node.world_matrix().into();
+    [world[3][0], world[3][1], world[3][2]]
+}
+
+/// root, child and grandchild in a line, with another root beside them
+fn chain() -> (Scene<()>, [NodeId; 4]) {
+    let mut scene: Scene<()> = Scene::new();
+    let root = scene.add_node(None, Transform::from_translation([1.0, 0.0, 0.0]));
+    let child = scene.add_node(Some(root), Transform::from_translation([0.0, 2.0, 0.0]).with_scale([2.0; 3]));
+    let grandchild = scene.add_node(Some(child), Transform::from_translation([0.0, 0.0, 1.0]));
+    let other = scene.add_node(None, Transform::from_translation([5.0, 0.0, 0.0]));
+    scene.update_world_matrices();
+    (scene, [root, child, grandchild, other])
+}
+
+
+#[test]
+fn changes_reach_descendants() {
+    let (mut scene, [root, child, grandchild, other]) = chain();
+    // the child's scale applies to the grandchild's translation
+    assert_eq!(translation(scene.node(grandchild)), [1.0, 2.0, 2.0]);
+
+    // a change is picked up by the next update, and reaches every descendant but no other nodes
+    scene.node_mut(root).set_transform(Transform::from_translation([-1.0, 0.0, 0.0]));
+    assert_eq!(translation(scene.node(grandchild)), [1.0, 2.0, 2.0]);
+    scene.update_world_matrices();
+    assert_eq!(translation(scene.node(child)), [-1.0, 2.0, 0.0]);
+    assert_eq!(translation(scene.node(grandchild)), [-1.0, 2.0, 2.0]);
+    assert_eq!(translation(scene.node(other)), [5.0, 0.0, 0.0]);
+
+    // editing a transform in place marks the node too
+    scene.node_mut(child).transform_mut().translation = [0.0, 4.0, 0.0];
+    scene.update_world_matrices();
+    assert_eq!(translation(scene.node(root)), [-1.0, 0.0, 0.0]);
+    assert_eq!(translation(scene.node(grandchild)), [-1.0, 4.0, 2.0]);
+
+    // a moved node takes its descendants with it
+    scene.set_parent(child, Some(other)).unwrap();
+    scene.update_world_matrices();
+    assert_eq!(translation(scene.node(grandchild)), [5.0, 4.0, 2.0]);
+    assert!(scene.node(root).children().is_empty());
+    assert_eq!(scene.node(other).children(), &[child]);
+    assert_eq!(scene.node(child).parent(), Some(other));
+}
+
+#[test]
+fn set_parent_rejects_cycles() {
+    let (mut scene, [root, child, grandchild, other]) = chain();
+    assert!(matches!(scene.set_parent(root, Some(grandchild)), Err(Error::SceneCycle)));
+    assert!(matches!(scene.set_parent(child, Some(child)), Err(Error::SceneCycle)));
+
+    // a rejected move leaves the hierarchy as it was
+    assert_eq!(scene.roots(), &[root, other]);
+    assert_eq!(scene.node(root).parent(), None);
+    assert_eq!(scene.node(child).children(), &[grandchild]);
+
+    // moving a node to the top is always allowed
+    scene.set_parent(grandchild, None).unwrap();
+    assert_eq!(scene.roots(), &[root, other, grandchild]);
+    scene.update_world_matrices();
+    assert_eq!(translation(scene.node(grandchild)), [0.0, 0.0, 1.0]);
+}
@@ -0,0 +1,98 @@
+//! Shadow cascade fitting tests
+mod common;
+use common::*;
+use maths::Vector3;
+use rust_vulkan_graphics::*;
+
+const ASPECT_RATIO: f32 = 1.5;
+const LIGHT_DIRECTION: [f32; 3] = [0.3, 1.0, 0.2];
+
+fn camera() -> Camera {
+    Camera::new(Some([3.0, -2.0, 1.0]), Some([1.0, 0.3, 0.2]), None, None)
+}
+
+/// where a world position lands in a light matrix, x and y from -1 to 1 and depth from 0 to 1 inside the map
+fn light_space(matrix: &[[f32; 4]; 4], point: Vector3) -> [f32; 3] {
+    let point: [f32; 3] = point.into();
+    let mut out = [0.0; 3];
+    for row in 0..3 {
+        out[row] = matrix[0][row] * point[0] + matrix[1][row] * point[1] + matrix[2][row] * point[2] + matrix[3][row];
+    }
+    out
+}
+
+
+#[test]
+fn splits_cover_the_view() {
+    let camera = camera();
+    for split_lambda in [0.0, 0.5, 1.0] {
+        let config = ShadowMapConfig {cascades: 4, split_lambda, max_distance: 50.0, ..Default::default()};
+        let cascades = ShadowCascades::fit(&config, &camera, ASPECT_RATIO, LIGHT_DIRECTION);
+        assert_eq!(cascades.light_matrices().len(), 4);
+
+        // each split is further than the last and the final one reaches the shadow distance
+        assert!(cascades.splits[0] > camera.projection.near());
+        assert!(cascades.splits.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", cascades.splits);
+        assert!((cascades.splits[3] - 50.0).abs() < 1e-3);
+
+        // every corner of each slice lands inside its cascade's map
+        let forward = camera.direction();
+        let right = camera.up().cross(forward).normalised();
+        let up = forward.cross(right);
+        let mut near = camera.projection.near();
+        for (cascade, far) in cascades.splits.iter().enumerate() {
+            for distance in [near, *far] {
+                let half_height = camera.projection.half_height_at(distance);
+                let half_width = half_height * ASPECT_RATIO;
+                for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+                    let corner = camera.position + forward * distance + right * (x * half_width) + up * (y * half_height);
+                    let [x, y, depth] = light_space(&cascades.light_matrices[cascade], corner);
+                    assert!(x.abs() <= 1.01 && y.abs() <= 1.01 && (-1e-3..=1.001).contains(&depth), "cascade {} misses {:?}", cascade, [x, y, depth]);
+                }
+            }
+            near = *far;
+        }
+    }
+
+    // an even split spaces the cascades equally
+    let config = ShadowMapConfig {cascades: 4, split_lambda: 0.0, max_distance: 40.01, ..Default::default()};
+    let splits = ShadowCascades::fit(&config, &camera, ASPECT_RATIO, LIGHT_DIRECTION).splits;
+    for (split, expected) in splits.iter().zip([10.01, 20.01, 30.01, 40.01]) {
+        assert!((split - expected).abs() < 1e-3, "{:?}", splits);
+    }
+
+    // too many cascades are clamped
+    let config = ShadowMapConfig {cascades: 10, ..Default::default()};
+    assert_eq!(ShadowCascades::fit(&config, &camera, ASPECT_RATIO, LIGHT_DIRECTION).count, MAX_CASCADES as u32);
+}
+
+#[test]
+fn cascades_are_stable() {
+    let config = ShadowMapConfig {cascades: 3, ..Default::default()};
+    let resolution = config.resolution as f32;
+    let mut camera = camera();
+    let first = ShadowCascades::fit(&config, &camera, ASPECT_RATIO, LIGHT_DIRECTION);
+
+    // the size of each map only depends on the camera's projection, so turning leaves it alone
+    let scale = |matrix: &[[f32; 4]; 4]| (matrix[0][0].powi(2) + matrix[1][0].powi(2) + matrix[2][0].powi(2)).sqrt();
+    camera.rotate_yaw(1.3);
+    camera.rotate_pitch(0.4);
+    let turned = ShadowCascades::fit(&config, &camera, ASPECT_RATIO, LIGHT_DIRECTION);
+    for cascade in 0..3 {
+        let (before, after) = (scale(&first.light_matrices[cascade]), scale(&turned.light_matrices[cascade]));
+        assert!((before - after).abs() / before < 1e-4, "cascade {} changed size", cascade);
+    }
+
+    // as the camera moves the maps only ever shift by whole texels
+    for step in 1..20 {
+        camera.position += Vector3::new(0.013, 0.007, -0.011) * step as f32;
+        let moved = ShadowCascades::fit(&config, &camera, ASPECT_RATIO, LIGHT_DIRECTION);
+        for matrix in moved.light_matrices() {
+            for axis in 0..2 {
+                // the offset is -2 * texels from the origin / resolution
+                let texels = matrix[3][axis] * resolution / 2.0;
+                assert!((texels - texels.round()).abs() < 1e-2, "offset of {} texels", texels);
+            }
+        }
+    }
+}